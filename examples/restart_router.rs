@@ -4,8 +4,7 @@
 //! 1. Stop router
 //! 2. Wait 10 seconds
 //! 3. Start router
-//! 4. Wait 1 second
-//! 5. Verify with get-version
+//! 4. Wait until the router responds to get-version
 //!
 //! # Usage
 //!
@@ -73,21 +72,19 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
-    // Step 4: Wait 1 second
-    println!("Step 4: Waiting 1 second for router initialization...");
-    sleep(Duration::from_secs(1)).await;
-    println!("✓ Wait completed\n");
-
-    // Step 5: Verify with get-version
-    println!("Step 5: Verifying router status with get-version...");
-    match bjig.router().get_version().await {
+    // Step 4: Wait until the router responds
+    println!("Step 4: Waiting for router initialization...");
+    match bjig
+        .router()
+        .wait_until_ready(Duration::from_millis(500), Duration::from_secs(10))
+        .await
+    {
         Ok(version) => {
             println!("✓ Router is operational!");
             println!("  Version: {}.{}.{}", version.major, version.minor, version.build);
         }
         Err(e) => {
-            println!("✗ Failed to get version: {}", e);
-            println!("  Router may need more time to initialize");
+            println!("✗ Router did not become ready: {}", e);
             return Err(e.into());
         }
     }
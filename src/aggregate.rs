@@ -0,0 +1,347 @@
+//! In-memory statistics over monitor uplinks
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{BjigError, MonitorEvent, Result};
+
+/// Default EMA smoothing factor
+pub const DEFAULT_EMA_ALPHA: f64 = 0.2;
+
+/// Statistics of a numeric uplink field for one module
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UplinkStats {
+    pub count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Exponential moving average (the first value seeds the average)
+    pub ema: f64,
+    /// Uplinks per second between the first and last recorded uplink
+    ///
+    /// `None` until two uplinks have been recorded at different instants.
+    pub rate_per_sec: Option<f64>,
+}
+
+/// Running accumulator for one module
+#[derive(Debug, Clone, Copy)]
+struct Accumulator {
+    stats: UplinkStats,
+    first_seen: Instant,
+    last_seen: Instant,
+}
+
+/// Aggregates a numeric uplink field per module ID
+///
+/// Feed events with `record`, e.g. from `MonitorCommand::start_with_filter`.
+/// Events that are not uplinks or lack a numeric value at the pointer are
+/// ignored.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::{BjigController, MonitorFilter, UplinkAggregator};
+///
+/// let bjig = BjigController::from_env()?;
+/// let mut lux = UplinkAggregator::new("/lux");
+///
+/// bjig.monitor().start_with_filter(MonitorFilter::new(), |event| {
+///     lux.record(event);
+///     for (module_id, stats) in lux.snapshot() {
+///         println!("{}: mean {:.1}, ema {:.1}", module_id, stats.mean, stats.ema);
+///     }
+///     Ok(true)
+/// }).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UplinkAggregator {
+    pointer: String,
+    alpha: f64,
+    modules: HashMap<String, Accumulator>,
+}
+
+impl UplinkAggregator {
+    /// Create aggregator for the field at `pointer` (JSON pointer into the uplink)
+    pub fn new(pointer: impl Into<String>) -> Self {
+        Self {
+            pointer: pointer.into(),
+            alpha: DEFAULT_EMA_ALPHA,
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Set EMA smoothing factor in `(0, 1]` (higher reacts faster)
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` if `alpha` is outside `(0, 1]`
+    /// or NaN.
+    pub fn with_alpha(mut self, alpha: f64) -> Result<Self> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(BjigError::InvalidParameter(format!(
+                "EMA alpha {} out of range (0, 1]",
+                alpha
+            )));
+        }
+        self.alpha = alpha;
+        Ok(self)
+    }
+
+    /// Record an event received now
+    ///
+    /// Returns `true` if the event contributed a value.
+    pub fn record(&mut self, event: &MonitorEvent) -> bool {
+        self.record_at(event, Instant::now())
+    }
+
+    /// Record an event received at `at`
+    pub fn record_at(&mut self, event: &MonitorEvent, at: Instant) -> bool {
+        let (module_id, value) = match (event.module_id(), self.value(event)) {
+            (Some(module_id), Some(value)) => (module_id, value),
+            _ => return false,
+        };
+
+        match self.modules.get_mut(module_id) {
+            Some(acc) => {
+                let stats = &mut acc.stats;
+                stats.count += 1;
+                stats.min = stats.min.min(value);
+                stats.max = stats.max.max(value);
+                stats.mean += (value - stats.mean) / stats.count as f64;
+                stats.ema += self.alpha * (value - stats.ema);
+
+                acc.last_seen = at;
+                let elapsed = acc.last_seen.duration_since(acc.first_seen).as_secs_f64();
+                if elapsed > 0.0 {
+                    stats.rate_per_sec = Some((stats.count - 1) as f64 / elapsed);
+                }
+            }
+            None => {
+                let stats = UplinkStats {
+                    count: 1,
+                    min: value,
+                    max: value,
+                    mean: value,
+                    ema: value,
+                    rate_per_sec: None,
+                };
+                self.modules.insert(
+                    module_id.to_string(),
+                    Accumulator {
+                        stats,
+                        first_seen: at,
+                        last_seen: at,
+                    },
+                );
+            }
+        }
+
+        true
+    }
+
+    /// Get current statistics keyed by module ID
+    pub fn snapshot(&self) -> HashMap<String, UplinkStats> {
+        self.modules
+            .iter()
+            .map(|(id, acc)| (id.clone(), acc.stats))
+            .collect()
+    }
+
+    /// Forget all recorded values
+    pub fn reset(&mut self) {
+        self.modules.clear();
+    }
+
+    /// Look up the numeric value at the pointer
+    fn value(&self, event: &MonitorEvent) -> Option<f64> {
+        let uplink = event.as_uplink()?;
+        let rest = self.pointer.strip_prefix('/')?;
+        let (key, nested) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, ""),
+        };
+        let key = key.replace("~1", "/").replace("~0", "~");
+
+        uplink.extra.get(&key)?.pointer(nested)?.as_f64()
+    }
+}
+
+/// Estimates each module's reporting interval from uplink arrival times
+///
+/// The estimate is the median time between consecutive uplinks, so a
+/// missed or duplicated uplink does not skew it. Modules with fewer than
+/// two uplinks have no estimate.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::{BjigController, IntervalEstimator, MonitorFilter};
+///
+/// let bjig = BjigController::from_env()?;
+/// let mut intervals = IntervalEstimator::new();
+///
+/// bjig.monitor().start_with_filter(MonitorFilter::new(), |event| {
+///     intervals.record(event);
+///     for (module_id, interval) in intervals.estimates() {
+///         println!("{}: every {:?}", module_id, interval);
+///     }
+///     Ok(true)
+/// }).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IntervalEstimator {
+    arrivals: HashMap<String, Vec<Instant>>,
+}
+
+impl IntervalEstimator {
+    /// Create empty estimator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an event received now
+    ///
+    /// Returns `true` if the event is an uplink.
+    pub fn record(&mut self, event: &MonitorEvent) -> bool {
+        self.record_at(event, Instant::now())
+    }
+
+    /// Record an event received at `at`
+    pub fn record_at(&mut self, event: &MonitorEvent, at: Instant) -> bool {
+        match event.module_id() {
+            Some(module_id) => {
+                self.arrivals.entry(module_id.to_string()).or_default().push(at);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the median inter-arrival time keyed by module ID
+    ///
+    /// Modules with fewer than two recorded uplinks are excluded.
+    pub fn estimates(&self) -> HashMap<String, Duration> {
+        self.arrivals
+            .iter()
+            .filter_map(|(id, arrivals)| Some((id.clone(), median_gap(arrivals)?)))
+            .collect()
+    }
+
+    /// Forget all recorded arrivals
+    pub fn reset(&mut self) {
+        self.arrivals.clear();
+    }
+}
+
+/// Median of the gaps between consecutive arrivals
+fn median_gap(arrivals: &[Instant]) -> Option<Duration> {
+    let mut gaps: Vec<Duration> = arrivals
+        .windows(2)
+        .map(|w| w[1].saturating_duration_since(w[0]))
+        .collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    gaps.sort_unstable();
+
+    let mid = gaps.len() / 2;
+    Some(if gaps.len().is_multiple_of(2) {
+        (gaps[mid - 1] + gaps[mid]) / 2
+    } else {
+        gaps[mid]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uplink(module_id: &str, lux: f64) -> MonitorEvent {
+        MonitorEvent::parse(&format!(
+            r#"{{"sensor_id":"0121","module_id":"{}","lux":{},"meta":{{"battery":{}}}}}"#,
+            module_id, lux, lux / 10.0
+        ))
+    }
+
+    #[test]
+    fn test_aggregate_known_sequence() {
+        let mut agg = UplinkAggregator::new("/lux").with_alpha(0.5).unwrap();
+        let start = Instant::now();
+
+        for (i, lux) in [100.0, 200.0, 300.0, 0.0].into_iter().enumerate() {
+            assert!(agg.record_at(&uplink("A", lux), start + Duration::from_secs(i as u64 * 10)));
+        }
+        assert!(agg.record_at(&uplink("B", 42.0), start));
+        assert!(!agg.record_at(&MonitorEvent::parse("noise"), start));
+
+        let snapshot = agg.snapshot();
+        assert_eq!(snapshot.len(), 2);
+
+        let a = snapshot["A"];
+        assert_eq!(a.count, 4);
+        assert_eq!(a.min, 0.0);
+        assert_eq!(a.max, 300.0);
+        assert_eq!(a.mean, 150.0);
+        // 100 -> 150 -> 225 -> 112.5
+        assert_eq!(a.ema, 112.5);
+        assert_eq!(a.rate_per_sec, Some(0.1));
+
+        let b = snapshot["B"];
+        assert_eq!((b.count, b.mean, b.ema, b.rate_per_sec), (1, 42.0, 42.0, None));
+    }
+
+    #[test]
+    fn test_alpha_range() {
+        assert!(UplinkAggregator::new("/lux").with_alpha(1.0).is_ok());
+        for alpha in [0.0, -0.5, 1.5, f64::NAN, f64::INFINITY] {
+            assert!(
+                matches!(UplinkAggregator::new("/lux").with_alpha(alpha), Err(BjigError::InvalidParameter(_))),
+                "{}",
+                alpha
+            );
+        }
+    }
+
+    #[test]
+    fn test_aggregate_nested_pointer() {
+        let mut agg = UplinkAggregator::new("/meta/battery");
+        agg.record(&uplink("A", 30.0));
+        agg.record(&uplink("A", 50.0));
+        assert_eq!(agg.snapshot()["A"].mean, 4.0);
+
+        let mut missing = UplinkAggregator::new("/temperature");
+        assert!(!missing.record(&uplink("A", 30.0)));
+        assert!(missing.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_interval_estimate_median() {
+        let mut intervals = IntervalEstimator::new();
+        let start = Instant::now();
+
+        for i in 0..5 {
+            assert!(intervals.record_at(&uplink("A", 1.0), start + Duration::from_secs(i * 60)));
+        }
+        // One late uplink does not move the median
+        for secs in [0, 30, 60, 150] {
+            intervals.record_at(&uplink("B", 1.0), start + Duration::from_secs(secs));
+        }
+        intervals.record_at(&uplink("C", 1.0), start);
+        assert!(!intervals.record_at(&MonitorEvent::parse("noise"), start));
+
+        let estimates = intervals.estimates();
+        assert_eq!(estimates.len(), 2);
+        assert_eq!(estimates["A"], Duration::from_secs(60));
+        assert_eq!(estimates["B"], Duration::from_secs(30));
+
+        intervals.reset();
+        assert!(intervals.estimates().is_empty());
+    }
+}
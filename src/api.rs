@@ -0,0 +1,267 @@
+//! Object-safe traits over the command interfaces
+//!
+//! Code that consumes a router or module can depend on `&dyn RouterApi` /
+//! `&dyn ModuleApi` (or a boxed trait object) instead of the concrete
+//! command structs, and be tested against a fake implementation. Methods
+//! take owned, `Send` arguments and return boxed `Send` futures, so the
+//! traits stay object-safe without an async-trait macro.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use crate::commands::{ModuleCommands, RouterCommands};
+use crate::types::*;
+
+/// Future returned by `RouterApi` and `ModuleApi` methods
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Router operations (implemented by `RouterCommands`)
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::{BjigController, RouterApi};
+///
+/// async fn report(router: &dyn RouterApi) -> bjig_controller::Result<String> {
+///     let version = router.get_version().await?;
+///     let modules = router.get_module_id(None).await?;
+///     Ok(format!("{} ({} modules)", version.version, modules.module_count))
+/// }
+///
+/// let bjig = BjigController::from_env()?;
+/// println!("{}", report(&bjig.router()).await?);
+/// # Ok(())
+/// # }
+/// ```
+pub trait RouterApi: Send + Sync {
+    /// See `RouterCommands::start`
+    fn start(&self) -> ApiFuture<'_, StartResult>;
+
+    /// See `RouterCommands::stop`
+    fn stop(&self) -> ApiFuture<'_, StopResult>;
+
+    /// See `RouterCommands::get_version`
+    fn get_version(&self) -> ApiFuture<'_, Version>;
+
+    /// See `RouterCommands::status`
+    fn status(&self) -> ApiFuture<'_, RouterStatus>;
+
+    /// See `RouterCommands::get_module_id`
+    fn get_module_id(&self, index: Option<u8>) -> ApiFuture<'_, ModuleIdList>;
+
+    /// See `RouterCommands::remove_module_id`
+    fn remove_module_id(&self, index: Option<u8>) -> ApiFuture<'_, RemoveResult>;
+
+    /// See `RouterCommands::get_scan_mode`
+    fn get_scan_mode(&self) -> ApiFuture<'_, ScanMode>;
+
+    /// See `RouterCommands::set_scan_mode`
+    fn set_scan_mode(&self, mode: ScanModeType) -> ApiFuture<'_, SetScanModeResult>;
+
+    /// See `RouterCommands::keep_alive`
+    fn keep_alive(&self) -> ApiFuture<'_, KeepAliveResult>;
+
+    /// See `RouterCommands::dfu`
+    fn dfu(&self, firmware_path: PathBuf) -> ApiFuture<'_, DfuResult>;
+}
+
+/// Module operations (implemented by `ModuleCommands`)
+pub trait ModuleApi: Send + Sync {
+    /// See `ModuleCommands::instant_uplink`
+    fn instant_uplink(&self) -> ApiFuture<'_, UplinkResult>;
+
+    /// See `ModuleCommands::get_parameter`
+    fn get_parameter(&self) -> ApiFuture<'_, serde_json::Value>;
+
+    /// See `ModuleCommands::set_parameter`
+    fn set_parameter(&self, data: serde_json::Value) -> ApiFuture<'_, SetParameterResult>;
+
+    /// See `ModuleCommands::restart`
+    fn restart(&self) -> ApiFuture<'_, RestartResult>;
+
+    /// See `ModuleCommands::control`
+    fn control(&self, data: serde_json::Value) -> ApiFuture<'_, ControlResult>;
+
+    /// See `ModuleCommands::dfu`
+    fn dfu(&self, firmware_path: PathBuf) -> ApiFuture<'_, DfuResult>;
+}
+
+impl RouterApi for RouterCommands<'_> {
+    fn start(&self) -> ApiFuture<'_, StartResult> {
+        Box::pin(RouterCommands::start(self))
+    }
+
+    fn stop(&self) -> ApiFuture<'_, StopResult> {
+        Box::pin(RouterCommands::stop(self))
+    }
+
+    fn get_version(&self) -> ApiFuture<'_, Version> {
+        Box::pin(RouterCommands::get_version(self))
+    }
+
+    fn status(&self) -> ApiFuture<'_, RouterStatus> {
+        Box::pin(RouterCommands::status(self))
+    }
+
+    fn get_module_id(&self, index: Option<u8>) -> ApiFuture<'_, ModuleIdList> {
+        Box::pin(RouterCommands::get_module_id(self, index))
+    }
+
+    fn remove_module_id(&self, index: Option<u8>) -> ApiFuture<'_, RemoveResult> {
+        Box::pin(RouterCommands::remove_module_id(self, index))
+    }
+
+    fn get_scan_mode(&self) -> ApiFuture<'_, ScanMode> {
+        Box::pin(RouterCommands::get_scan_mode(self))
+    }
+
+    fn set_scan_mode(&self, mode: ScanModeType) -> ApiFuture<'_, SetScanModeResult> {
+        Box::pin(RouterCommands::set_scan_mode(self, mode))
+    }
+
+    fn keep_alive(&self) -> ApiFuture<'_, KeepAliveResult> {
+        Box::pin(RouterCommands::keep_alive(self))
+    }
+
+    fn dfu(&self, firmware_path: PathBuf) -> ApiFuture<'_, DfuResult> {
+        Box::pin(async move { RouterCommands::dfu(self, firmware_path).await })
+    }
+}
+
+impl ModuleApi for ModuleCommands<'_> {
+    fn instant_uplink(&self) -> ApiFuture<'_, UplinkResult> {
+        Box::pin(ModuleCommands::instant_uplink(self))
+    }
+
+    fn get_parameter(&self) -> ApiFuture<'_, serde_json::Value> {
+        Box::pin(ModuleCommands::get_parameter(self))
+    }
+
+    fn set_parameter(&self, data: serde_json::Value) -> ApiFuture<'_, SetParameterResult> {
+        Box::pin(async move { ModuleCommands::set_parameter(self, &data).await })
+    }
+
+    fn restart(&self) -> ApiFuture<'_, RestartResult> {
+        Box::pin(ModuleCommands::restart(self))
+    }
+
+    fn control(&self, data: serde_json::Value) -> ApiFuture<'_, ControlResult> {
+        Box::pin(async move { ModuleCommands::control(self, &data).await })
+    }
+
+    fn dfu(&self, firmware_path: PathBuf) -> ApiFuture<'_, DfuResult> {
+        Box::pin(async move { ModuleCommands::dfu(self, firmware_path).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+    use crate::BjigController;
+    use std::sync::Mutex;
+
+    /// Fake router recording which methods were called
+    #[derive(Default)]
+    struct FakeRouter {
+        calls: Mutex<Vec<&'static str>>,
+    }
+
+    impl FakeRouter {
+        fn record<T: Send + 'static>(&self, name: &'static str, value: T) -> ApiFuture<'_, T> {
+            self.calls.lock().unwrap().push(name);
+            Box::pin(async move { Ok(value) })
+        }
+    }
+
+    impl RouterApi for FakeRouter {
+        fn start(&self) -> ApiFuture<'_, StartResult> {
+            self.record("start", StartResult { result: "success".into(), message: "started".into() })
+        }
+
+        fn stop(&self) -> ApiFuture<'_, StopResult> {
+            self.record("stop", StopResult { result: "success".into(), message: "stopped".into() })
+        }
+
+        fn get_version(&self) -> ApiFuture<'_, Version> {
+            let version = serde_json::from_str(r#"{"major":9,"minor":9,"build":9,"version":"9.9.9"}"#);
+            self.record("get_version", version.unwrap())
+        }
+
+        fn status(&self) -> ApiFuture<'_, RouterStatus> {
+            Box::pin(async { Err(BjigError::CommandFailed("not faked".into())) })
+        }
+
+        fn get_module_id(&self, _index: Option<u8>) -> ApiFuture<'_, ModuleIdList> {
+            let list = serde_json::from_str(r#"{"module_count":2,"modules":[]}"#);
+            self.record("get_module_id", list.unwrap())
+        }
+
+        fn remove_module_id(&self, _index: Option<u8>) -> ApiFuture<'_, RemoveResult> {
+            Box::pin(async { Err(BjigError::CommandFailed("not faked".into())) })
+        }
+
+        fn get_scan_mode(&self) -> ApiFuture<'_, ScanMode> {
+            Box::pin(async { Err(BjigError::CommandFailed("not faked".into())) })
+        }
+
+        fn set_scan_mode(&self, _mode: ScanModeType) -> ApiFuture<'_, SetScanModeResult> {
+            Box::pin(async { Err(BjigError::CommandFailed("not faked".into())) })
+        }
+
+        fn keep_alive(&self) -> ApiFuture<'_, KeepAliveResult> {
+            Box::pin(async { Err(BjigError::CommandFailed("not faked".into())) })
+        }
+
+        fn dfu(&self, _firmware_path: PathBuf) -> ApiFuture<'_, DfuResult> {
+            Box::pin(async { Err(BjigError::CommandFailed("not faked".into())) })
+        }
+    }
+
+    /// Code under test that only depends on the trait
+    async fn describe(router: &dyn RouterApi) -> Result<String> {
+        let version = router.get_version().await?;
+        let modules = router.get_module_id(None).await?;
+        Ok(format!("{} with {} modules", version.version, modules.module_count))
+    }
+
+    #[tokio::test]
+    async fn test_router_api_fake_and_real() {
+        let fake: Box<dyn RouterApi> = Box::new(FakeRouter::default());
+        assert_eq!(describe(fake.as_ref()).await.unwrap(), "9.9.9 with 2 modules");
+
+        let fake = FakeRouter::default();
+        describe(&fake).await.unwrap();
+        assert_eq!(*fake.calls.lock().unwrap(), vec!["get_version", "get_module_id"]);
+
+        let mock = MockRunner::new()
+            .on(&["router", "get-version"], r#"{"major":1,"minor":2,"build":3,"version":"1.2.3"}"#)
+            .on(&["router", "get-module-id"], r#"{"module_count":1,"modules":[]}"#);
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock)
+            .build()
+            .unwrap();
+        let real: Box<dyn RouterApi + '_> = Box::new(bjig.router());
+        assert_eq!(describe(real.as_ref()).await.unwrap(), "1.2.3 with 1 modules");
+    }
+
+    #[tokio::test]
+    async fn test_module_api_owned_arguments() {
+        let mock = MockRunner::new().on(
+            &["module", "control"],
+            r#"{"result":"success","message":"ok"}"#,
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock)
+            .build()
+            .unwrap();
+        let module: Box<dyn ModuleApi + '_> = Box::new(bjig.module("0121", "2468800203400004"));
+
+        let handle = module.control(serde_json::json!({"led": "on"}));
+        assert!(handle.await.unwrap().is_success());
+    }
+}
@@ -0,0 +1,265 @@
+//! Batch command implementations for several modules
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::controller::BjigController;
+use crate::types::*;
+
+/// Per-module results of a batch, or the first failure when fail-fast is on
+///
+/// The error holds the ID of the module that failed.
+pub type BatchResult<T> = std::result::Result<HashMap<String, Result<T>>, (String, BjigError)>;
+
+/// Commands applied to several modules of the same sensor type
+///
+/// Modules are addressed one after another through the controller's
+/// command queue, so the serial port is never used concurrently. By
+/// default a failure for one module does not stop the batch; see
+/// `with_fail_fast` to stop at the first error instead.
+pub struct BatchModuleCommands<'a> {
+    controller: &'a BjigController,
+    sensor_id: String,
+    module_ids: Vec<String>,
+    delay: Duration,
+    fail_fast: bool,
+}
+
+impl<'a> BatchModuleCommands<'a> {
+    /// Create new batch commands interface
+    pub(crate) fn new(controller: &'a BjigController, sensor_id: &str, module_ids: Vec<String>) -> Self {
+        Self {
+            controller,
+            sensor_id: sensor_id.to_string(),
+            module_ids,
+            delay: Duration::ZERO,
+            fail_fast: false,
+        }
+    }
+
+    /// Set delay between consecutive module commands (default: none)
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Stop the batch at the first failing module (default: false)
+    ///
+    /// When enabled, the remaining modules are not addressed and the batch
+    /// returns `Err((module_id, error))` for the module that failed. When
+    /// disabled, the batch always returns `Ok` with every module's result.
+    pub fn with_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Request an instant uplink from every module
+    ///
+    /// Returns the result of `ModuleCommands::instant_uplink` keyed by module ID.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let uplinks = bjig
+    ///     .modules("0121", ["2468800203400004", "2468800203400005"])
+    ///     .with_fail_fast(true)
+    ///     .instant_uplink_all()
+    ///     .await;
+    ///
+    /// if let Err((module_id, e)) = uplinks {
+    ///     eprintln!("Aborted at {}: {}", module_id, e);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn instant_uplink_all(&self) -> BatchResult<UplinkResult> {
+        let controller = self.controller;
+        let sensor_id = self.sensor_id.as_str();
+        self.for_each_module(|module_id| async move {
+            controller.module(sensor_id, &module_id).instant_uplink().await
+        })
+        .await
+    }
+
+    /// Get parameters of every module
+    ///
+    /// Returns the result of `ModuleCommands::get_parameter` keyed by module ID.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use std::time::Duration;
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let parameters = bjig
+    ///     .modules("0121", ["2468800203400004", "2468800203400005"])
+    ///     .with_delay(Duration::from_millis(500))
+    ///     .get_parameters_all()
+    ///     .await
+    ///     .map_err(|(_, e)| e)?;
+    ///
+    /// for (module_id, result) in &parameters {
+    ///     match result {
+    ///         Ok(params) => println!("{}: {}", module_id, params),
+    ///         Err(e) => eprintln!("{}: {}", module_id, e),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_parameters_all(&self) -> BatchResult<serde_json::Value> {
+        let controller = self.controller;
+        let sensor_id = self.sensor_id.as_str();
+        self.for_each_module(|module_id| async move {
+            controller.module(sensor_id, &module_id).get_parameter().await
+        })
+        .await
+    }
+
+    /// Run `command` for each module in order, honoring delay and fail-fast
+    async fn for_each_module<T, F, Fut>(&self, command: F) -> BatchResult<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut results = HashMap::new();
+
+        for (i, module_id) in self.module_ids.iter().enumerate() {
+            if i > 0 && !self.delay.is_zero() {
+                tokio::time::sleep(self.delay).await;
+            }
+
+            match command(module_id.clone()).await {
+                Err(e) if self.fail_fast => return Err((module_id.clone(), e)),
+                result => {
+                    results.insert(module_id.clone(), result);
+                }
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_get_parameters_all_mixed_results() {
+        let mock = Arc::new(
+            MockRunner::new()
+                .on(&["--module-id", "AAAA000000000001"], r#"{"interval":60}"#)
+                .on_failure(&["--module-id", "AAAA000000000002"], "Response timeout")
+                .on(&["--module-id", "AAAA000000000003"], r#"{"interval":300}"#),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .baud(38400)
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+
+        let results = bjig
+            .modules("0121", ["AAAA000000000001", "AAAA000000000002", "AAAA000000000003"])
+            .with_delay(Duration::from_millis(1))
+            .get_parameters_all()
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results["AAAA000000000001"].as_ref().unwrap()["interval"], 60);
+        assert!(matches!(results["AAAA000000000002"], Err(BjigError::CommandFailed(_))));
+        assert_eq!(results["AAAA000000000003"].as_ref().unwrap()["interval"], 300);
+
+        let calls = mock.calls();
+        assert_eq!(calls.len(), 3);
+        assert!(calls
+            .iter()
+            .all(|argv| argv.windows(2).any(|w| w == ["module", "get-parameter"])));
+        assert!(calls[1].contains(&"AAAA000000000002".to_string()));
+    }
+
+    fn mixed_controller() -> (BjigController, Arc<MockRunner>) {
+        let mock = Arc::new(
+            MockRunner::new()
+                .on(&["--module-id", "AAAA000000000001"], r#"{"sensor_id":"0121","module_id":"AAAA000000000001","lux":10}"#)
+                .on_failure(&["--module-id", "AAAA000000000002"], "Response timeout")
+                .on(&["--module-id", "AAAA000000000003"], r#"{"sensor_id":"0121","module_id":"AAAA000000000003","lux":30}"#),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .baud(38400)
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+        (bjig, mock)
+    }
+
+    const MODULES: [&str; 3] = ["AAAA000000000001", "AAAA000000000002", "AAAA000000000003"];
+
+    #[tokio::test]
+    async fn test_instant_uplink_all_collects_by_default() {
+        let (bjig, mock) = mixed_controller();
+        let results = bjig.modules("0121", MODULES).instant_uplink_all().await.unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results["AAAA000000000001"].is_ok());
+        assert!(matches!(results["AAAA000000000002"], Err(BjigError::CommandFailed(_))));
+        assert!(results["AAAA000000000003"].is_ok());
+        assert_eq!(mock.calls().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_stops_at_first_error() {
+        let (bjig, mock) = mixed_controller();
+        let result = bjig
+            .modules("0121", MODULES)
+            .with_fail_fast(true)
+            .instant_uplink_all()
+            .await;
+
+        let (module_id, e) = result.unwrap_err();
+        assert_eq!(module_id, "AAAA000000000002");
+        assert!(matches!(e, BjigError::CommandFailed(_)));
+        assert_eq!(mock.calls().len(), 2);
+
+        let (bjig, mock) = mixed_controller();
+        let result = bjig
+            .modules("0121", MODULES)
+            .with_fail_fast(true)
+            .get_parameters_all()
+            .await;
+        assert_eq!(result.unwrap_err().0, "AAAA000000000002");
+        assert_eq!(mock.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_returns_all_results_on_success() {
+        let mock = Arc::new(MockRunner::new().on(&["module", "get-parameter"], r#"{"interval":60}"#));
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .baud(38400)
+            .runner(mock)
+            .build()
+            .unwrap();
+
+        let results = bjig
+            .modules("0121", ["AAAA000000000001", "AAAA000000000003"])
+            .with_fail_fast(true)
+            .get_parameters_all()
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.values().all(|r| r.is_ok()));
+    }
+}
@@ -1,9 +1,11 @@
 //! Command implementations
 
+pub mod batch;
 pub mod router;
 pub mod module;
 pub mod monitor;
 
+pub use batch::{BatchModuleCommands, BatchResult};
 pub use router::RouterCommands;
 pub use module::ModuleCommands;
 pub use monitor::MonitorCommand;
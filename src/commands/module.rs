@@ -1,11 +1,22 @@
 //! Module command implementations
 
 use std::path::Path;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
 
 use crate::controller::BjigController;
-use crate::executor::CommandExecutor;
+use crate::executor::{CommandExecutor, LogId};
 use crate::types::*;
 
+use super::router::{record_step, succeeded};
+
+/// Interval between readiness polls after a restart in `provision`
+const PROVISION_RESTART_POLL: Duration = Duration::from_secs(2);
+
+/// Maximum wait for the module to come back after a restart in `provision`
+const PROVISION_RESTART_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Module commands interface
 ///
 /// Provides access to all module-related operations including:
@@ -31,12 +42,13 @@ impl<'a> ModuleCommands<'a> {
     }
 
     /// Get command executor
-    fn executor(&self) -> CommandExecutor {
-        CommandExecutor::new(
-            &self.controller.bjig_path,
-            self.controller.default_port.as_deref(),
-            self.controller.default_baud,
-        )
+    fn executor(&self) -> CommandExecutor<'_> {
+        self.controller.executor()
+    }
+
+    /// Module ID formatted for logs
+    fn log_id(&self) -> LogId<'_> {
+        self.controller.config.log_id(&self.module_id)
     }
 
     /// Request instant uplink (immediate sensor data retrieval)
@@ -48,15 +60,55 @@ impl<'a> ModuleCommands<'a> {
     /// use bjig_controller::BjigController;
     ///
     /// let bjig = BjigController::from_env()?;
-    /// let data = bjig.module("0121", "2468800203400004")
+    /// let uplink = bjig.module("0121", "2468800203400004")
     ///     .instant_uplink()
     ///     .await?;
-    /// println!("Sensor data: {:?}", data);
+    /// println!("RSSI: {:?}, data: {:?}", uplink.rssi, uplink.data);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn instant_uplink(&self) -> Result<serde_json::Value> {
-        self.instant_uplink_with_timeout(30).await
+    pub async fn instant_uplink(&self) -> Result<UplinkResult> {
+        self.instant_uplink_with_timeout(self.controller.response_timeout).await
+    }
+
+    /// Request instant uplink from an illuminance module (sensor ID 0121)
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` without issuing the command if
+    /// this module is not an illuminance sensor, or if the response comes
+    /// from a different sensor.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let uplink = bjig.module("0121", "2468800203400004")
+    ///     .instant_uplink_illuminance()
+    ///     .await?;
+    /// println!("{} lx, battery: {:?} mV", uplink.lux, uplink.battery_mv);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn instant_uplink_illuminance(&self) -> Result<IlluminanceUplink> {
+        if self.sensor_id != IlluminanceUplink::SENSOR_ID {
+            return Err(BjigError::InvalidParameter(format!(
+                "Sensor {} is not an illuminance sensor ({})",
+                self.sensor_id,
+                IlluminanceUplink::SENSOR_ID
+            )));
+        }
+
+        let uplink = self.instant_uplink().await?;
+        IlluminanceUplink::from_uplink(&uplink)
+    }
+
+    /// Request instant uplink and return the response as-is
+    pub async fn instant_uplink_raw(&self) -> Result<serde_json::Value> {
+        self.instant_uplink_with_timeout_on_impl(None, None, self.controller.response_timeout)
+            .await
     }
 
     /// Request instant uplink on specific port
@@ -64,20 +116,22 @@ impl<'a> ModuleCommands<'a> {
         &self,
         port: &str,
         baud: u32,
-    ) -> Result<serde_json::Value> {
-        self.instant_uplink_with_timeout_on(port, baud, 30).await
+    ) -> Result<UplinkResult> {
+        self.instant_uplink_with_timeout_on(port, baud, self.controller.response_timeout).await
     }
 
     /// Request instant uplink with custom timeout
     ///
     /// # Arguments
-    /// * `timeout_secs` - Response timeout in seconds (default: 30)
+    /// * `timeout_secs` - Response timeout in seconds (overrides the controller default)
     pub async fn instant_uplink_with_timeout(
         &self,
         timeout_secs: u64,
-    ) -> Result<serde_json::Value> {
-        self.instant_uplink_with_timeout_on_impl(None, None, timeout_secs)
-            .await
+    ) -> Result<UplinkResult> {
+        let json = self
+            .instant_uplink_with_timeout_on_impl(None, None, timeout_secs)
+            .await?;
+        decode("module instant-uplink", json)
     }
 
     /// Request instant uplink on specific port with custom timeout
@@ -86,9 +140,11 @@ impl<'a> ModuleCommands<'a> {
         port: &str,
         baud: u32,
         timeout_secs: u64,
-    ) -> Result<serde_json::Value> {
-        self.instant_uplink_with_timeout_on_impl(Some(port), Some(baud), timeout_secs)
-            .await
+    ) -> Result<UplinkResult> {
+        let json = self
+            .instant_uplink_with_timeout_on_impl(Some(port), Some(baud), timeout_secs)
+            .await?;
+        decode("module instant-uplink", json)
     }
 
     async fn instant_uplink_with_timeout_on_impl(
@@ -132,7 +188,7 @@ impl<'a> ModuleCommands<'a> {
     /// # }
     /// ```
     pub async fn get_parameter(&self) -> Result<serde_json::Value> {
-        self.get_parameter_with_timeout(30).await
+        self.get_parameter_with_timeout(self.controller.response_timeout).await
     }
 
     /// Get module parameters on specific port
@@ -141,7 +197,7 @@ impl<'a> ModuleCommands<'a> {
         port: &str,
         baud: u32,
     ) -> Result<serde_json::Value> {
-        self.get_parameter_with_timeout_on(port, baud, 30).await
+        self.get_parameter_with_timeout_on(port, baud, self.controller.response_timeout).await
     }
 
     /// Get module parameters with custom timeout
@@ -188,6 +244,59 @@ impl<'a> ModuleCommands<'a> {
         Ok(json)
     }
 
+    /// Get module parameters deserialized into `T`
+    ///
+    /// Use one of the sensor-specific structs (e.g. `IlluminanceParameters`)
+    /// or your own type. `get_parameter` remains available for untyped access.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, IlluminanceParameters};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let params: IlluminanceParameters = bjig.module("0121", "2468800203400004")
+    ///     .get_parameter_as()
+    ///     .await?;
+    /// println!("Interval: {:?}", params.interval);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_parameter_as<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        let json = self.get_parameter().await?;
+        deserialize("module get-parameter", json)
+    }
+
+    /// Compare desired parameters against the module's current parameters
+    ///
+    /// Calls `get_parameter` and computes which desired values would change,
+    /// which would be added, and which are already applied. No write is
+    /// performed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use serde_json::json;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let module = bjig.module("0121", "2468800203400004");
+    /// let desired = json!({"interval": 60});
+    ///
+    /// let diff = module.parameter_diff(&desired).await?;
+    /// if !diff.is_empty() {
+    ///     module.set_parameter(&desired).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn parameter_diff(&self, desired: &serde_json::Value) -> Result<ParameterDiff> {
+        let current = self.get_parameter().await?;
+        Ok(ParameterDiff::compute(&current, desired))
+    }
+
     /// Set module parameters
     ///
     /// # Arguments
@@ -213,7 +322,212 @@ impl<'a> ModuleCommands<'a> {
     /// # }
     /// ```
     pub async fn set_parameter(&self, data: &serde_json::Value) -> Result<SetParameterResult> {
-        self.set_parameter_with_timeout(data, 30).await
+        self.set_parameter_with_timeout(data, self.controller.response_timeout).await
+    }
+
+    /// Set module parameters only if at least one value differs
+    ///
+    /// Reads the current parameters and skips the write when all desired
+    /// values are already applied. Numbers are compared by value, so `60`
+    /// and `60.0` are considered equal. This avoids wearing out module flash
+    /// in reconciliation loops.
+    ///
+    /// # Returns
+    /// `None` if nothing changed, otherwise the result of the write
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use serde_json::json;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let result = bjig.module("0121", "2468800203400004")
+    ///     .set_parameter_if_changed(&json!({"interval": 60}))
+    ///     .await?;
+    /// if result.is_none() {
+    ///     println!("Parameters already up to date");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_parameter_if_changed(
+        &self,
+        desired: &serde_json::Value,
+    ) -> Result<Option<SetParameterResult>> {
+        let diff = self.parameter_diff(desired).await?;
+        if diff.is_empty() {
+            log::debug!("Parameters unchanged for module {}, skipping write", self.log_id());
+            return Ok(None);
+        }
+
+        Ok(Some(self.set_parameter(desired).await?))
+    }
+
+    /// Set module parameters and roll back if the readback does not match
+    ///
+    /// Snapshots the current parameters, writes `desired` and reads the
+    /// parameters back. If any desired value was not applied, the snapshot
+    /// is written back. This is best-effort atomicity: parameters missing
+    /// from the snapshot cannot be restored, and the restore itself is not
+    /// verified.
+    ///
+    /// # Errors
+    /// Returns `BjigError::ParameterRollback` listing the failed and
+    /// restored parameters if verification fails. If the write or the
+    /// readback fails, the snapshot is restored and that error is returned;
+    /// if the restore fails too, both errors are returned in
+    /// `BjigError::ParameterRestoreFailed`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, BjigError};
+    /// use serde_json::json;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let module = bjig.module("0121", "2468800203400004");
+    ///
+    /// match module.set_parameters_transactional(&json!({"interval": 60, "threshold": 100})).await {
+    ///     Ok(_) => println!("Parameters applied"),
+    ///     Err(BjigError::ParameterRollback { failed, restored }) => {
+    ///         eprintln!("Not applied: {:?}, restored: {:?}", failed, restored);
+    ///     }
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_parameters_transactional(
+        &self,
+        desired: &serde_json::Value,
+    ) -> Result<SetParameterResult> {
+        let snapshot = self.get_parameter().await?;
+        let touched: Vec<String> = ParameterDiff::compute(&snapshot, desired)
+            .changed
+            .into_iter()
+            .map(|change| change.key)
+            .collect();
+
+        let result = match self.set_parameter(desired).await {
+            Ok(result) => result,
+            Err(e) => return Err(self.restore_after_error(&snapshot, e).await),
+        };
+
+        let readback = match self.get_parameter().await {
+            Ok(readback) => readback,
+            Err(e) => return Err(self.restore_after_error(&snapshot, e).await),
+        };
+        let diff = ParameterDiff::compute(&readback, desired);
+        if diff.is_empty() {
+            return Ok(result);
+        }
+
+        let failed = diff
+            .changed
+            .into_iter()
+            .map(|change| change.key)
+            .chain(diff.added.into_iter().map(|(key, _)| key))
+            .collect();
+        let restored = match self.restore_parameters(&snapshot).await {
+            Ok(()) => touched,
+            Err(_) => Vec::new(),
+        };
+        Err(BjigError::ParameterRollback { failed, restored })
+    }
+
+    /// Provision the module: set parameters, optionally restart, then verify
+    ///
+    /// After writing `params`, the module is restarted and polled until it
+    /// answers again if `restart_after` is set. The parameters are then read
+    /// back and compared with `params`; the comparison is stored in
+    /// `ProvisionReport::parameters`.
+    ///
+    /// # Errors
+    /// Stops at the first failed step (command error, unsuccessful result,
+    /// module not back within 60 seconds, or parameters not applied) and
+    /// returns `BjigError::ProvisionFailed` carrying the partial report.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use serde_json::json;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let report = bjig.module("0121", "2468800203400004")
+    ///     .provision(&json!({"interval": 60}), true)
+    ///     .await?;
+    /// for step in &report.steps {
+    ///     println!("{:?}: {}", step.kind, step.message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn provision(&self, params: &serde_json::Value, restart_after: bool) -> Result<ProvisionReport> {
+        let mut report = ProvisionReport::default();
+
+        let outcome = self
+            .set_parameter(params)
+            .await
+            .and_then(|r| succeeded(r.is_success(), r.message));
+        record_step(&mut report, ProvisionStepKind::SetParameter, outcome)?;
+
+        if restart_after {
+            let outcome = self
+                .restart_and_wait(PROVISION_RESTART_POLL, PROVISION_RESTART_TIMEOUT)
+                .await
+                .map(|()| "Module restarted and responding".to_string());
+            record_step(&mut report, ProvisionStepKind::RestartModule, outcome)?;
+        }
+
+        let outcome = match self.get_parameter().await {
+            Ok(readback) => {
+                let diff = ParameterDiff::compute(&readback, params);
+                let mismatched: Vec<&str> = diff
+                    .changed
+                    .iter()
+                    .map(|change| change.key.as_str())
+                    .chain(diff.added.iter().map(|(key, _)| key.as_str()))
+                    .collect();
+                let outcome = if mismatched.is_empty() {
+                    Ok(format!("{} parameters applied", diff.unchanged.len()))
+                } else {
+                    Err(BjigError::VerificationFailed(format!(
+                        "Parameters not applied: {}",
+                        mismatched.join(", ")
+                    )))
+                };
+                report.parameters = Some(diff);
+                outcome
+            }
+            Err(e) => Err(e),
+        };
+        record_step(&mut report, ProvisionStepKind::VerifyParameters, outcome)?;
+
+        Ok(report)
+    }
+
+    /// Write `snapshot` back
+    async fn restore_parameters(&self, snapshot: &serde_json::Value) -> Result<()> {
+        self.set_parameter(snapshot).await.map(|_| ()).map_err(|e| {
+            log::warn!("Failed to restore parameters of module {}: {}", self.log_id(), e);
+            e
+        })
+    }
+
+    /// Write `snapshot` back after `error`, combining both errors if the restore fails
+    async fn restore_after_error(&self, snapshot: &serde_json::Value, error: BjigError) -> BjigError {
+        match self.restore_parameters(snapshot).await {
+            Ok(()) => error,
+            Err(restore) => BjigError::ParameterRestoreFailed {
+                source: Box::new(error),
+                restore: Box::new(restore),
+            },
+        }
     }
 
     /// Set module parameters on specific port
@@ -223,7 +537,7 @@ impl<'a> ModuleCommands<'a> {
         baud: u32,
         data: &serde_json::Value,
     ) -> Result<SetParameterResult> {
-        self.set_parameter_with_timeout_on(port, baud, data, 30)
+        self.set_parameter_with_timeout_on(port, baud, data, self.controller.response_timeout)
             .await
     }
 
@@ -233,7 +547,7 @@ impl<'a> ModuleCommands<'a> {
         data: &serde_json::Value,
         timeout_secs: u64,
     ) -> Result<SetParameterResult> {
-        self.set_parameter_with_timeout_on_impl(None, None, data, timeout_secs)
+        self.set_parameter_with_response_timeout(data, Duration::from_secs(timeout_secs))
             .await
     }
 
@@ -245,7 +559,48 @@ impl<'a> ModuleCommands<'a> {
         data: &serde_json::Value,
         timeout_secs: u64,
     ) -> Result<SetParameterResult> {
-        self.set_parameter_with_timeout_on_impl(Some(port), Some(baud), data, timeout_secs)
+        self.set_parameter_with_response_timeout_on(port, baud, data, Duration::from_secs(timeout_secs))
+            .await
+    }
+
+    /// Set module parameters with a response timeout
+    ///
+    /// `timeout` is passed to bjig in whole seconds, rounded to the nearest
+    /// second and at least one.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use std::time::Duration;
+    /// use bjig_controller::BjigController;
+    /// use serde_json::json;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.module("0121", "2468800203400004")
+    ///     .set_parameter_with_response_timeout(&json!({"interval": 60}), Duration::from_secs(45))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_parameter_with_response_timeout(
+        &self,
+        data: &serde_json::Value,
+        timeout: Duration,
+    ) -> Result<SetParameterResult> {
+        self.set_parameter_with_timeout_on_impl(None, None, data, timeout)
+            .await
+    }
+
+    /// Set module parameters on specific port with a response timeout
+    pub async fn set_parameter_with_response_timeout_on(
+        &self,
+        port: &str,
+        baud: u32,
+        data: &serde_json::Value,
+        timeout: Duration,
+    ) -> Result<SetParameterResult> {
+        self.set_parameter_with_timeout_on_impl(Some(port), Some(baud), data, timeout)
             .await
     }
 
@@ -254,11 +609,11 @@ impl<'a> ModuleCommands<'a> {
         port: Option<&str>,
         baud: Option<u32>,
         data: &serde_json::Value,
-        timeout_secs: u64,
+        timeout: Duration,
     ) -> Result<SetParameterResult> {
         let executor = self.executor();
         let data_str = serde_json::to_string(data)?;
-        let timeout_str = timeout_secs.to_string();
+        let timeout_str = response_timeout_arg(timeout);
 
         let args = vec![
             "module",
@@ -274,7 +629,7 @@ impl<'a> ModuleCommands<'a> {
         ];
 
         let json = executor.execute_json(&args, port, baud).await?;
-        Ok(serde_json::from_value(json)?)
+        decode("module set-parameter", json)
     }
 
     /// Restart module
@@ -293,12 +648,12 @@ impl<'a> ModuleCommands<'a> {
     /// # }
     /// ```
     pub async fn restart(&self) -> Result<RestartResult> {
-        self.restart_with_timeout(30).await
+        self.restart_with_timeout(self.controller.response_timeout).await
     }
 
     /// Restart module on specific port
     pub async fn restart_on(&self, port: &str, baud: u32) -> Result<RestartResult> {
-        self.restart_with_timeout_on(port, baud, 30).await
+        self.restart_with_timeout_on(port, baud, self.controller.response_timeout).await
     }
 
     /// Restart module with custom timeout
@@ -338,8 +693,61 @@ impl<'a> ModuleCommands<'a> {
             &timeout_str,
         ];
 
-        let json = executor.execute_json(&args, port, baud).await?;
-        Ok(serde_json::from_value(json)?)
+        let json = executor.execute_status(&args, port, baud).await?;
+        status_from_json("module restart", json, "Module restarted")
+    }
+
+    /// Restart module and wait until it responds to instant uplink again
+    ///
+    /// After the restart is acknowledged, `instant_uplink_raw` is polled every
+    /// `poll_interval` until it succeeds. An in-flight uplink request is
+    /// cancelled when `max_wait` elapses.
+    ///
+    /// # Errors
+    /// Returns `BjigError::CommandFailed` if the restart is rejected and
+    /// `BjigError::Timeout` (rounded up to whole seconds) if the module does
+    /// not respond within `max_wait`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use std::time::Duration;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.module("0121", "2468800203400004")
+    ///     .restart_and_wait(Duration::from_secs(2), Duration::from_secs(60))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restart_and_wait(&self, poll_interval: Duration, max_wait: Duration) -> Result<()> {
+        let result = self.restart().await?;
+        if !result.is_success() {
+            return Err(BjigError::CommandFailed(format!(
+                "Module restart failed: {}",
+                result.message
+            )));
+        }
+
+        let poll = async {
+            loop {
+                match self.instant_uplink_raw().await {
+                    Ok(_) => return Ok(()),
+                    Err(BjigError::Closed) => return Err(BjigError::Closed),
+                    Err(e) => {
+                        log::debug!("Module {} not ready after restart: {}", self.log_id(), e);
+                        tokio::time::sleep(poll_interval).await;
+                    }
+                }
+            }
+        };
+
+        tokio::time::timeout(max_wait, poll).await.map_err(|_| {
+            log::error!("Module {} did not come back within {:?}", self.log_id(), max_wait);
+            BjigError::timeout_after(max_wait)
+        })?
     }
 
     /// Module DFU (firmware update)
@@ -401,8 +809,119 @@ impl<'a> ModuleCommands<'a> {
             &path_str,
         ];
 
+        let _port = executor.hold_port();
         let json = executor.execute_json(&args, port, baud).await?;
-        Ok(serde_json::from_value(json)?)
+        decode("module dfu", json)
+    }
+
+    /// Start module DFU in a background task that can be aborted
+    ///
+    /// `DfuTask::abort` kills the bjig process running the transfer. Nothing
+    /// is sent to the module afterwards, so its state after an abort is
+    /// unknown; check it (e.g. with `instant_uplink`) before relying on it.
+    /// The transfer is never retried, regardless of the retry policy.
+    ///
+    /// Dropping the handle does not abort the DFU; it keeps running to
+    /// completion in the background.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, DfuOutcome};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let task = bjig.module("0121", "2468800203400004")
+    ///     .dfu_with_abort("firmware.bin")?;
+    ///
+    /// tokio::select! {
+    ///     outcome = task.wait() => println!("DFU finished: {:?}", outcome?),
+    ///     _ = tokio::signal::ctrl_c() => {}
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Aborting explicitly:
+    ///
+    /// ```no_run
+    /// # async fn example(task: bjig_controller::DfuTask) -> anyhow::Result<()> {
+    /// use bjig_controller::DfuOutcome;
+    ///
+    /// match task.abort().await? {
+    ///     DfuOutcome::Completed(result) => println!("Already finished: {}", result.result),
+    ///     // Re-run the DFU, power-cycling the module if it stays in its bootloader
+    ///     DfuOutcome::Aborted { .. } => eprintln!("DFU aborted, run it again"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dfu_with_abort<P: AsRef<Path>>(&self, firmware_path: P) -> Result<DfuTask> {
+        self.dfu_with_abort_impl(None, None, firmware_path.as_ref())
+    }
+
+    /// Start abortable module DFU on specific port
+    pub fn dfu_with_abort_on<P: AsRef<Path>>(
+        &self,
+        port: &str,
+        baud: u32,
+        firmware_path: P,
+    ) -> Result<DfuTask> {
+        self.dfu_with_abort_impl(Some(port.to_string()), Some(baud), firmware_path.as_ref())
+    }
+
+    fn dfu_with_abort_impl(
+        &self,
+        port: Option<String>,
+        baud: Option<u32>,
+        firmware_path: &Path,
+    ) -> Result<DfuTask> {
+        self.controller.config.queue.ensure_open()?;
+        if !firmware_path.exists() {
+            return Err(BjigError::FileNotFound(firmware_path.to_path_buf()));
+        }
+
+        let config = self.controller.executor_config();
+        let sensor_id = self.sensor_id.clone();
+        let module_id = self.module_id.clone();
+        let path_str = firmware_path.to_string_lossy().into_owned();
+        let (abort_tx, abort_rx) = oneshot::channel();
+
+        let task_handle = tokio::spawn(async move {
+            let executor = config.executor().with_retry(None);
+            let args = [
+                "module",
+                "dfu",
+                "--sensor-id",
+                sensor_id.as_str(),
+                "--module-id",
+                module_id.as_str(),
+                "--file",
+                path_str.as_str(),
+            ];
+
+            let transfer = async {
+                let _port = executor.hold_port();
+                executor.execute_json(&args, port.as_deref(), baud).await
+            };
+
+            // A dropped handle closes the channel without matching `Ok`,
+            // which leaves the transfer running
+            tokio::select! {
+                json = transfer => decode("module dfu", json?).map(DfuOutcome::Completed),
+                Ok(()) = abort_rx => {
+                    // The transfer future has been dropped, killing the DFU process
+                    log::warn!("DFU of module {} aborted", executor.log_id(&module_id));
+                    // bjig cannot cancel a DFU, so the process is always killed
+                    Ok(DfuOutcome::Aborted { forced: true })
+                }
+            }
+        });
+
+        Ok(DfuTask {
+            abort_tx: Some(abort_tx),
+            task_handle,
+        })
     }
 
     /// Send module-specific control command
@@ -428,7 +947,7 @@ impl<'a> ModuleCommands<'a> {
     /// # }
     /// ```
     pub async fn control(&self, data: &serde_json::Value) -> Result<ControlResult> {
-        self.control_with_timeout(data, 30).await
+        self.control_with_timeout(data, self.controller.response_timeout).await
     }
 
     /// Send control command on specific port
@@ -438,7 +957,7 @@ impl<'a> ModuleCommands<'a> {
         baud: u32,
         data: &serde_json::Value,
     ) -> Result<ControlResult> {
-        self.control_with_timeout_on(port, baud, data, 30).await
+        self.control_with_timeout_on(port, baud, data, self.controller.response_timeout).await
     }
 
     /// Send control command with custom timeout
@@ -447,7 +966,7 @@ impl<'a> ModuleCommands<'a> {
         data: &serde_json::Value,
         timeout_secs: u64,
     ) -> Result<ControlResult> {
-        self.control_with_timeout_on_impl(None, None, data, timeout_secs)
+        self.control_with_response_timeout(data, Duration::from_secs(timeout_secs))
             .await
     }
 
@@ -459,7 +978,48 @@ impl<'a> ModuleCommands<'a> {
         data: &serde_json::Value,
         timeout_secs: u64,
     ) -> Result<ControlResult> {
-        self.control_with_timeout_on_impl(Some(port), Some(baud), data, timeout_secs)
+        self.control_with_response_timeout_on(port, baud, data, Duration::from_secs(timeout_secs))
+            .await
+    }
+
+    /// Send control command with a response timeout
+    ///
+    /// `timeout` is passed to bjig in whole seconds, rounded to the nearest
+    /// second and at least one.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use std::time::Duration;
+    /// use bjig_controller::BjigController;
+    /// use serde_json::json;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.module("0126", "2468800203400004")
+    ///     .control_with_response_timeout(&json!({"clear_counts": "all"}), Duration::from_secs(45))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn control_with_response_timeout(
+        &self,
+        data: &serde_json::Value,
+        timeout: Duration,
+    ) -> Result<ControlResult> {
+        self.control_with_timeout_on_impl(None, None, data, timeout)
+            .await
+    }
+
+    /// Send control command on specific port with a response timeout
+    pub async fn control_with_response_timeout_on(
+        &self,
+        port: &str,
+        baud: u32,
+        data: &serde_json::Value,
+        timeout: Duration,
+    ) -> Result<ControlResult> {
+        self.control_with_timeout_on_impl(Some(port), Some(baud), data, timeout)
             .await
     }
 
@@ -468,11 +1028,11 @@ impl<'a> ModuleCommands<'a> {
         port: Option<&str>,
         baud: Option<u32>,
         data: &serde_json::Value,
-        timeout_secs: u64,
+        timeout: Duration,
     ) -> Result<ControlResult> {
         let executor = self.executor();
         let data_str = serde_json::to_string(data)?;
-        let timeout_str = timeout_secs.to_string();
+        let timeout_str = response_timeout_arg(timeout);
 
         let args = vec![
             "module",
@@ -488,6 +1048,624 @@ impl<'a> ModuleCommands<'a> {
         ];
 
         let json = executor.execute_json(&args, port, baud).await?;
-        Ok(serde_json::from_value(json)?)
+        decode("module control", json)
+    }
+
+    /// Send control command with the payload piped through stdin
+    ///
+    /// Passes `--data -` so that bjig reads the JSON payload from stdin,
+    /// which avoids command line length limits for large payloads.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use serde_json::json;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let table: Vec<u32> = (0..4096).collect();
+    /// let result = bjig.module("0121", "2468800203400004")
+    ///     .control_via_stdin(&json!({"table": table}))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn control_via_stdin(&self, data: &serde_json::Value) -> Result<ControlResult> {
+        self.control_via_stdin_with_timeout(data, self.controller.response_timeout).await
+    }
+
+    /// Send control command through stdin with custom timeout
+    pub async fn control_via_stdin_with_timeout(
+        &self,
+        data: &serde_json::Value,
+        timeout_secs: u64,
+    ) -> Result<ControlResult> {
+        let executor = self.executor();
+        let payload = serde_json::to_vec(data)?;
+        let timeout_str = timeout_secs.to_string();
+
+        let args = vec![
+            "module",
+            "control",
+            "--sensor-id",
+            &self.sensor_id,
+            "--module-id",
+            &self.module_id,
+            "--data",
+            "-",
+            "--response-timeout",
+            &timeout_str,
+        ];
+
+        let json = executor
+            .execute_json_with_stdin(&args, &payload, None, None)
+            .await?;
+        decode("module control", json)
+    }
+}
+
+/// Handle of a DFU started with `ModuleCommands::dfu_with_abort`
+///
+/// Unlike other task handles, dropping it does not stop the task: an
+/// interrupted transfer leaves the module in an unknown state, so the DFU
+/// only stops on an explicit `abort()`.
+pub struct DfuTask {
+    abort_tx: Option<oneshot::Sender<()>>,
+    task_handle: tokio::task::JoinHandle<Result<DfuOutcome>>,
+}
+
+impl DfuTask {
+    /// Abort the DFU by killing the transfer process
+    ///
+    /// Returns `DfuOutcome::Completed` if the DFU finished before the abort
+    /// took effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns the DFU error if the transfer failed before the abort, or an
+    /// error if the DFU task panicked.
+    pub async fn abort(mut self) -> Result<DfuOutcome> {
+        if let Some(abort_tx) = self.abort_tx.take() {
+            let _ = abort_tx.send(());
+        }
+        self.wait().await
+    }
+
+    /// Wait for the DFU to finish
+    pub async fn wait(self) -> Result<DfuOutcome> {
+        match self.task_handle.await {
+            Ok(result) => result,
+            Err(e) => Err(BjigError::CommandFailed(format!("DFU task panicked: {}", e))),
+        }
+    }
+
+    /// Check if the DFU is still running
+    pub fn is_running(&self) -> bool {
+        !self.task_handle.is_finished()
+    }
+}
+
+/// Format a timeout as bjig's `--response-timeout` value (whole seconds, at least 1)
+fn response_timeout_arg(timeout: Duration) -> String {
+    let secs = (timeout.as_millis() + 500) / 1000;
+    secs.max(1).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_bjig;
+
+    /// Fake bjig that echoes its argv back as JSON
+    fn echo_bjig() -> std::path::PathBuf {
+        fake_bjig(r#"printf '{"result":"success","message":"%s"}' "$*""#)
+    }
+
+    #[tokio::test]
+    async fn test_default_response_timeout_in_argv() {
+        let bjig = BjigController::new(echo_bjig())
+            .unwrap()
+            .with_port("/dev/null")
+            .with_response_timeout(45);
+        let module = bjig.module("0121", "2468800203400004");
+
+        let json = module.instant_uplink_raw().await.unwrap();
+        assert!(json["message"].as_str().unwrap().ends_with("--response-timeout 45"));
+
+        let result = module.restart().await.unwrap();
+        assert!(result.message.ends_with("--response-timeout 45"));
+    }
+
+    #[tokio::test]
+    async fn test_explicit_timeout_overrides_default() {
+        let bjig = BjigController::new(echo_bjig())
+            .unwrap()
+            .with_port("/dev/null")
+            .with_response_timeout(45);
+
+        let json = bjig
+            .module("0121", "2468800203400004")
+            .get_parameter_with_timeout(5)
+            .await
+            .unwrap();
+        assert!(json["message"].as_str().unwrap().ends_with("--response-timeout 5"));
+    }
+
+    #[tokio::test]
+    async fn test_instant_uplink_illuminance() {
+        let mock = std::sync::Arc::new(crate::runner::MockRunner::new().on(
+            &["module", "instant-uplink"],
+            r#"{"sensor_id":"0121","module_id":"2468800203400004","rssi":-62,"lux":523.4,"battery_mv":3012}"#,
+        ));
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+
+        let uplink = bjig
+            .module("0121", "2468800203400004")
+            .instant_uplink_illuminance()
+            .await
+            .unwrap();
+        assert_eq!(uplink.lux, 523.4);
+        assert_eq!(uplink.battery_mv, Some(3012));
+
+        assert!(matches!(
+            bjig.module("0122", "2468800203400004")
+                .instant_uplink_illuminance()
+                .await,
+            Err(BjigError::InvalidParameter(_))
+        ));
+        assert_eq!(mock.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_provision() {
+        let mock = std::sync::Arc::new(
+            crate::runner::MockRunner::new()
+                .on(&["module", "set-parameter"], r#"{"result":"success","message":"ok"}"#)
+                .on(&["module", "restart"], r#"{"result":"success","message":"restarted"}"#)
+                .on(&["module", "instant-uplink"], r#"{"sensor_id":"0121","module_id":"2468800203400004","lux":1}"#)
+                .on(&["module", "get-parameter"], r#"{"interval":60,"tx_power":0,"firmware":"1.0.3"}"#),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+        let module = bjig.module("0121", "2468800203400004");
+
+        let report = module
+            .provision(&serde_json::json!({"interval": 60, "tx_power": 0}), true)
+            .await
+            .unwrap();
+        let kinds: Vec<ProvisionStepKind> = report.steps.iter().map(|step| step.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ProvisionStepKind::SetParameter,
+                ProvisionStepKind::RestartModule,
+                ProvisionStepKind::VerifyParameters,
+            ]
+        );
+        assert!(report.is_success());
+        assert_eq!(report.parameters.unwrap().unchanged, vec!["/interval", "/tx_power"]);
+
+        // The module keeps tx_power at 0
+        let err = module
+            .provision(&serde_json::json!({"interval": 60, "tx_power": 4}), false)
+            .await
+            .unwrap_err();
+        let report = match err {
+            BjigError::ProvisionFailed { report, source } => {
+                assert!(matches!(
+                    *source,
+                    BjigError::VerificationFailed(ref m) if m == "Parameters not applied: /tx_power"
+                ));
+                report
+            }
+            other => panic!("unexpected error: {:?}", other),
+        };
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.steps[1].kind, ProvisionStepKind::VerifyParameters);
+        assert!(!report.steps[1].success);
+        let diff = report.parameters.unwrap();
+        assert_eq!(diff.unchanged, vec!["/interval"]);
+        assert_eq!(diff.changed[0].key, "/tx_power");
+    }
+
+    #[tokio::test]
+    async fn test_duration_response_timeout_in_argv() {
+        let mock = std::sync::Arc::new(
+            crate::runner::MockRunner::new()
+                .on(&["module"], r#"{"result":"success","message":"ok"}"#),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+        let module = bjig.module("0126", "2468800203400004");
+        let data = serde_json::json!({"clear_counts": "all"});
+
+        module
+            .control_with_response_timeout(&data, Duration::from_secs(45))
+            .await
+            .unwrap();
+        module
+            .set_parameter_with_response_timeout(&data, Duration::from_secs(45))
+            .await
+            .unwrap();
+        module.control_with_timeout(&data, 45).await.unwrap();
+
+        for argv in mock.calls() {
+            assert_eq!(argv[argv.len() - 2..], ["--response-timeout", "45"]);
+        }
+
+        assert_eq!(response_timeout_arg(Duration::from_millis(1600)), "2");
+        assert_eq!(response_timeout_arg(Duration::from_millis(1400)), "1");
+        assert_eq!(response_timeout_arg(Duration::ZERO), "1");
+    }
+
+    #[tokio::test]
+    async fn test_parameter_diff() {
+        let bin = fake_bjig(r#"echo '{"interval":60,"threshold":100}'"#);
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+
+        let diff = bjig
+            .module("0121", "2468800203400004")
+            .parameter_diff(&serde_json::json!({"interval": 30, "threshold": 100}))
+            .await
+            .unwrap();
+
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].key, "/interval");
+        assert_eq!(diff.unchanged, vec!["/threshold"]);
+    }
+
+    /// Fake bjig with fixed parameters that records set-parameter calls
+    fn parameter_bjig(params: &str) -> std::path::PathBuf {
+        fake_bjig(&format!(
+            r#"case "$*" in
+  *get-parameter*) echo '{}' ;;
+  *set-parameter*) touch "$0.set"; echo '{{"result":"success","message":"ok"}}' ;;
+esac"#,
+            params
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_set_parameter_if_changed_noop() {
+        let bin = parameter_bjig(r#"{"interval":60.0}"#);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let result = bjig
+            .module("0121", "2468800203400004")
+            .set_parameter_if_changed(&serde_json::json!({"interval": 60}))
+            .await
+            .unwrap();
+
+        assert!(result.is_none());
+        assert!(!bin.with_extension("set").exists());
+    }
+
+    #[tokio::test]
+    async fn test_set_parameter_if_changed_writes() {
+        let bin = parameter_bjig(r#"{"interval":60}"#);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let result = bjig
+            .module("0121", "2468800203400004")
+            .set_parameter_if_changed(&serde_json::json!({"interval": 120}))
+            .await
+            .unwrap();
+
+        assert!(result.unwrap().is_success());
+        assert!(bin.with_extension("set").exists());
+    }
+
+    /// Fake bjig whose instant uplink fails `failures` times before succeeding
+    fn flaky_uplink_bjig(failures: u32) -> std::path::PathBuf {
+        fake_bjig(&format!(
+            r#"case "$*" in
+  *restart*) echo '{{"result":"success","message":"ok"}}' ;;
+  *instant-uplink*)
+    echo x >> "$0.polls"
+    if [ "$(wc -l < "$0.polls")" -le {} ]; then exit 1; fi
+    echo '{{"lux":120}}' ;;
+esac"#,
+            failures
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_set_parameters_transactional_rolls_back() {
+        // The module ignores writes, so the readback never matches
+        let mock = std::sync::Arc::new(
+            crate::runner::MockRunner::new()
+                .on(&["module", "get-parameter"], r#"{"interval":60,"threshold":100}"#)
+                .on(&["module", "set-parameter"], r#"{"result":"success","message":"ok"}"#),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+
+        let err = bjig
+            .module("0121", "2468800203400004")
+            .set_parameters_transactional(&serde_json::json!({"interval": 30, "mode": 1}))
+            .await
+            .unwrap_err();
+
+        match err {
+            BjigError::ParameterRollback { failed, restored } => {
+                assert_eq!(failed, vec!["/interval", "/mode"]);
+                assert_eq!(restored, vec!["/interval"]);
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+
+        let calls = mock.calls();
+        let subcommands: Vec<&str> = calls.iter().map(|argv| argv[5].as_str()).collect();
+        assert_eq!(
+            subcommands,
+            vec!["get-parameter", "set-parameter", "get-parameter", "set-parameter"]
+        );
+        let restore = &calls[3];
+        let data = &restore[restore.iter().position(|a| a == "--data").unwrap() + 1];
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(data).unwrap(),
+            serde_json::json!({"interval": 60, "threshold": 100})
+        );
+    }
+
+    /// Fake bjig whose parameter readback fails, optionally failing the restore too
+    fn failing_readback_bjig(restore_fails: bool) -> std::path::PathBuf {
+        fake_bjig(&format!(
+            r#"echo "$6" >> "$0.calls"
+case "$6" in
+  get-parameter)
+    if [ "$(grep -c get-parameter "$0.calls")" -gt 1 ]; then echo 'Module not responding' >&2; exit 1; fi
+    echo '{{"interval":60}}' ;;
+  set-parameter)
+    if {} && [ "$(grep -c set-parameter "$0.calls")" -gt 1 ]; then echo 'Module busy' >&2; exit 1; fi
+    echo '{{"result":"success","message":"ok"}}' ;;
+esac"#,
+            restore_fails
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_set_parameters_transactional_restores_after_failed_readback() {
+        let desired = serde_json::json!({"interval": 30});
+
+        let bin = failing_readback_bjig(false);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let err = bjig
+            .module("0121", "2468800203400004")
+            .set_parameters_transactional(&desired)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BjigError::CommandFailed(ref m) if m.contains("Module not responding")));
+        let calls = std::fs::read_to_string(bin.with_extension("calls")).unwrap();
+        assert_eq!(
+            calls.lines().collect::<Vec<_>>(),
+            ["get-parameter", "set-parameter", "get-parameter", "set-parameter"]
+        );
+
+        let bin = failing_readback_bjig(true);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        match bjig
+            .module("0121", "2468800203400004")
+            .set_parameters_transactional(&desired)
+            .await
+        {
+            Err(BjigError::ParameterRestoreFailed { source, restore }) => {
+                assert!(matches!(*source, BjigError::CommandFailed(ref m) if m.contains("Module not responding")));
+                assert!(matches!(*restore, BjigError::CommandFailed(ref m) if m.contains("Module busy")));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flag_like_module_id_rejected() {
+        let mock = std::sync::Arc::new(crate::runner::MockRunner::new().on(&[], r#"{"interval":60}"#));
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+
+        let result = bjig.module("0121", "--port").get_parameter().await;
+        assert!(matches!(result, Err(BjigError::InvalidParameter(_))));
+        let result = bjig
+            .module("0121", "--port")
+            .set_parameter(&serde_json::json!({"interval": 30}))
+            .await;
+        assert!(matches!(result, Err(BjigError::InvalidParameter(_))));
+        assert!(mock.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restart_and_wait_polls_until_uplink() {
+        let bin = flaky_uplink_bjig(2);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        bjig.module("0121", "2468800203400004")
+            .restart_and_wait(Duration::from_millis(10), Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let polls = std::fs::read_to_string(bin.with_extension("polls")).unwrap();
+        assert_eq!(polls.lines().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_restart_and_wait_times_out() {
+        let bin = flaky_uplink_bjig(u32::MAX);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let result = bjig
+            .module("0121", "2468800203400004")
+            .restart_and_wait(Duration::from_millis(10), Duration::from_millis(200))
+            .await;
+
+        assert!(matches!(result, Err(BjigError::Timeout { secs: 1 })));
+    }
+
+    #[tokio::test]
+    async fn test_get_parameter_as() {
+        let bin = parameter_bjig(r#"{"interval":60,"threshold":10.5}"#);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let params: IlluminanceParameters = bjig
+            .module("0121", "2468800203400004")
+            .get_parameter_as()
+            .await
+            .unwrap();
+
+        assert_eq!(params.interval, Some(60));
+        assert_eq!(params.extra["threshold"], 10.5);
+    }
+
+    #[tokio::test]
+    async fn test_decode_failure_names_subcommand() {
+        let long = "x".repeat(300);
+        let bin = parameter_bjig(&format!(r#"{{"interval":"sixty","note":"{}"}}"#, long));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let err = bjig
+            .module("0121", "2468800203400004")
+            .get_parameter_as::<IlluminanceParameters>()
+            .await
+            .unwrap_err();
+
+        match &err {
+            BjigError::ResponseDecode { subcommand, raw, .. } => {
+                assert_eq!(subcommand, "module get-parameter");
+                assert!(raw.starts_with(r#"{"interval":"sixty""#));
+                assert_eq!(raw.chars().count(), 203);
+                assert!(raw.ends_with("..."));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert!(err.to_string().starts_with("Failed to decode `module get-parameter` response: invalid type"));
+    }
+
+    #[tokio::test]
+    async fn test_control_via_stdin_pipes_payload() {
+        let bin = fake_bjig(r#"cat > "$0.stdin"; echo '{"result":"success","message":"ok"}'"#);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let table: Vec<u32> = (0..20000).collect();
+        let data = serde_json::json!({"table": table});
+
+        let result = bjig
+            .module("0121", "2468800203400004")
+            .control_via_stdin(&data)
+            .await
+            .unwrap();
+
+        assert!(result.is_success());
+        let piped = std::fs::read(bin.with_extension("stdin")).unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&piped).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_control_via_stdin_child_exits_early() {
+        let bin = fake_bjig("echo 'busy' >&2; exit 1");
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let data = serde_json::json!({"blob": "x".repeat(1 << 20)});
+
+        let result = bjig.module("0121", "2468800203400004").control_via_stdin(&data).await;
+        assert!(matches!(result, Err(BjigError::CommandFailed(_))));
+    }
+
+    /// Runner whose `module dfu` never finishes, delegating everything else
+    #[derive(Debug)]
+    struct StalledDfu(std::sync::Arc<crate::runner::MockRunner>);
+
+    impl crate::runner::CommandRunner for StalledDfu {
+        fn run<'a>(&'a self, argv: &'a [String]) -> crate::runner::RunFuture<'a> {
+            if argv.iter().any(|arg| arg == "dfu") {
+                return Box::pin(std::future::pending());
+            }
+            self.0.run(argv)
+        }
+    }
+
+    fn abortable_dfu(
+        mock: crate::runner::MockRunner,
+    ) -> (BjigController, std::sync::Arc<crate::runner::MockRunner>) {
+        let mock = std::sync::Arc::new(mock);
+        let bjig = BjigController::builder()
+            .bin_path("/nonexistent/bjig")
+            .port("/dev/ttyACM0")
+            .runner(std::sync::Arc::new(StalledDfu(mock.clone())))
+            .build()
+            .unwrap();
+        (bjig, mock)
+    }
+
+    #[tokio::test]
+    async fn test_dfu_abort_kills_transfer() {
+        let (bjig, mock) = abortable_dfu(crate::runner::MockRunner::new());
+        let firmware = crate::test_support::temp_file("fw.bin", "firmware");
+
+        let task = bjig.module("0121", "2468800203400004").dfu_with_abort(&firmware).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(task.is_running());
+        assert!(bjig.executor_config().port_busy());
+
+        let outcome = task.abort().await.unwrap();
+        assert!(matches!(outcome, DfuOutcome::Aborted { forced: true }));
+        assert!(!bjig.executor_config().port_busy());
+        assert!(mock.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dfu_with_abort_is_not_retried() {
+        let mock = std::sync::Arc::new(
+            crate::runner::MockRunner::new().on_failure(&["module", "dfu"], "Error: timeout"),
+        );
+        let bjig = BjigController::builder()
+            .bin_path("/nonexistent/bjig")
+            .port("/dev/ttyACM0")
+            .runner(mock.clone())
+            .build()
+            .unwrap()
+            .with_retry(crate::RetryPolicy {
+                max_retries: 3,
+                delay: Duration::from_millis(1),
+            });
+        let firmware = crate::test_support::temp_file("fw.bin", "firmware");
+
+        let task = bjig.module("0121", "2468800203400004").dfu_with_abort(&firmware).unwrap();
+        assert!(matches!(task.wait().await, Err(BjigError::CommandFailed(_))));
+        assert_eq!(mock.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dfu_abort_after_completion() {
+        let mock = crate::runner::MockRunner::new()
+            .on(&["module", "dfu"], r#"{"result":"success","message":"DFU complete"}"#);
+        let bjig = BjigController::builder()
+            .bin_path("/nonexistent/bjig")
+            .port("/dev/ttyACM0")
+            .runner(std::sync::Arc::new(mock))
+            .build()
+            .unwrap();
+        let firmware = crate::test_support::temp_file("fw.bin", "firmware");
+
+        let task = bjig.module("0121", "2468800203400004").dfu_with_abort(&firmware).unwrap();
+        while task.is_running() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let outcome = task.abort().await.unwrap();
+        assert!(matches!(outcome, DfuOutcome::Completed(result) if result.is_success()));
     }
 }
@@ -1,8 +1,18 @@
 //! Monitor command implementation
 
+use std::collections::HashMap;
+use std::future::poll_fn;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::aggregate::IntervalEstimator;
 use crate::controller::BjigController;
-use crate::executor::CommandExecutor;
-use crate::types::Result;
+use crate::env::resolve_port;
+use crate::executor::{set_paused, CommandExecutor, StreamOutcome};
+use crate::export::CsvExporter;
+use crate::types::{BjigError, MonitorEvent, Result};
+use futures_util::{Sink, SinkExt};
 use tokio::sync::mpsc;
 
 /// Control messages for monitor process
@@ -46,6 +56,8 @@ pub(crate) enum ControlMessage {
 pub struct MonitorHandle {
     control_tx: mpsc::Sender<ControlMessage>,
     task_handle: tokio::task::JoinHandle<Result<()>>,
+    /// Pause state maintained by the streaming loop
+    paused: Arc<AtomicBool>,
 }
 
 impl MonitorHandle {
@@ -53,7 +65,7 @@ impl MonitorHandle {
     ///
     /// This pauses callback processing. Data from the monitor process continues
     /// to be received but callbacks are not invoked. When resumed, processing
-    /// continues with new data.
+    /// continues with new data. Pausing a paused monitor has no effect.
     ///
     /// # Errors
     ///
@@ -70,7 +82,8 @@ impl MonitorHandle {
     /// Resume the monitor
     ///
     /// This resumes callback processing after a pause. The monitor will
-    /// continue processing new data received from the device.
+    /// continue processing new data received from the device. Resuming a
+    /// running monitor has no effect.
     ///
     /// # Errors
     ///
@@ -113,6 +126,14 @@ impl MonitorHandle {
     pub fn is_running(&self) -> bool {
         !self.task_handle.is_finished()
     }
+
+    /// Check if monitor is currently paused
+    ///
+    /// The state changes once the monitor has processed a `pause` or
+    /// `resume` signal, so it may lag briefly behind those calls.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
 }
 
 impl Drop for MonitorHandle {
@@ -122,6 +143,204 @@ impl Drop for MonitorHandle {
     }
 }
 
+/// Predicate over parsed monitor events
+type EventPredicate = Box<dyn Fn(&MonitorEvent) -> bool + Send + Sync>;
+
+/// Filter applied to parsed monitor events before the callback is invoked
+///
+/// All configured criteria must match. Sensor and module ID criteria match
+/// if the event carries any of the listed IDs. An empty filter matches every
+/// event.
+///
+/// # Examples
+///
+/// ```
+/// use bjig_controller::MonitorFilter;
+///
+/// let filter = MonitorFilter::new()
+///     .with_sensor_id("0121")
+///     .with_predicate(|event| event.as_uplink().is_some());
+/// ```
+#[derive(Default)]
+pub struct MonitorFilter {
+    sensor_ids: Vec<String>,
+    module_ids: Vec<String>,
+    predicate: Option<EventPredicate>,
+}
+
+impl MonitorFilter {
+    /// Create an empty filter that matches every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match events from the given sensor ID (may be called multiple times)
+    pub fn with_sensor_id(mut self, sensor_id: impl Into<String>) -> Self {
+        self.sensor_ids.push(sensor_id.into());
+        self
+    }
+
+    /// Match events from the given module ID (may be called multiple times)
+    pub fn with_module_id(mut self, module_id: impl Into<String>) -> Self {
+        self.module_ids.push(module_id.into());
+        self
+    }
+
+    /// Match events for which the predicate returns `true`
+    pub fn with_predicate<P>(mut self, predicate: P) -> Self
+    where
+        P: Fn(&MonitorEvent) -> bool + Send + Sync + 'static,
+    {
+        self.predicate = Some(Box::new(predicate));
+        self
+    }
+
+    /// Check whether an event passes the filter
+    pub fn matches(&self, event: &MonitorEvent) -> bool {
+        if !self.sensor_ids.is_empty()
+            && !event
+                .sensor_id()
+                .is_some_and(|id| self.sensor_ids.iter().any(|s| s == id))
+        {
+            return false;
+        }
+
+        if !self.module_ids.is_empty()
+            && !event
+                .module_id()
+                .is_some_and(|id| self.module_ids.iter().any(|m| m == id))
+        {
+            return false;
+        }
+
+        match &self.predicate {
+            Some(predicate) => predicate(event),
+            None => true,
+        }
+    }
+}
+
+/// Extracts the deduplication key of a monitor event
+type DedupKeyFn = Box<dyn Fn(&MonitorEvent) -> Option<String> + Send + Sync>;
+
+/// Uplink fields tried, in order, by the default deduplication key
+const DEDUP_KEY_FIELDS: [&str; 4] = ["sequence_no", "sequence", "seq", "timestamp"];
+
+/// Suppresses repeated uplinks (e.g. retransmits) within a time window
+///
+/// Events are keyed by module ID plus the first of `sequence_no`,
+/// `sequence`, `seq` or `timestamp` present in the uplink. Events without a
+/// key are never suppressed. Use `with_key` for sensors that carry none of
+/// these fields.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use bjig_controller::{MonitorDedup, MonitorEvent};
+///
+/// let mut dedup = MonitorDedup::new(Duration::from_secs(10));
+/// let event = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","sequence_no":7}"#);
+///
+/// assert!(!dedup.is_duplicate(&event));
+/// assert!(dedup.is_duplicate(&event));
+/// ```
+pub struct MonitorDedup {
+    window: Duration,
+    key: DedupKeyFn,
+    seen: HashMap<String, Instant>,
+}
+
+impl MonitorDedup {
+    /// Create deduplicator suppressing repeats within `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            key: Box::new(default_dedup_key),
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Use a custom key; events for which it returns `None` always pass
+    pub fn with_key<K>(mut self, key: K) -> Self
+    where
+        K: Fn(&MonitorEvent) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key = Box::new(key);
+        self
+    }
+
+    /// Check whether an event received now repeats one seen within the window
+    ///
+    /// Non-duplicate events are remembered.
+    pub fn is_duplicate(&mut self, event: &MonitorEvent) -> bool {
+        self.is_duplicate_at(event, Instant::now())
+    }
+
+    /// Check whether an event received at `at` repeats one seen within the window
+    pub fn is_duplicate_at(&mut self, event: &MonitorEvent, at: Instant) -> bool {
+        let key = match (self.key)(event) {
+            Some(key) => key,
+            None => return false,
+        };
+
+        let window = self.window;
+        self.seen
+            .retain(|_, seen_at| at.saturating_duration_since(*seen_at) < window);
+
+        if self.seen.contains_key(&key) {
+            return true;
+        }
+        self.seen.insert(key, at);
+        false
+    }
+}
+
+fn default_dedup_key(event: &MonitorEvent) -> Option<String> {
+    let module_id = event.module_id()?;
+    let extra = &event.as_uplink()?.extra;
+    let value = DEDUP_KEY_FIELDS.iter().find_map(|field| extra.get(*field))?;
+
+    Some(format!("{}/{}", module_id, value))
+}
+
+/// Write `line` as pretty-printed JSON, or unchanged if it is not JSON
+///
+/// `buf` is cleared and reused for formatting.
+fn write_pretty<W: std::io::Write>(line: &str, buf: &mut Vec<u8>, out: &mut W) -> std::io::Result<()> {
+    buf.clear();
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(value) if serde_json::to_writer_pretty(&mut *buf, &value).is_ok() => {
+            buf.push(b'\n');
+            out.write_all(buf)
+        }
+        _ => writeln!(out, "{}", line),
+    }
+}
+
+/// Reconnection policy for long-running monitors
+///
+/// When the monitor process exits unexpectedly (e.g. the USB router
+/// re-enumerated), the monitor waits `delay` and re-spawns `bjig monitor`.
+/// After `max_attempts` consecutive reconnects without receiving any data,
+/// the monitor gives up and returns an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before each reconnect attempt
+    pub delay: Duration,
+    /// Maximum number of consecutive reconnect attempts
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_secs(5),
+            max_attempts: 5,
+        }
+    }
+}
+
 /// Monitor command interface
 ///
 /// Provides real-time monitoring of router and module events.
@@ -137,12 +356,8 @@ impl<'a> MonitorCommand<'a> {
     }
 
     /// Get command executor
-    fn executor(&self) -> CommandExecutor {
-        CommandExecutor::new(
-            &self.controller.bjig_path,
-            self.controller.default_port.as_deref(),
-            self.controller.default_baud,
-        )
+    fn executor(&self) -> CommandExecutor<'_> {
+        self.controller.executor()
     }
 
     /// Start real-time monitoring (runs until Ctrl+C)
@@ -158,7 +373,7 @@ impl<'a> MonitorCommand<'a> {
     ///
     /// let bjig = BjigController::from_env()?;
     ///
-    /// // Monitor indefinitely (until Ctrl+C)
+    /// // Monitor indefinitely (see `start_until_ctrl_c` for a clean stop)
     /// bjig.monitor().start().await?;
     /// # Ok(())
     /// # }
@@ -172,6 +387,98 @@ impl<'a> MonitorCommand<'a> {
         self.start_on_impl(Some(port), Some(baud), None).await
     }
 
+    /// Start monitoring until Ctrl+C is pressed
+    ///
+    /// Like `start`, but installs a SIGINT handler: on Ctrl+C the bjig
+    /// process is killed and `Ok(())` is returned, instead of the program
+    /// being terminated abruptly.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.monitor().start_until_ctrl_c().await?;
+    /// println!("Monitor stopped");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_until_ctrl_c(&self) -> Result<()> {
+        self.start_until_impl(None, None, ctrl_c()).await
+    }
+
+    /// Start monitoring on specific port until Ctrl+C is pressed
+    pub async fn start_until_ctrl_c_on(&self, port: &str, baud: u32) -> Result<()> {
+        self.start_until_impl(Some(port), Some(baud), ctrl_c()).await
+    }
+
+    /// Start monitoring until `shutdown` completes
+    ///
+    /// Like `start`, but when `shutdown` completes the bjig process is
+    /// killed and `Ok(())` is returned. Use it to stop on a custom signal,
+    /// e.g. SIGTERM or a cancellation channel.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use tokio::sync::oneshot;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    ///
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    ///     let _ = stop_tx.send(());
+    /// });
+    /// bjig.monitor().start_until(async { let _ = stop_rx.await; }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_until<S>(&self, shutdown: S) -> Result<()>
+    where
+        S: std::future::Future<Output = ()>,
+    {
+        self.start_until_impl(None, None, shutdown).await
+    }
+
+    /// Start monitoring on specific port until `shutdown` completes
+    pub async fn start_until_on<S>(&self, port: &str, baud: u32, shutdown: S) -> Result<()>
+    where
+        S: std::future::Future<Output = ()>,
+    {
+        self.start_until_impl(Some(port), Some(baud), shutdown).await
+    }
+
+    /// Start monitoring and print each line as pretty-printed JSON
+    ///
+    /// Like `start`, but intended for reading in a terminal. Lines that are
+    /// not valid JSON are printed unchanged. Object keys are printed in
+    /// sorted order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.monitor().start_pretty().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_pretty(&self) -> Result<()> {
+        self.start_pretty_impl(None, None).await
+    }
+
+    /// Start monitoring on specific port and print pretty-printed JSON
+    pub async fn start_pretty_on(&self, port: &str, baud: u32) -> Result<()> {
+        self.start_pretty_impl(Some(port), Some(baud)).await
+    }
+
     /// Start monitoring with TTL (time-to-live in seconds)
     ///
     /// The monitoring will automatically stop after the specified duration.
@@ -262,149 +569,982 @@ impl<'a> MonitorCommand<'a> {
             .await
     }
 
-    /// Start monitoring with handle for external control
+    /// Start monitoring with callback for at most `duration`
     ///
-    /// Returns a `MonitorHandle` that can be used to stop the monitor
-    /// from external code. The monitor runs in a background task.
+    /// `duration` is passed to bjig as `--ttl` (rounded up to whole seconds)
+    /// and is also enforced by the library: if bjig is still running when
+    /// the deadline hits, the process is killed and `Ok(())` is returned.
+    ///
+    /// # Arguments
+    /// * `duration` - Maximum monitoring time
+    /// * `callback` - Function called for each line. Returns Ok(true) to continue, Ok(false) to stop.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # async fn example() -> anyhow::Result<()> {
+    /// use std::time::Duration;
     /// use bjig_controller::BjigController;
-    /// use tokio::time::{sleep, Duration};
     ///
     /// let bjig = BjigController::from_env()?;
-    ///
-    /// // Start monitor with handle
-    /// let handle = bjig.monitor().start_with_handle().await?;
-    ///
-    /// // Let it run for 5 seconds
-    /// sleep(Duration::from_secs(5)).await;
-    ///
-    /// // Stop gracefully
-    /// handle.stop().await?;
+    /// bjig.monitor().start_for(Duration::from_secs(30), |line| {
+    ///     println!("Received: {}", line);
+    ///     Ok(true)
+    /// }).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn start_with_handle(&self) -> Result<MonitorHandle> {
-        self.start_with_handle_impl(None, None, None).await
+    pub async fn start_for<F>(&self, duration: Duration, callback: F) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<bool>,
+    {
+        self.start_for_impl(None, None, duration, callback).await
     }
 
-    /// Start monitoring on specific port with handle
-    pub async fn start_with_handle_on(&self, port: &str, baud: u32) -> Result<MonitorHandle> {
-        self.start_with_handle_impl(Some(port), Some(baud), None)
+    /// Start monitoring on specific port with callback for at most `duration`
+    pub async fn start_for_on<F>(&self, port: &str, baud: u32, duration: Duration, callback: F) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<bool>,
+    {
+        self.start_for_impl(Some(port), Some(baud), duration, callback)
             .await
     }
 
-    /// Start monitoring with TTL and handle
+    /// Start monitoring with a filter applied to parsed events
+    ///
+    /// Each line is parsed into a `MonitorEvent`. Events that do not match
+    /// the filter are skipped and the callback is not invoked for them.
     ///
     /// # Arguments
-    /// * `ttl_secs` - Time to live in seconds
+    /// * `filter` - Filter applied to each parsed event
+    /// * `callback` - Function called for each matching event. Returns Ok(true) to continue, Ok(false) to stop.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # async fn example() -> anyhow::Result<()> {
-    /// use bjig_controller::BjigController;
+    /// use bjig_controller::{BjigController, MonitorFilter};
     ///
     /// let bjig = BjigController::from_env()?;
+    /// let filter = MonitorFilter::new().with_sensor_id("0121");
     ///
-    /// // Monitor for max 60 seconds, but can be stopped early
-    /// let handle = bjig.monitor().start_with_ttl_and_handle(60).await?;
-    ///
-    /// // Stop before TTL expires
-    /// handle.stop().await?;
+    /// bjig.monitor().start_with_filter(filter, |event| {
+    ///     println!("Illuminance uplink: {:?}", event);
+    ///     Ok(true)
+    /// }).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn start_with_ttl_and_handle(&self, ttl_secs: u64) -> Result<MonitorHandle> {
-        self.start_with_handle_impl(None, None, Some(ttl_secs))
+    pub async fn start_with_filter<F>(&self, filter: MonitorFilter, callback: F) -> Result<()>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool>,
+    {
+        self.start_with_filter_on_impl(None, None, filter, callback)
             .await
     }
 
-    /// Start monitoring with callback and handle
+    /// Start monitoring on specific port with a filter applied to parsed events
+    pub async fn start_with_filter_on<F>(
+        &self,
+        port: &str,
+        baud: u32,
+        filter: MonitorFilter,
+        callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool>,
+    {
+        self.start_with_filter_on_impl(Some(port), Some(baud), filter, callback)
+            .await
+    }
+
+    /// Start monitoring with a custom line parser ahead of the callback
     ///
-    /// Combines callback functionality with external control via handle.
-    /// The callback receives each line and can stop monitoring by returning
-    /// `Ok(false)`. The handle can also be used to stop from external code.
+    /// For firmware whose monitor output is not JSON (e.g. CSV or
+    /// `key=value` lines). Each line is converted by `parser` and the result
+    /// passed to `callback`. Returning an error from `parser` stops
+    /// monitoring with that error; parse to an `Option` to skip lines
+    /// instead.
     ///
     /// # Arguments
-    /// * `callback` - Function called for each line. Returns Ok(true) to continue, Ok(false) to stop.
+    /// * `parser` - Function converting a monitor line into `T`
+    /// * `callback` - Function called with each parsed value. Returns Ok(true) to continue, Ok(false) to stop.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # async fn example() -> anyhow::Result<()> {
     /// use bjig_controller::BjigController;
-    /// use std::sync::{Arc, Mutex};
     ///
     /// let bjig = BjigController::from_env()?;
-    /// let count = Arc::new(Mutex::new(0));
-    /// let count_clone = count.clone();
-    ///
-    /// let handle = bjig.monitor().start_with_callback_and_handle(move |line| {
-    ///     println!("Received: {}", line);
-    ///     let mut c = count_clone.lock().unwrap();
-    ///     *c += 1;
-    ///     Ok(true) // Continue
-    /// }).await?;
     ///
-    /// // Stop from external code when needed
-    /// handle.stop().await?;
+    /// // Firmware emitting "module,lux" lines
+    /// bjig.monitor().start_with_parser(
+    ///     |line| Ok(line.split_once(',').map(|(m, l)| (m.to_string(), l.to_string()))),
+    ///     |reading| {
+    ///         if let Some((module_id, lux)) = reading {
+    ///             println!("{}: {} lx", module_id, lux);
+    ///         }
+    ///         Ok(true)
+    ///     },
+    /// ).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn start_with_callback_and_handle<F>(&self, callback: F) -> Result<MonitorHandle>
+    pub async fn start_with_parser<T, P, F>(&self, parser: P, callback: F) -> Result<()>
     where
-        F: FnMut(&str) -> Result<bool> + Send + 'static,
+        P: FnMut(&str) -> Result<T>,
+        F: FnMut(T) -> Result<bool>,
     {
-        self.start_with_callback_and_handle_impl(None, None, None, callback)
+        self.start_with_parser_impl(None, None, parser, callback)
             .await
     }
 
-    /// Start monitoring on specific port with callback and handle
-    pub async fn start_with_callback_and_handle_on<F>(
+    /// Start monitoring on specific port with a custom line parser
+    pub async fn start_with_parser_on<T, P, F>(
         &self,
         port: &str,
         baud: u32,
+        parser: P,
         callback: F,
-    ) -> Result<MonitorHandle>
+    ) -> Result<()>
     where
-        F: FnMut(&str) -> Result<bool> + Send + 'static,
+        P: FnMut(&str) -> Result<T>,
+        F: FnMut(T) -> Result<bool>,
     {
-        self.start_with_callback_and_handle_impl(Some(port), Some(baud), None, callback)
+        self.start_with_parser_impl(Some(port), Some(baud), parser, callback)
             .await
     }
 
-    /// Start monitoring with TTL, callback, and handle
-    pub async fn start_with_ttl_callback_and_handle<F>(
+    async fn start_with_parser_impl<T, P, F>(
         &self,
-        ttl_secs: u64,
-        callback: F,
-    ) -> Result<MonitorHandle>
+        port: Option<&str>,
+        baud: Option<u32>,
+        mut parser: P,
+        mut callback: F,
+    ) -> Result<()>
     where
-        F: FnMut(&str) -> Result<bool> + Send + 'static,
+        P: FnMut(&str) -> Result<T>,
+        F: FnMut(T) -> Result<bool>,
     {
-        self.start_with_callback_and_handle_impl(None, None, Some(ttl_secs), callback)
+        self.start_with_callback_on_impl(port, baud, None, |line| callback(parser(line)?))
             .await
     }
 
-    async fn start_on_impl(
-        &self,
-        port: Option<&str>,
-        baud: Option<u32>,
-        ttl_secs: Option<u64>,
-    ) -> Result<()> {
-        let executor = self.executor();
+    /// Start monitoring with repeated uplinks suppressed
+    ///
+    /// Uses `MonitorDedup` with the default key; see `start_dedup_with` to
+    /// override it.
+    ///
+    /// # Arguments
+    /// * `window` - Repeats within this duration of the first occurrence are dropped
+    /// * `callback` - Function called for each non-duplicate event. Returns Ok(true) to continue, Ok(false) to stop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use std::time::Duration;
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    ///
+    /// bjig.monitor().start_dedup(Duration::from_secs(30), |event| {
+    ///     println!("{:?}", event);
+    ///     Ok(true)
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_dedup<F>(&self, window: Duration, callback: F) -> Result<()>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool>,
+    {
+        self.start_dedup_with(MonitorDedup::new(window), callback)
+            .await
+    }
 
-        let mut args = vec!["monitor"];
+    /// Start monitoring with repeated uplinks suppressed by a configured `MonitorDedup`
+    pub async fn start_dedup_with<F>(&self, mut dedup: MonitorDedup, mut callback: F) -> Result<()>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool>,
+    {
+        self.start_with_filter(MonitorFilter::new(), |event| {
+            if dedup.is_duplicate(event) {
+                Ok(true)
+            } else {
+                callback(event)
+            }
+        })
+        .await
+    }
 
-        let ttl_str;
-        if let Some(ttl) = ttl_secs {
-            ttl_str = ttl.to_string();
-            args.push("--ttl");
-            args.push(&ttl_str);
+    /// Start monitoring with a callback for each uplink carrying an RSSI value
+    ///
+    /// Uplinks without a parseable `rssi` field and non-uplink lines are
+    /// skipped.
+    ///
+    /// # Arguments
+    /// * `callback` - Function called with the module ID and RSSI. Returns Ok(true) to continue, Ok(false) to stop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    ///
+    /// bjig.monitor().start_rssi_callback(|module_id, rssi| {
+    ///     println!("{}: {} dBm", module_id, rssi);
+    ///     Ok(true)
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_rssi_callback<F>(&self, mut callback: F) -> Result<()>
+    where
+        F: FnMut(&str, i16) -> Result<bool>,
+    {
+        self.start_with_filter(MonitorFilter::new(), |event| match (event.module_id(), event.rssi()) {
+            (Some(module_id), Some(rssi)) => callback(module_id, rssi),
+            _ => Ok(true),
+        })
+        .await
+    }
+
+    /// Collect RSSI samples per module over a time window
+    ///
+    /// Monitors for `duration` and returns the RSSI values received, in
+    /// arrival order, keyed by module ID. Modules whose uplinks carry no RSSI
+    /// are not included. The monitor process is killed when the window ends;
+    /// if it exits earlier, the samples collected so far are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use std::time::Duration;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let samples = bjig.monitor().rssi_histogram(Duration::from_secs(60)).await?;
+    ///
+    /// for (module_id, rssi) in &samples {
+    ///     let mean = rssi.iter().map(|&r| f64::from(r)).sum::<f64>() / rssi.len() as f64;
+    ///     println!("{}: {} samples, mean {:.1} dBm", module_id, rssi.len(), mean);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rssi_histogram(&self, duration: Duration) -> Result<HashMap<String, Vec<i16>>> {
+        let mut samples: HashMap<String, Vec<i16>> = HashMap::new();
+
+        let monitor = self.start_rssi_callback(|module_id, rssi| {
+            samples.entry(module_id.to_string()).or_default().push(rssi);
+            Ok(true)
+        });
+
+        match tokio::time::timeout(duration, monitor).await {
+            Ok(result) => result?,
+            Err(_) => log::debug!("RSSI collection window of {:?} elapsed", duration),
+        }
+
+        Ok(samples)
+    }
+
+    /// Estimate each module's reporting interval over a time window
+    ///
+    /// Monitors for `window` and returns the median time between
+    /// consecutive uplinks keyed by module ID (see `IntervalEstimator`).
+    /// Modules with fewer than two uplinks in the window are excluded, so
+    /// the window should span at least two reporting intervals. The monitor
+    /// process is killed when the window ends.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use std::time::Duration;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let intervals = bjig.monitor().estimate_intervals(Duration::from_secs(600)).await?;
+    ///
+    /// for (module_id, interval) in &intervals {
+    ///     println!("{}: every {}s", module_id, interval.as_secs());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn estimate_intervals(&self, window: Duration) -> Result<HashMap<String, Duration>> {
+        let mut estimator = IntervalEstimator::new();
+
+        let monitor = self.start_with_filter(MonitorFilter::new(), |event| {
+            estimator.record(event);
+            Ok(true)
+        });
+
+        match tokio::time::timeout(window, monitor).await {
+            Ok(result) => result?,
+            Err(_) => log::debug!("Interval estimation window of {:?} elapsed", window),
+        }
+
+        Ok(estimator.estimates())
+    }
+
+    /// Wait for the next uplink from a specific module
+    ///
+    /// Starts a monitor, returns the first uplink carrying `module_id`, and
+    /// stops the monitor. The monitor process is killed both when an uplink
+    /// arrives and when the timeout expires.
+    ///
+    /// # Errors
+    /// Returns `BjigError::Timeout` (rounded up to whole seconds) if no
+    /// matching uplink arrives in time and `BjigError::CommandFailed` if the
+    /// monitor exits before one arrives.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use std::time::Duration;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let monitor = bjig.monitor();
+    /// let module = bjig.module("0121", "2468800203400004");
+    ///
+    /// let (event, _) = tokio::join!(
+    ///     monitor.next_uplink_for("2468800203400004", Duration::from_secs(30)),
+    ///     module.instant_uplink(),
+    /// );
+    /// println!("{:?}", event?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn next_uplink_for(&self, module_id: &str, timeout: Duration) -> Result<MonitorEvent> {
+        let filter = MonitorFilter::new().with_module_id(module_id);
+        let mut received = None;
+
+        let monitor = self.start_with_filter(filter, |event| {
+            received = Some(event.clone());
+            Ok(false)
+        });
+
+        match tokio::time::timeout(timeout, monitor).await {
+            Ok(result) => result?,
+            Err(_) => {
+                log::debug!(
+                    "No uplink from module {} within {:?}",
+                    self.controller.config.log_id(module_id),
+                    timeout
+                );
+                return Err(BjigError::timeout_after(timeout));
+            }
+        }
+
+        received.ok_or_else(|| {
+            BjigError::CommandFailed(format!(
+                "Monitor exited before an uplink from module {} arrived",
+                module_id
+            ))
+        })
+    }
+
+    /// Start monitoring and report modules that stop sending uplinks
+    ///
+    /// Tracks when each module last sent an uplink. When a module goes
+    /// longer than `per_module_timeout` without one, `on_silence` is called
+    /// with its module ID; it is called again only after the module has
+    /// sent another uplink and then fallen silent again. Modules are tracked
+    /// from their first uplink on. Runs until the monitor process exits.
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` if `per_module_timeout` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use std::time::Duration;
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.monitor()
+    ///     .start_with_silence_detection(Duration::from_secs(600), |module_id| {
+    ///         eprintln!("Module {} has gone silent", module_id);
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_with_silence_detection<F>(
+        &self,
+        per_module_timeout: Duration,
+        on_silence: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&str),
+    {
+        self.start_with_silence_detection_impl(None, None, per_module_timeout, on_silence)
+            .await
+    }
+
+    /// Start monitoring on specific port and report silent modules
+    pub async fn start_with_silence_detection_on<F>(
+        &self,
+        port: &str,
+        baud: u32,
+        per_module_timeout: Duration,
+        on_silence: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&str),
+    {
+        self.start_with_silence_detection_impl(Some(port), Some(baud), per_module_timeout, on_silence)
+            .await
+    }
+
+    async fn start_with_silence_detection_impl<F>(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        per_module_timeout: Duration,
+        mut on_silence: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&str),
+    {
+        if per_module_timeout.is_zero() {
+            return Err(BjigError::InvalidParameter(
+                "Silence timeout must be greater than zero".to_string(),
+            ));
+        }
+
+        let mut lines = self.executor().spawn_line_stream(&["monitor"], port, baud)?;
+        // Check often enough that silence is reported soon after the timeout
+        let mut ticker = tokio::time::interval((per_module_timeout / 4).max(Duration::from_millis(10)));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // Last uplink per module, and whether its current silence was reported
+        let mut last_seen: HashMap<String, (Instant, bool)> = HashMap::new();
+
+        loop {
+            tokio::select! {
+                line = lines.recv() => {
+                    let line = match line {
+                        Some(line) => line?,
+                        None => return Ok(()),
+                    };
+                    if let Some(uplink) = MonitorEvent::parse(&line).as_uplink() {
+                        last_seen.insert(uplink.module_id.clone(), (Instant::now(), false));
+                    }
+                }
+                _ = ticker.tick() => {
+                    for (module_id, (seen, reported)) in last_seen.iter_mut() {
+                        if !*reported && seen.elapsed() >= per_module_timeout {
+                            log::debug!(
+                                "Module {} silent for {:?}",
+                                self.controller.config.log_id(module_id),
+                                seen.elapsed()
+                            );
+                            *reported = true;
+                            on_silence(module_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start monitoring and export uplinks as CSV rows
+    ///
+    /// The header row (if enabled on the exporter) is written first. Non-uplink
+    /// lines are skipped. Monitoring stops after `max_items` rows have been
+    /// written and the writer is flushed.
+    ///
+    /// # Arguments
+    /// * `exporter` - Column mapping for the CSV rows
+    /// * `writer` - CSV writer receiving the rows
+    /// * `max_items` - Stop after this many rows
+    ///
+    /// # Returns
+    /// Number of rows written (excluding the header)
+    pub async fn start_with_csv_export<W: std::io::Write>(
+        &self,
+        exporter: &CsvExporter,
+        writer: &mut csv::Writer<W>,
+        max_items: usize,
+    ) -> Result<usize> {
+        exporter.write_header(writer)?;
+
+        let mut rows = 0;
+        if max_items > 0 {
+            self.start_with_callback(|line| {
+                if exporter.write_event(writer, &MonitorEvent::parse(line))? {
+                    rows += 1;
+                }
+                Ok(rows < max_items)
+            })
+            .await?;
+        }
+
+        writer.flush()?;
+        Ok(rows)
+    }
+
+    /// Start monitoring with callback, copying every raw line to a log file
+    ///
+    /// The log is gzip-compressed if `path` ends in `.gz`. Each line is
+    /// written and flushed before it is passed to the callback, so the log is
+    /// readable even if the process is killed mid-capture.
+    ///
+    /// # Arguments
+    /// * `path` - Log file to create (truncated if it exists)
+    /// * `callback` - Function called for each line. Returns Ok(true) to continue, Ok(false) to stop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.monitor().start_with_tee("monitor.log.gz", |line| {
+    ///     println!("Received: {}", line);
+    ///     Ok(true)
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_with_tee<P, F>(&self, path: P, callback: F) -> Result<()>
+    where
+        P: AsRef<std::path::Path>,
+        F: FnMut(&str) -> Result<bool>,
+    {
+        let compression = crate::export::TeeCompression::from_path(&path);
+        self.start_with_tee_compressed(path, compression, callback)
+            .await
+    }
+
+    /// Start monitoring with callback, copying every raw line to a log file
+    /// with the given compression
+    pub async fn start_with_tee_compressed<P, F>(
+        &self,
+        path: P,
+        compression: crate::export::TeeCompression,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        P: AsRef<std::path::Path>,
+        F: FnMut(&str) -> Result<bool>,
+    {
+        let mut log = crate::export::TeeLog::create_with(path, compression)?;
+
+        self.start_with_callback(|line| {
+            log.write_line(line)?;
+            callback(line)
+        })
+        .await?;
+
+        log.finish()
+    }
+
+    /// Start monitoring and export uplinks as a Parquet file
+    ///
+    /// Uplinks are buffered in `sink`; non-uplink lines are skipped. After
+    /// `max_items` rows have been collected, or the monitor exits, the rows
+    /// are written to `writer` as a single Parquet file. The file is also
+    /// written when the monitor fails; the monitor's error is returned
+    /// afterwards.
+    ///
+    /// # Arguments
+    /// * `sink` - Column spec and row buffer
+    /// * `writer` - Destination of the Parquet file
+    /// * `max_items` - Stop after this many rows
+    ///
+    /// # Returns
+    /// Number of rows written
+    #[cfg(feature = "parquet")]
+    pub async fn start_with_parquet_export<W: std::io::Write + Send>(
+        &self,
+        sink: &mut crate::export::ParquetSink,
+        writer: W,
+        max_items: usize,
+    ) -> Result<usize> {
+        let monitored = if max_items > 0 {
+            self.start_with_callback(|line| {
+                sink.record(&MonitorEvent::parse(line))?;
+                Ok(sink.len() < max_items)
+            })
+            .await
+        } else {
+            Ok(())
+        };
+
+        // Keep the rows collected so far even if the monitor failed
+        let rows = sink.len();
+        sink.write(writer)?;
+        monitored.map(|()| rows)
+    }
+
+    /// Replay a recorded JSONL capture through the event callback
+    ///
+    /// Each non-empty line of the file is parsed into a `MonitorEvent` and
+    /// passed to the callback, as during live monitoring. Events are
+    /// delivered as fast as possible; use `replay_timed` to reproduce the
+    /// recorded timing. No bjig process is spawned.
+    ///
+    /// # Arguments
+    /// * `path` - Capture file with one monitor line per line
+    /// * `callback` - Function called for each event. Returns Ok(true) to continue, Ok(false) to stop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.monitor().replay("capture.jsonl", |event| {
+    ///     println!("Replayed: {:?}", event);
+    ///     Ok(true)
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn replay<P, F>(&self, path: P, callback: F) -> Result<()>
+    where
+        P: AsRef<std::path::Path>,
+        F: FnMut(&MonitorEvent) -> Result<bool>,
+    {
+        self.replay_impl(path.as_ref(), None, callback).await
+    }
+
+    /// Replay a recorded JSONL capture, honoring the recorded timing
+    ///
+    /// Before each event, waits for the gap between its `timestamp` and the
+    /// previous timestamped event, divided by `speed` (`1.0` is real time,
+    /// `2.0` twice as fast). Numeric timestamps are read as Unix seconds;
+    /// with the `chrono` feature, RFC 3339 strings are supported as well.
+    /// Events without a timestamp are delivered immediately.
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` if `speed` is not a positive number.
+    pub async fn replay_timed<P, F>(&self, path: P, speed: f64, callback: F) -> Result<()>
+    where
+        P: AsRef<std::path::Path>,
+        F: FnMut(&MonitorEvent) -> Result<bool>,
+    {
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(BjigError::InvalidParameter(format!(
+                "Replay speed must be positive, got {}",
+                speed
+            )));
+        }
+        self.replay_impl(path.as_ref(), Some(speed), callback).await
+    }
+
+    /// Start monitoring with handle for external control
+    ///
+    /// Returns a `MonitorHandle` that can be used to stop the monitor
+    /// from external code. The monitor runs in a background task.
+    ///
+    /// # Errors
+    /// Returns `BjigError::PortNotConfigured` immediately if neither a port
+    /// override nor a default port is set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use tokio::time::{sleep, Duration};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    ///
+    /// // Start monitor with handle
+    /// let handle = bjig.monitor().start_with_handle().await?;
+    ///
+    /// // Let it run for 5 seconds
+    /// sleep(Duration::from_secs(5)).await;
+    ///
+    /// // Stop gracefully
+    /// handle.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_with_handle(&self) -> Result<MonitorHandle> {
+        self.start_with_handle_impl(None, None, None).await
+    }
+
+    /// Start monitoring on specific port with handle
+    pub async fn start_with_handle_on(&self, port: &str, baud: u32) -> Result<MonitorHandle> {
+        self.start_with_handle_impl(Some(port), Some(baud), None)
+            .await
+    }
+
+    /// Start monitoring with TTL and handle
+    ///
+    /// # Arguments
+    /// * `ttl_secs` - Time to live in seconds
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    ///
+    /// // Monitor for max 60 seconds, but can be stopped early
+    /// let handle = bjig.monitor().start_with_ttl_and_handle(60).await?;
+    ///
+    /// // Stop before TTL expires
+    /// handle.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_with_ttl_and_handle(&self, ttl_secs: u64) -> Result<MonitorHandle> {
+        self.start_with_handle_impl(None, None, Some(ttl_secs))
+            .await
+    }
+
+    /// Start monitoring with callback and handle
+    ///
+    /// Combines callback functionality with external control via handle.
+    /// The callback receives each line and can stop monitoring by returning
+    /// `Ok(false)`. The handle can also be used to stop from external code.
+    ///
+    /// # Arguments
+    /// * `callback` - Function called for each line. Returns Ok(true) to continue, Ok(false) to stop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let count = Arc::new(Mutex::new(0));
+    /// let count_clone = count.clone();
+    ///
+    /// let handle = bjig.monitor().start_with_callback_and_handle(move |line| {
+    ///     println!("Received: {}", line);
+    ///     let mut c = count_clone.lock().unwrap();
+    ///     *c += 1;
+    ///     Ok(true) // Continue
+    /// }).await?;
+    ///
+    /// // Stop from external code when needed
+    /// handle.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_with_callback_and_handle<F>(&self, callback: F) -> Result<MonitorHandle>
+    where
+        F: FnMut(&str) -> Result<bool> + Send + 'static,
+    {
+        self.start_with_callback_and_handle_impl(None, None, None, callback)
+            .await
+    }
+
+    /// Start monitoring on specific port with callback and handle
+    pub async fn start_with_callback_and_handle_on<F>(
+        &self,
+        port: &str,
+        baud: u32,
+        callback: F,
+    ) -> Result<MonitorHandle>
+    where
+        F: FnMut(&str) -> Result<bool> + Send + 'static,
+    {
+        self.start_with_callback_and_handle_impl(Some(port), Some(baud), None, callback)
+            .await
+    }
+
+    /// Start monitoring with TTL, callback, and handle
+    pub async fn start_with_ttl_callback_and_handle<F>(
+        &self,
+        ttl_secs: u64,
+        callback: F,
+    ) -> Result<MonitorHandle>
+    where
+        F: FnMut(&str) -> Result<bool> + Send + 'static,
+    {
+        self.start_with_callback_and_handle_impl(None, None, Some(ttl_secs), callback)
+            .await
+    }
+
+    /// Start monitoring and send parsed events to a channel
+    ///
+    /// Each line is parsed into a `MonitorEvent` and sent to `tx`. When the
+    /// channel is full, reading pauses until the receiver catches up. The
+    /// monitor stops when the receiver is dropped or via the returned handle.
+    ///
+    /// # Arguments
+    /// * `tx` - Sender receiving the events
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    /// use tokio::sync::mpsc;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let (tx, mut rx) = mpsc::channel(64);
+    ///
+    /// let handle = bjig.monitor().start_to_channel(tx).await?;
+    /// while let Some(event) = rx.recv().await {
+    ///     if let Some(uplink) = event.as_uplink() {
+    ///         println!("{}: {:?}", uplink.module_id, uplink.extra);
+    ///     }
+    /// }
+    /// handle.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_to_channel(&self, tx: mpsc::Sender<MonitorEvent>) -> Result<MonitorHandle> {
+        self.start_to_channel_impl(None, None, tx).await
+    }
+
+    /// Start monitoring on specific port and send parsed events to a channel
+    pub async fn start_to_channel_on(
+        &self,
+        port: &str,
+        baud: u32,
+        tx: mpsc::Sender<MonitorEvent>,
+    ) -> Result<MonitorHandle> {
+        self.start_to_channel_impl(Some(port), Some(baud), tx).await
+    }
+
+    /// Start monitoring and forward parsed events to a `futures::Sink`
+    ///
+    /// Each line is parsed into a `MonitorEvent` and sent to `sink`. While the
+    /// sink is not ready, reading pauses until it accepts more items. The
+    /// monitor stops when the sink returns an error (e.g. because it was
+    /// closed) or via the returned handle; the sink is closed when the monitor
+    /// stops.
+    ///
+    /// # Arguments
+    /// * `sink` - Sink receiving the events, e.g. a websocket adapter
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, MonitorEvent};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let sink = futures_util::sink::unfold((), |(), event: MonitorEvent| async move {
+    ///     // Forward to a websocket here
+    ///     println!("{:?}", event);
+    ///     Ok::<_, std::convert::Infallible>(())
+    /// });
+    ///
+    /// let handle = bjig.monitor().start_to_sink(sink).await?;
+    /// tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    /// handle.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_to_sink<Si>(&self, sink: Si) -> Result<MonitorHandle>
+    where
+        Si: Sink<MonitorEvent> + Send + 'static,
+        Si::Error: std::fmt::Display,
+    {
+        self.start_to_sink_impl(None, None, sink).await
+    }
+
+    /// Start monitoring on specific port and forward parsed events to a `futures::Sink`
+    pub async fn start_to_sink_on<Si>(&self, port: &str, baud: u32, sink: Si) -> Result<MonitorHandle>
+    where
+        Si: Sink<MonitorEvent> + Send + 'static,
+        Si::Error: std::fmt::Display,
+    {
+        self.start_to_sink_impl(Some(port), Some(baud), sink).await
+    }
+
+    /// Start monitoring with automatic reconnection and handle
+    ///
+    /// If the monitor process exits unexpectedly, it is re-spawned according
+    /// to `policy` and `MonitorEvent::Reconnected` is passed to the callback
+    /// before streaming resumes. The attempt counter resets once a
+    /// reconnected monitor delivers data. Stopping via the handle also works
+    /// while waiting between attempts.
+    ///
+    /// # Arguments
+    /// * `policy` - Reconnection delay and attempt limit
+    /// * `callback` - Function called for each parsed event. Returns Ok(true) to continue, Ok(false) to stop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, MonitorEvent, ReconnectPolicy};
+    /// use std::time::Duration;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let policy = ReconnectPolicy {
+    ///     delay: Duration::from_secs(3),
+    ///     max_attempts: 10,
+    /// };
+    ///
+    /// let handle = bjig.monitor().start_with_reconnect(policy, |event| {
+    ///     if let MonitorEvent::Reconnected { attempt } = event {
+    ///         println!("Monitor reconnected (attempt {})", attempt);
+    ///     }
+    ///     Ok(true)
+    /// }).await?;
+    ///
+    /// handle.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_with_reconnect<F>(
+        &self,
+        policy: ReconnectPolicy,
+        callback: F,
+    ) -> Result<MonitorHandle>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool> + Send + 'static,
+    {
+        self.start_with_reconnect_impl(None, None, policy, callback)
+            .await
+    }
+
+    /// Start monitoring on specific port with automatic reconnection and handle
+    pub async fn start_with_reconnect_on<F>(
+        &self,
+        port: &str,
+        baud: u32,
+        policy: ReconnectPolicy,
+        callback: F,
+    ) -> Result<MonitorHandle>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool> + Send + 'static,
+    {
+        self.start_with_reconnect_impl(Some(port), Some(baud), policy, callback)
+            .await
+    }
+
+    async fn start_on_impl(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        ttl_secs: Option<u64>,
+    ) -> Result<()> {
+        let executor = self.executor();
+
+        let mut args = vec!["monitor"];
+
+        let ttl_str;
+        if let Some(ttl) = ttl_secs {
+            ttl_str = ttl.to_string();
+            args.push("--ttl");
+            args.push(&ttl_str);
         }
 
         // Monitor command outputs to stdout continuously in real-time
@@ -414,6 +1554,33 @@ impl<'a> MonitorCommand<'a> {
         Ok(())
     }
 
+    async fn start_until_impl<S>(&self, port: Option<&str>, baud: Option<u32>, shutdown: S) -> Result<()>
+    where
+        S: std::future::Future<Output = ()>,
+    {
+        // Dropping the streaming future kills the child
+        tokio::select! {
+            result = self.start_on_impl(port, baud, None) => result,
+            () = shutdown => {
+                log::debug!("Shutdown requested, terminating bjig monitor");
+                Ok(())
+            }
+        }
+    }
+
+    async fn start_pretty_impl(&self, port: Option<&str>, baud: Option<u32>) -> Result<()> {
+        let stdout = std::io::stdout();
+        // Reused across lines to avoid an allocation per uplink
+        let mut buf = Vec::new();
+
+        self.executor()
+            .execute_streaming_with_callback(&["monitor"], port, baud, |line| {
+                write_pretty(line, &mut buf, &mut stdout.lock())?;
+                Ok(true)
+            })
+            .await
+    }
+
     async fn start_with_callback_on_impl<F>(
         &self,
         port: Option<&str>,
@@ -444,6 +1611,49 @@ impl<'a> MonitorCommand<'a> {
         Ok(())
     }
 
+    async fn start_for_impl<F>(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        duration: Duration,
+        callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<bool>,
+    {
+        let ttl_secs = duration.as_millis().div_ceil(1000).max(1) as u64;
+        let streaming = self.start_with_callback_on_impl(port, baud, Some(ttl_secs), callback);
+
+        // Dropping the streaming future kills the child
+        match tokio::time::timeout(duration, streaming).await {
+            Ok(result) => result,
+            Err(_) => {
+                log::debug!("Monitor deadline of {:?} reached, terminating bjig", duration);
+                Ok(())
+            }
+        }
+    }
+
+    async fn start_with_filter_on_impl<F>(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        filter: MonitorFilter,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool>,
+    {
+        self.start_with_callback_on_impl(port, baud, None, move |line| {
+            let event = MonitorEvent::parse(line);
+            if !filter.matches(&event) {
+                return Ok(true);
+            }
+            callback(&event)
+        })
+        .await
+    }
+
     async fn start_with_handle_impl(
         &self,
         port: Option<&str>,
@@ -451,21 +1661,21 @@ impl<'a> MonitorCommand<'a> {
         ttl_secs: Option<u64>,
     ) -> Result<MonitorHandle> {
         // Clone necessary data to move into task
-        let bjig_path = self.controller.bjig_path.clone();
-        let default_port = self.controller.default_port.clone();
-        let default_baud = self.controller.default_baud;
+        let config = self.controller.executor_config();
         let port_owned = port.map(|s| s.to_string());
 
+        // Fail now rather than through the task handle
+        resolve_port(port, config.default_port.as_deref())?;
+
         // Create channel for control signals
         let (control_tx, control_rx) = mpsc::channel(10);
+        self.controller.register_monitor(&control_tx)?;
+        let paused = Arc::new(AtomicBool::new(false));
+        let task_paused = Arc::clone(&paused);
 
         // Spawn monitor task
         let task_handle = tokio::spawn(async move {
-            let executor = CommandExecutor::new(
-                &bjig_path,
-                default_port.as_deref(),
-                default_baud,
-            );
+            let executor = config.executor();
 
             let mut args_vec = vec!["monitor".to_string()];
             if let Some(ttl) = ttl_secs {
@@ -475,13 +1685,14 @@ impl<'a> MonitorCommand<'a> {
             let args: Vec<&str> = args_vec.iter().map(|s| s.as_str()).collect();
 
             executor
-                .execute_streaming_with_control(&args, port_owned.as_deref(), baud, control_rx)
+                .execute_streaming_with_control(&args, port_owned.as_deref(), baud, control_rx, &task_paused)
                 .await
         });
 
         Ok(MonitorHandle {
             control_tx,
             task_handle,
+            paused,
         })
     }
 
@@ -496,21 +1707,21 @@ impl<'a> MonitorCommand<'a> {
         F: FnMut(&str) -> Result<bool> + Send + 'static,
     {
         // Clone necessary data to move into task
-        let bjig_path = self.controller.bjig_path.clone();
-        let default_port = self.controller.default_port.clone();
-        let default_baud = self.controller.default_baud;
+        let config = self.controller.executor_config();
         let port_owned = port.map(|s| s.to_string());
 
+        // Fail now rather than through the task handle
+        resolve_port(port, config.default_port.as_deref())?;
+
         // Create channel for control signals
         let (control_tx, control_rx) = mpsc::channel(10);
+        self.controller.register_monitor(&control_tx)?;
+        let paused = Arc::new(AtomicBool::new(false));
+        let task_paused = Arc::clone(&paused);
 
         // Spawn monitor task
         let task_handle = tokio::spawn(async move {
-            let executor = CommandExecutor::new(
-                &bjig_path,
-                default_port.as_deref(),
-                default_baud,
-            );
+            let executor = config.executor();
 
             let mut args_vec = vec!["monitor".to_string()];
             if let Some(ttl) = ttl_secs {
@@ -520,13 +1731,1053 @@ impl<'a> MonitorCommand<'a> {
             let args: Vec<&str> = args_vec.iter().map(|s| s.as_str()).collect();
 
             executor
-                .execute_streaming_with_callback_and_control(&args, port_owned.as_deref(), baud, callback, control_rx)
+                .execute_streaming_with_callback_and_control(
+                    &args,
+                    port_owned.as_deref(),
+                    baud,
+                    callback,
+                    control_rx,
+                    &task_paused,
+                )
                 .await
         });
 
         Ok(MonitorHandle {
             control_tx,
             task_handle,
+            paused,
+        })
+    }
+
+    async fn start_to_channel_impl(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        tx: mpsc::Sender<MonitorEvent>,
+    ) -> Result<MonitorHandle> {
+        let (control_tx, mut control_rx) = mpsc::channel(10);
+        self.controller.register_monitor(&control_tx)?;
+        let paused = Arc::new(AtomicBool::new(false));
+        let task_paused = Arc::clone(&paused);
+
+        // Dropping the line stream kills the child process
+        let mut lines = self.executor().spawn_line_stream(&["monitor"], port, baud)?;
+
+        let task_handle = tokio::spawn(async move {
+            // Event waiting for channel capacity; no lines are read meanwhile
+            let mut pending: Option<MonitorEvent> = None;
+
+            loop {
+                tokio::select! {
+                    line = lines.recv(), if pending.is_none() => {
+                        match line.transpose()? {
+                            Some(line) if !task_paused.load(Ordering::SeqCst) => {
+                                pending = Some(MonitorEvent::parse(&line));
+                            }
+                            Some(_) => {}
+                            None => return Ok(()),
+                        }
+                    }
+                    permit = tx.reserve(), if pending.is_some() => {
+                        match permit {
+                            Ok(permit) => permit.send(pending.take().unwrap()),
+                            Err(_) => {
+                                log::debug!("Event receiver dropped, stopping monitor");
+                                return Ok(());
+                            }
+                        }
+                    }
+                    _ = tx.closed() => {
+                        log::debug!("Event receiver dropped, stopping monitor");
+                        return Ok(());
+                    }
+                    msg = control_rx.recv() => {
+                        match msg {
+                            Some(ControlMessage::Stop) | None => {
+                                log::info!("Stop signal received, terminating monitor");
+                                return Ok(());
+                            }
+                            Some(ControlMessage::Pause) => set_paused(&task_paused, true),
+                            Some(ControlMessage::Resume) => set_paused(&task_paused, false),
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(MonitorHandle {
+            control_tx,
+            task_handle,
+            paused,
+        })
+    }
+
+    async fn start_to_sink_impl<Si>(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        sink: Si,
+    ) -> Result<MonitorHandle>
+    where
+        Si: Sink<MonitorEvent> + Send + 'static,
+        Si::Error: std::fmt::Display,
+    {
+        let (control_tx, mut control_rx) = mpsc::channel(10);
+        self.controller.register_monitor(&control_tx)?;
+        let paused = Arc::new(AtomicBool::new(false));
+        let task_paused = Arc::clone(&paused);
+
+        // Dropping the line stream kills the child process
+        let mut lines = self.executor().spawn_line_stream(&["monitor"], port, baud)?;
+        let mut sink = Box::pin(sink);
+
+        let task_handle = tokio::spawn(async move {
+            // Event waiting for the sink to become ready; no lines are read meanwhile
+            let mut pending: Option<MonitorEvent> = None;
+            let mut unflushed = false;
+
+            let result = loop {
+                tokio::select! {
+                    line = lines.recv(), if pending.is_none() => {
+                        match line.transpose() {
+                            Ok(Some(line)) if !task_paused.load(Ordering::SeqCst) => {
+                                pending = Some(MonitorEvent::parse(&line));
+                            }
+                            Ok(Some(_)) => {}
+                            Ok(None) => break Ok(()),
+                            Err(e) => break Err(e),
+                        }
+                    }
+                    // Wait for capacity if an event is pending, otherwise flush
+                    ready = poll_fn(|cx| match pending {
+                        Some(_) => sink.as_mut().poll_ready(cx),
+                        None => sink.as_mut().poll_flush(cx),
+                    }), if pending.is_some() || unflushed => {
+                        let sent = ready.and_then(|()| match pending.take() {
+                            Some(event) => sink.as_mut().start_send(event).map(|()| unflushed = true),
+                            None => {
+                                unflushed = false;
+                                Ok(())
+                            }
+                        });
+                        if let Err(e) = sent {
+                            log::debug!("Event sink closed ({}), stopping monitor", e);
+                            return Ok(());
+                        }
+                    }
+                    msg = control_rx.recv() => {
+                        match msg {
+                            Some(ControlMessage::Stop) | None => {
+                                log::info!("Stop signal received, terminating monitor");
+                                break Ok(());
+                            }
+                            Some(ControlMessage::Pause) => set_paused(&task_paused, true),
+                            Some(ControlMessage::Resume) => set_paused(&task_paused, false),
+                        }
+                    }
+                }
+            };
+
+            if let Err(e) = sink.close().await {
+                log::debug!("Failed to close event sink: {}", e);
+            }
+            result
+        });
+
+        Ok(MonitorHandle {
+            control_tx,
+            task_handle,
+            paused,
+        })
+    }
+
+    async fn replay_impl<F>(
+        &self,
+        path: &std::path::Path,
+        speed: Option<f64>,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool>,
+    {
+        use tokio::io::AsyncBufReadExt;
+
+        let file = tokio::fs::File::open(path).await?;
+        let mut lines = tokio::io::BufReader::new(file).lines();
+        let mut previous: Option<f64> = None;
+
+        while let Some(line) = lines.next_line().await? {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let event = MonitorEvent::parse(line);
+
+            if let Some(speed) = speed {
+                if let Some(at) = replay_time(&event) {
+                    if let Some(gap) = previous.map(|prev| (at - prev) / speed).filter(|gap| *gap > 0.0) {
+                        tokio::time::sleep(Duration::from_secs_f64(gap)).await;
+                    }
+                    previous = Some(at);
+                }
+            }
+
+            if !callback(&event)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn start_with_reconnect_impl<F>(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        policy: ReconnectPolicy,
+        mut callback: F,
+    ) -> Result<MonitorHandle>
+    where
+        F: FnMut(&MonitorEvent) -> Result<bool> + Send + 'static,
+    {
+        // Clone necessary data to move into task
+        let config = self.controller.executor_config();
+        let port_owned = port.map(|s| s.to_string());
+
+        // Fail now rather than through the task handle
+        resolve_port(port, config.default_port.as_deref())?;
+
+        // Create channel for control signals
+        let (control_tx, mut control_rx) = mpsc::channel(10);
+        self.controller.register_monitor(&control_tx)?;
+        let paused = Arc::new(AtomicBool::new(false));
+        let task_paused = Arc::clone(&paused);
+
+        // Spawn monitor task
+        let task_handle = tokio::spawn(async move {
+            let executor = config.executor();
+
+            let mut attempt = 0;
+
+            loop {
+                let mut received = false;
+                let mut on_line = |line: &str| {
+                    received = true;
+                    callback(&MonitorEvent::parse(line))
+                };
+
+                let outcome = executor
+                    .execute_streaming_session(
+                        &["monitor"],
+                        port_owned.as_deref(),
+                        baud,
+                        &mut on_line,
+                        &mut control_rx,
+                        &task_paused,
+                    )
+                    .await?;
+
+                let code = match outcome {
+                    StreamOutcome::Exited(code) => code,
+                    _ => return Ok(()),
+                };
+
+                if received {
+                    attempt = 0;
+                }
+                if attempt >= policy.max_attempts {
+                    log::error!("Monitor exited (code {:?}), giving up after {} reconnect attempts", code, attempt);
+                    return Err(BjigError::CommandFailed(format!(
+                        "Monitor exited with code {:?} after {} reconnect attempts",
+                        code, attempt
+                    )));
+                }
+                attempt += 1;
+
+                log::warn!(
+                    "Monitor exited unexpectedly (code {:?}), reconnecting in {:?} (attempt {}/{})",
+                    code, policy.delay, attempt, policy.max_attempts
+                );
+
+                // Wait before reconnecting, still honoring control signals
+                let delay = tokio::time::sleep(policy.delay);
+                tokio::pin!(delay);
+                loop {
+                    tokio::select! {
+                        _ = &mut delay => break,
+                        msg = control_rx.recv() => {
+                            match msg {
+                                Some(ControlMessage::Pause) => set_paused(&task_paused, true),
+                                Some(ControlMessage::Resume) => set_paused(&task_paused, false),
+                                Some(ControlMessage::Stop) | None => {
+                                    log::info!("Monitor stopped during reconnect wait");
+                                    return Ok(());
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if !callback(&MonitorEvent::Reconnected { attempt })? {
+                    return Ok(());
+                }
+            }
+        });
+
+        Ok(MonitorHandle {
+            control_tx,
+            task_handle,
+            paused,
+        })
+    }
+}
+
+/// Recorded time of an event in (fractional) Unix seconds, for replay timing
+fn replay_time(event: &MonitorEvent) -> Option<f64> {
+    match event.as_uplink()?.extra.get("timestamp")? {
+        serde_json::Value::Number(n) => n.as_f64(),
+        #[cfg(feature = "chrono")]
+        serde_json::Value::String(_) => event.timestamp().map(|t| t.timestamp_millis() as f64 / 1000.0),
+        _ => None,
+    }
+}
+
+/// Complete on Ctrl+C; never completes if the handler cannot be installed
+async fn ctrl_c() {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        log::error!("Failed to install Ctrl+C handler: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{assert_process_gone, fake_bjig, temp_file};
+
+    const TEMP_LINE: &str = r#"{"sensor_id":"0122","module_id":"AAAA000000000001","temperature":21.5}"#;
+    const LUX_LINE: &str = r#"{"sensor_id":"0121","module_id":"BBBB000000000002","lux":300}"#;
+
+    #[test]
+    fn test_filter_matches() {
+        let temp = MonitorEvent::parse(TEMP_LINE);
+        let lux = MonitorEvent::parse(LUX_LINE);
+        let unknown = MonitorEvent::parse("not json");
+
+        let all = MonitorFilter::new();
+        assert!(all.matches(&temp) && all.matches(&lux) && all.matches(&unknown));
+
+        let by_sensor = MonitorFilter::new().with_sensor_id("0122");
+        assert!(by_sensor.matches(&temp));
+        assert!(!by_sensor.matches(&lux));
+        assert!(!by_sensor.matches(&unknown));
+
+        let by_module = MonitorFilter::new()
+            .with_module_id("AAAA000000000001")
+            .with_module_id("BBBB000000000002");
+        assert!(by_module.matches(&temp) && by_module.matches(&lux));
+
+        let by_predicate = MonitorFilter::new().with_predicate(|e| {
+            e.as_uplink().is_some_and(|u| u.extra.contains_key("lux"))
+        });
+        assert!(!by_predicate.matches(&temp));
+        assert!(by_predicate.matches(&lux));
+    }
+
+    #[tokio::test]
+    async fn test_start_with_filter_skips_non_matching() {
+        let bin = fake_bjig(&format!(
+            "echo '{}'\necho 'noise'\necho '{}'\necho '{}'",
+            TEMP_LINE, LUX_LINE, TEMP_LINE
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let mut received = Vec::new();
+        bjig.monitor()
+            .start_with_filter(MonitorFilter::new().with_sensor_id("0122"), |event| {
+                received.push(event.module_id().unwrap().to_string());
+                Ok(true)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(received, vec!["AAAA000000000001", "AAAA000000000001"]);
+    }
+
+    #[tokio::test]
+    async fn test_start_with_parser_key_value() {
+        #[derive(Debug, PartialEq)]
+        struct Reading {
+            module_id: String,
+            lux: f32,
+        }
+
+        let bin = fake_bjig("echo 'module=A lux=120.5'\necho 'module=B lux=80'\necho 'module=C lux=bad'");
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let parse = |line: &str| -> Result<Reading> {
+            let fields: HashMap<&str, &str> =
+                line.split_whitespace().filter_map(|kv| kv.split_once('=')).collect();
+            let field = |key: &str| {
+                fields
+                    .get(key)
+                    .copied()
+                    .ok_or_else(|| BjigError::InvalidParameter(format!("missing {}", key)))
+            };
+            Ok(Reading {
+                module_id: field("module")?.to_string(),
+                lux: field("lux")?
+                    .parse()
+                    .map_err(|_| BjigError::InvalidParameter(format!("bad lux: {}", line)))?,
+            })
+        };
+
+        let mut received = Vec::new();
+        let result = bjig
+            .monitor()
+            .start_with_parser(parse, |reading| {
+                received.push(reading);
+                Ok(true)
+            })
+            .await;
+
+        assert!(matches!(result, Err(BjigError::InvalidParameter(_))));
+        assert_eq!(
+            received,
+            vec![
+                Reading { module_id: "A".to_string(), lux: 120.5 },
+                Reading { module_id: "B".to_string(), lux: 80.0 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_silence_detection_reports_silent_module() {
+        let line = |module_id: &str| format!(r#"{{"sensor_id":"0121","module_id":"{}","lux":1}}"#, module_id);
+        let bin = fake_bjig(&format!(
+            "echo '{a}'\necho '{b}'\nfor i in 1 2 3 4 5 6 7 8; do sleep 0.1; echo '{a}'; done",
+            a = line("A"),
+            b = line("B"),
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let mut silent = Vec::new();
+        bjig.monitor()
+            .start_with_silence_detection(Duration::from_millis(300), |module_id| {
+                silent.push(module_id.to_string());
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(silent, vec!["B"]);
+
+        assert!(matches!(
+            bjig.monitor().start_with_silence_detection(Duration::ZERO, |_| {}).await,
+            Err(BjigError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_dedup_window() {
+        let mut dedup = MonitorDedup::new(Duration::from_secs(10));
+        let start = Instant::now();
+        let first = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","sequence_no":1,"lux":5}"#);
+        let next = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","sequence_no":2,"lux":5}"#);
+        let other_module = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"B","sequence_no":1}"#);
+        let no_key = MonitorEvent::parse(LUX_LINE);
+
+        assert!(!dedup.is_duplicate_at(&first, start));
+        assert!(dedup.is_duplicate_at(&first, start + Duration::from_secs(9)));
+        assert!(!dedup.is_duplicate_at(&next, start + Duration::from_secs(1)));
+        assert!(!dedup.is_duplicate_at(&other_module, start + Duration::from_secs(1)));
+        assert!(!dedup.is_duplicate_at(&no_key, start));
+        assert!(!dedup.is_duplicate_at(&no_key, start));
+        // Window expired
+        assert!(!dedup.is_duplicate_at(&first, start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_dedup_custom_key() {
+        let mut dedup = MonitorDedup::new(Duration::from_secs(10)).with_key(|event| {
+            let uplink = event.as_uplink()?;
+            Some(format!("{}/{}", uplink.module_id, uplink.extra.get("lux")?))
+        });
+        let start = Instant::now();
+
+        assert!(!dedup.is_duplicate_at(&MonitorEvent::parse(LUX_LINE), start));
+        assert!(dedup.is_duplicate_at(&MonitorEvent::parse(LUX_LINE), start));
+        assert!(!dedup.is_duplicate_at(&MonitorEvent::parse(TEMP_LINE), start));
+    }
+
+    #[tokio::test]
+    async fn test_start_dedup_suppresses_repeats() {
+        let line = |seq: u32| {
+            format!(r#"{{"sensor_id":"0121","module_id":"BBBB000000000002","sequence_no":{},"lux":300}}"#, seq)
+        };
+        let bin = fake_bjig(&format!(
+            "echo '{}'\necho '{}'\necho '{}'\necho '{}'",
+            line(1), line(1), line(2), line(1)
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let mut received = Vec::new();
+        bjig.monitor()
+            .start_dedup(Duration::from_secs(60), |event| {
+                received.push(event.as_uplink().unwrap().extra["sequence_no"].as_u64().unwrap());
+                Ok(true)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(received, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_start_rssi_callback_skips_without_rssi() {
+        let bin = fake_bjig(&format!(
+            "echo '{}'\necho '{}'\necho '{}'",
+            TEMP_LINE,
+            r#"{"sensor_id":"0121","module_id":"BBBB000000000002","rssi":-80}"#,
+            r#"{"sensor_id":"0122","module_id":"AAAA000000000001","rssi":"-61"}"#
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let mut received = Vec::new();
+        bjig.monitor()
+            .start_rssi_callback(|module_id, rssi| {
+                received.push((module_id.to_string(), rssi));
+                Ok(true)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            received,
+            vec![
+                ("BBBB000000000002".to_string(), -80),
+                ("AAAA000000000001".to_string(), -61)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_pretty() {
+        let mut buf = Vec::new();
+        let mut out = Vec::new();
+
+        write_pretty(r#"{"module_id":"AAAA000000000001","data":{"lux":300}}"#, &mut buf, &mut out).unwrap();
+        write_pretty("router ready", &mut buf, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\n  \"data\": {\n    \"lux\": 300\n  },\n  \"module_id\": \"AAAA000000000001\"\n}\nrouter ready\n"
+        );
+        assert!(buf.is_empty() && buf.capacity() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_rssi_histogram_collects_until_window_ends() {
+        let bin = fake_bjig(&format!(
+            "echo $$ > \"$0.pid\"\necho '{}'\necho '{}'\necho '{}'\necho '{}'\nsleep 5",
+            TEMP_LINE,
+            r#"{"sensor_id":"0121","module_id":"BBBB000000000002","rssi":-80}"#,
+            r#"{"sensor_id":"0122","module_id":"AAAA000000000001","rssi":"-61"}"#,
+            r#"{"sensor_id":"0121","module_id":"BBBB000000000002","rssi":-78}"#
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let samples = bjig
+            .monitor()
+            .rssi_histogram(Duration::from_millis(300))
+            .await
+            .unwrap();
+
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples["BBBB000000000002"], vec![-80, -78]);
+        assert_eq!(samples["AAAA000000000001"], vec![-61]);
+        assert_process_gone(&bin).await;
+    }
+
+    #[tokio::test]
+    async fn test_callback_error_kills_monitor() {
+        let bin = fake_bjig("echo $$ > \"$0.pid\"\necho 'first line'\nsleep 30");
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let handle = bjig
+            .monitor()
+            .start_with_callback_and_handle(|_| {
+                Err(BjigError::CommandFailed("callback failed".to_string()))
+            })
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handle.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let result = handle.stop().await;
+        assert!(matches!(result, Err(BjigError::CommandFailed(ref m)) if m == "callback failed"));
+        assert_process_gone(&bin).await;
+    }
+
+    #[tokio::test]
+    async fn test_estimate_intervals_from_evenly_spaced_uplinks() {
+        let bin = fake_bjig(&format!(
+            "echo $$ > \"$0.pid\"\nfor i in 1 2 3 4 5; do echo '{}'; echo '{}'; sleep 0.2; done\nsleep 5",
+            TEMP_LINE, LUX_LINE
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let intervals = bjig
+            .monitor()
+            .estimate_intervals(Duration::from_millis(1500))
+            .await
+            .unwrap();
+
+        assert_eq!(intervals.len(), 2);
+        for interval in intervals.values() {
+            assert!(
+                (Duration::from_millis(150)..Duration::from_millis(400)).contains(interval),
+                "{:?}",
+                interval
+            );
+        }
+        assert_process_gone(&bin).await;
+    }
+
+    #[tokio::test]
+    async fn test_start_until_kills_child_on_shutdown() {
+        let bin = fake_bjig(&format!("echo $$ > \"$0.pid\"\necho '{}'\nsleep 30", TEMP_LINE));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let (signal_tx, signal_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let monitor = bjig.monitor();
+        let started = Instant::now();
+        let (result, ()) = tokio::join!(
+            monitor.start_until(async {
+                let _ = signal_rx.await;
+            }),
+            async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                signal_tx.send(()).unwrap();
+            }
+        );
+
+        result.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_process_gone(&bin).await;
+    }
+
+    #[tokio::test]
+    async fn test_next_uplink_for_returns_match_and_kills_monitor() {
+        let bin = fake_bjig(&format!(
+            "echo $$ > \"$0.pid\"\necho '{}'\necho '{}'\nsleep 5\necho '{}'",
+            TEMP_LINE, LUX_LINE, LUX_LINE
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let event = bjig
+            .monitor()
+            .next_uplink_for("BBBB000000000002", Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(event.sensor_id(), Some("0121"));
+        assert_process_gone(&bin).await;
+
+        let result = bjig
+            .monitor()
+            .next_uplink_for("CCCC000000000003", Duration::from_millis(200))
+            .await;
+        assert!(matches!(result, Err(BjigError::Timeout { secs: 1 })));
+        assert_process_gone(&bin).await;
+    }
+
+    #[tokio::test]
+    async fn test_start_with_reconnect_respawns_and_gives_up() {
+        // First run emits one line and exits; later runs fail without output
+        let bin = fake_bjig(&format!(
+            "if [ -f \"$0.ran\" ]; then exit 1; fi\ntouch \"$0.ran\"\necho '{}'",
+            LUX_LINE
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let policy = ReconnectPolicy {
+            delay: Duration::from_millis(10),
+            max_attempts: 2,
+        };
+
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let handle = bjig
+            .monitor()
+            .start_with_reconnect(policy, move |event| {
+                events_clone.lock().unwrap().push(event.clone());
+                Ok(true)
+            })
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handle.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let result = handle.stop().await;
+        assert!(matches!(result, Err(BjigError::CommandFailed(_))));
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].sensor_id(), Some("0121"));
+        assert!(matches!(events[1], MonitorEvent::Reconnected { attempt: 1 }));
+        assert!(matches!(events[2], MonitorEvent::Reconnected { attempt: 2 }));
+    }
+
+    #[tokio::test]
+    async fn test_start_with_reconnect_stops_during_wait() {
+        let bin = fake_bjig("exit 1");
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let policy = ReconnectPolicy {
+            delay: Duration::from_secs(60),
+            max_attempts: 3,
+        };
+
+        let handle = bjig
+            .monitor()
+            .start_with_reconnect(policy, |_| Ok(true))
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let result = tokio::time::timeout(Duration::from_secs(5), handle.stop()).await;
+        assert!(matches!(result, Ok(Ok(()))));
+    }
+
+    #[tokio::test]
+    async fn test_handle_reports_pause_state() {
+        async fn wait_paused(handle: &MonitorHandle, expected: bool) {
+            for _ in 0..100 {
+                if handle.is_paused() == expected {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            panic!("is_paused never became {}", expected);
+        }
+
+        let bin = fake_bjig(&format!("while true; do echo '{}'; sleep 0.05; done", LUX_LINE));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let received = Arc::new(std::sync::Mutex::new(0usize));
+        let counter = Arc::clone(&received);
+
+        let handle = bjig
+            .monitor()
+            .start_with_callback_and_handle(move |_| {
+                *counter.lock().unwrap() += 1;
+                Ok(true)
+            })
+            .await
+            .unwrap();
+        assert!(!handle.is_paused());
+
+        handle.pause().await.unwrap();
+        wait_paused(&handle, true).await;
+        // Pausing again is a no-op
+        handle.pause().await.unwrap();
+        assert!(handle.is_paused());
+
+        let count = *received.lock().unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(*received.lock().unwrap(), count);
+
+        handle.resume().await.unwrap();
+        wait_paused(&handle, false).await;
+        handle.resume().await.unwrap();
+        assert!(!handle.is_paused());
+
+        handle.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resume_right_after_pause() {
+        let bin = fake_bjig(&format!("while true; do echo '{}'; sleep 0.05; done", LUX_LINE));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let received = Arc::new(std::sync::Mutex::new(0usize));
+        let counter = Arc::clone(&received);
+
+        let handle = bjig
+            .monitor()
+            .start_with_callback_and_handle(move |_| {
+                *counter.lock().unwrap() += 1;
+                Ok(true)
+            })
+            .await
+            .unwrap();
+
+        // Neither call may be skipped based on a pause state that lags behind
+        handle.pause().await.unwrap();
+        handle.resume().await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!handle.is_paused());
+        let count = *received.lock().unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(*received.lock().unwrap() > count, "monitor stayed paused");
+
+        handle.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stderr_drained_while_streaming() {
+        // More stderr than a pipe buffer holds, between two stdout lines
+        let bin = fake_bjig(&format!(
+            r#"echo 'warning: low battery' >&2
+echo '{}'
+i=0
+while [ $i -lt 2000 ]; do echo "diagnostic line $i padded to fill the stderr pipe" >&2; i=$((i+1)); done
+echo '{}'"#,
+            TEMP_LINE, LUX_LINE
+        ));
+        let stderr_lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&stderr_lines);
+        let bjig = BjigController::new(&bin)
+            .unwrap()
+            .with_port("/dev/null")
+            .on_stderr(Arc::new(move |line: &str| sink.lock().unwrap().push(line.to_string())));
+
+        let mut lines = Vec::new();
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            bjig.monitor().start_with_callback(|line| {
+                lines.push(line.to_string());
+                Ok(true)
+            }),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(lines, vec![TEMP_LINE, LUX_LINE]);
+
+        let (tx, mut rx) = mpsc::channel(10);
+        let handle = bjig.monitor().start_to_channel(tx).await.unwrap();
+        let mut events = 0;
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while rx.recv().await.is_some() {
+                events += 1;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(events, 2);
+        handle.stop().await.unwrap();
+
+        // Drain tasks finish once stderr closes
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while stderr_lines.lock().unwrap().len() < 2 * 2001 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let stderr_lines = stderr_lines.lock().unwrap();
+        assert_eq!(stderr_lines[0], "warning: low battery");
+        assert_eq!(stderr_lines[1], "diagnostic line 0 padded to fill the stderr pipe");
+    }
+
+    #[tokio::test]
+    async fn test_start_for_stops_when_bjig_ignores_ttl() {
+        let bin = fake_bjig("echo $$ > \"$0.pid\"\necho \"$*\"\nsleep 5");
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let started = Instant::now();
+        let mut lines = Vec::new();
+        bjig.monitor()
+            .start_for(Duration::from_millis(300), |line| {
+                lines.push(line.to_string());
+                Ok(true)
+            })
+            .await
+            .unwrap();
+
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(300) && elapsed < Duration::from_secs(2), "{:?}", elapsed);
+        assert!(lines[0].ends_with("monitor --ttl 1"), "{}", lines[0]);
+        assert_process_gone(&bin).await;
+    }
+
+    #[tokio::test]
+    async fn test_start_with_handle_requires_port() {
+        let bjig = BjigController::new(fake_bjig("exit 0")).unwrap();
+
+        assert!(matches!(
+            bjig.monitor().start_with_handle().await,
+            Err(BjigError::PortNotConfigured)
+        ));
+        assert!(matches!(
+            bjig.monitor().start_with_callback_and_handle(|_| Ok(true)).await,
+            Err(BjigError::PortNotConfigured)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_start_to_channel_stops_when_receiver_dropped() {
+        let bin = fake_bjig(&format!(
+            "echo $$ > \"$0.pid\"\necho '{}'\necho '{}'\nsleep 5",
+            TEMP_LINE, LUX_LINE
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let handle = bjig.monitor().start_to_channel(tx).await.unwrap();
+        let first = rx.recv().await.unwrap();
+        let second = rx.recv().await.unwrap();
+        assert_eq!(first.module_id(), Some("AAAA000000000001"));
+        assert_eq!(second.sensor_id(), Some("0121"));
+
+        drop(rx);
+        assert_process_gone(&bin).await;
+        wait_stopped(&handle).await;
+        handle.stop().await.unwrap();
+    }
+
+    /// Sink adapter collecting events into a shared `Vec`, failing once `limit` is reached
+    struct VecSink {
+        events: Arc<std::sync::Mutex<Vec<MonitorEvent>>>,
+        limit: usize,
+        closed: Arc<AtomicBool>,
+    }
+
+    impl Sink<MonitorEvent> for VecSink {
+        type Error = String;
+
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::result::Result<(), String>> {
+            let full = self.events.lock().unwrap().len() >= self.limit;
+            std::task::Poll::Ready(if full { Err("closed".to_string()) } else { Ok(()) })
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, event: MonitorEvent) -> std::result::Result<(), String> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::result::Result<(), String>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::result::Result<(), String>> {
+            self.closed.store(true, Ordering::SeqCst);
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    fn vec_sink(limit: usize) -> (VecSink, Arc<std::sync::Mutex<Vec<MonitorEvent>>>, Arc<AtomicBool>) {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let sink = VecSink {
+            events: Arc::clone(&events),
+            limit,
+            closed: Arc::clone(&closed),
+        };
+        (sink, events, closed)
+    }
+
+    async fn wait_stopped(handle: &MonitorHandle) {
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while handle.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
         })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_start_to_sink_collects_events() {
+        let bin = fake_bjig(&format!("echo '{}'\necho '{}'", TEMP_LINE, LUX_LINE));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let (sink, events, closed) = vec_sink(usize::MAX);
+
+        let handle = bjig.monitor().start_to_sink(sink).await.unwrap();
+        wait_stopped(&handle).await;
+        handle.stop().await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].module_id(), Some("AAAA000000000001"));
+        assert_eq!(events[1].sensor_id(), Some("0121"));
+        assert!(closed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_start_to_sink_stops_when_sink_closed() {
+        let bin = fake_bjig(&format!(
+            "echo $$ > \"$0.pid\"\necho '{}'\necho '{}'\nsleep 5",
+            TEMP_LINE, LUX_LINE
+        ));
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let (sink, events, _closed) = vec_sink(1);
+
+        let handle = bjig.monitor().start_to_sink(sink).await.unwrap();
+        wait_stopped(&handle).await;
+        assert_process_gone(&bin).await;
+        handle.stop().await.unwrap();
+
+        assert_eq!(events.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_drives_callback() {
+        let capture = temp_file(
+            "capture.jsonl",
+            &format!(
+                "{}\n\n{}\n{}\n",
+                r#"{"sensor_id":"0122","module_id":"AAAA000000000001","temperature":21.5,"timestamp":1000}"#,
+                r#"{"notification":"module_joined","module_id":"CCCC000000000003"}"#,
+                r#"{"sensor_id":"0121","module_id":"BBBB000000000002","lux":300,"timestamp":1003}"#
+            ),
+        );
+        let bjig = BjigController::new(fake_bjig("exit 1")).unwrap();
+
+        let mut events = Vec::new();
+        bjig.monitor()
+            .replay(&capture, |event| {
+                events.push(event.clone());
+                Ok(true)
+            })
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].module_id(), Some("AAAA000000000001"));
+        assert!(events[1].notification_kind().is_some());
+        assert_eq!(events[2].module_id(), Some("BBBB000000000002"));
+
+        // 3 recorded seconds at 20x speed
+        let started = Instant::now();
+        let mut count = 0;
+        bjig.monitor()
+            .replay_timed(&capture, 20.0, |_| {
+                count += 1;
+                Ok(count < 3)
+            })
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+        assert!(started.elapsed() >= Duration::from_millis(140));
+
+        assert!(matches!(
+            bjig.monitor().replay_timed(&capture, 0.0, |_| Ok(true)).await,
+            Err(BjigError::InvalidParameter(_))
+        ));
     }
 }
@@ -1,11 +1,49 @@
 //! Router command implementations
 
 use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::Stream;
 
 use crate::controller::BjigController;
 use crate::executor::CommandExecutor;
 use crate::types::*;
 
+/// Highest module index accepted by the router
+const MAX_MODULE_INDEX: u8 = 99;
+
+/// Recovery policy for `RouterCommands::start_watchdog`
+///
+/// After `max_consecutive_timeouts` commands in a row have timed out, the
+/// watchdog stops the router, waits `restart_delay` and starts it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogPolicy {
+    /// Consecutive command timeouts that trigger a recovery
+    pub max_consecutive_timeouts: u32,
+    /// Delay between stopping and starting the router
+    pub restart_delay: Duration,
+    /// How often the timeout count is checked
+    pub check_interval: Duration,
+    /// Timeout for each of the recovery's stop and start commands, in seconds
+    pub command_timeout_secs: u64,
+}
+
+impl Default for WatchdogPolicy {
+    fn default() -> Self {
+        Self {
+            max_consecutive_timeouts: 3,
+            restart_delay: Duration::from_secs(2),
+            check_interval: Duration::from_secs(1),
+            command_timeout_secs: 10,
+        }
+    }
+}
+
 /// Router commands interface
 ///
 /// Provides access to all router-related operations including:
@@ -25,12 +63,8 @@ impl<'a> RouterCommands<'a> {
     }
 
     /// Get command executor
-    fn executor(&self) -> CommandExecutor {
-        CommandExecutor::new(
-            &self.controller.bjig_path,
-            self.controller.default_port.as_deref(),
-            self.controller.default_baud,
-        )
+    fn executor(&self) -> CommandExecutor<'_> {
+        self.controller.executor()
     }
 
     /// Start router
@@ -61,10 +95,54 @@ impl<'a> RouterCommands<'a> {
     pub async fn start_on(&self, port: Option<&str>, baud: Option<u32>) -> Result<StartResult> {
         let executor = self.executor();
         let json = executor
-            .execute_json(&["router", "start"], port, baud)
+            .execute_status(&["router", "start"], port, baud)
+            .await?;
+
+        status_from_json("router start", json, "Router started")
+    }
+
+    /// Start router, bounded by a timeout
+    ///
+    /// The bjig process is killed if the router does not respond in time.
+    ///
+    /// # Arguments
+    /// * `timeout_secs` - Maximum time to wait in seconds
+    ///
+    /// # Errors
+    /// Returns `BjigError::Timeout` if the timeout expires.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, BjigError};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// match bjig.router().start_with_timeout(10).await {
+    ///     Ok(result) => println!("Router started: {}", result.is_success()),
+    ///     Err(BjigError::Timeout { secs }) => println!("Router did not respond in {}s", secs),
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn start_with_timeout(&self, timeout_secs: u64) -> Result<StartResult> {
+        self.start_with_timeout_on(None, None, timeout_secs).await
+    }
+
+    /// Start router on specific port, bounded by a timeout
+    pub async fn start_with_timeout_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        timeout_secs: u64,
+    ) -> Result<StartResult> {
+        let executor = self.executor();
+        let json = executor
+            .execute_status_with_timeout(&["router", "start"], port, baud, timeout_secs)
             .await?;
 
-        Ok(serde_json::from_value(json)?)
+        status_from_json("router start", json, "Router started")
     }
 
     /// Stop router
@@ -78,10 +156,36 @@ impl<'a> RouterCommands<'a> {
     pub async fn stop_on(&self, port: Option<&str>, baud: Option<u32>) -> Result<StopResult> {
         let executor = self.executor();
         let json = executor
-            .execute_json(&["router", "stop"], port, baud)
+            .execute_status(&["router", "stop"], port, baud)
+            .await?;
+
+        status_from_json("router stop", json, "Router stopped")
+    }
+
+    /// Stop router, bounded by a timeout
+    ///
+    /// # Arguments
+    /// * `timeout_secs` - Maximum time to wait in seconds
+    ///
+    /// # Errors
+    /// Returns `BjigError::Timeout` if the timeout expires.
+    pub async fn stop_with_timeout(&self, timeout_secs: u64) -> Result<StopResult> {
+        self.stop_with_timeout_on(None, None, timeout_secs).await
+    }
+
+    /// Stop router on specific port, bounded by a timeout
+    pub async fn stop_with_timeout_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        timeout_secs: u64,
+    ) -> Result<StopResult> {
+        let executor = self.executor();
+        let json = executor
+            .execute_status_with_timeout(&["router", "stop"], port, baud, timeout_secs)
             .await?;
 
-        Ok(serde_json::from_value(json)?)
+        status_from_json("router stop", json, "Router stopped")
     }
 
     /// Get router firmware version
@@ -113,58 +217,79 @@ impl<'a> RouterCommands<'a> {
             .execute_json(&["router", "get-version"], port, baud)
             .await?;
 
-        Ok(serde_json::from_value(json)?)
+        decode("router get-version", json)
     }
 
-    /// Get module IDs
+    /// Wait until the router responds to `get_version`
     ///
-    /// # Arguments
-    /// * `index` - Optional module index (0-99). If None, returns all module IDs.
+    /// The router may reject commands for a second or two after `start`.
+    /// This polls `get_version` every `poll_interval` until it succeeds and
+    /// returns the version. An in-flight request is cancelled when
+    /// `max_wait` elapses.
+    ///
+    /// # Errors
+    /// Returns `BjigError::Timeout` (rounded up to whole seconds) if the
+    /// router does not respond within `max_wait`. Errors that polling cannot
+    /// fix, such as a missing bjig binary or an unconfigured port, are
+    /// returned immediately.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # async fn example() -> anyhow::Result<()> {
     /// use bjig_controller::BjigController;
+    /// use std::time::Duration;
     ///
     /// let bjig = BjigController::from_env()?;
-    ///
-    /// // Get all module IDs
-    /// let all_modules = bjig.router().get_module_id(None).await?;
-    /// println!("Found {} modules", all_modules.modules.len());
-    ///
-    /// // Get specific module at index 0
-    /// let module = bjig.router().get_module_id(Some(0)).await?;
+    /// bjig.router().start().await?;
+    /// let version = bjig
+    ///     .router()
+    ///     .wait_until_ready(Duration::from_millis(500), Duration::from_secs(10))
+    ///     .await?;
+    /// println!("Router ready: {}", version.version);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_module_id(&self, index: Option<u8>) -> Result<ModuleIdList> {
-        self.get_module_id_on(None, None, index).await
+    pub async fn wait_until_ready(&self, poll_interval: Duration, max_wait: Duration) -> Result<Version> {
+        self.wait_until_ready_on(None, None, poll_interval, max_wait).await
     }
 
-    /// Get module IDs on specific port
-    pub async fn get_module_id_on(
+    /// Wait until the router on specific port responds to `get_version`
+    pub async fn wait_until_ready_on(
         &self,
         port: Option<&str>,
         baud: Option<u32>,
-        index: Option<u8>,
-    ) -> Result<ModuleIdList> {
-        let executor = self.executor();
-
-        let idx_str;
-        let args = if let Some(idx) = index {
-            idx_str = idx.to_string();
-            vec!["router", "get-module-id", idx_str.as_str()]
-        } else {
-            vec!["router", "get-module-id"]
+        poll_interval: Duration,
+        max_wait: Duration,
+    ) -> Result<Version> {
+        let poll = async {
+            loop {
+                match self.get_version_on(port, baud).await {
+                    Ok(version) => return Ok(version),
+                    Err(e) if is_permanent(&e) => return Err(e),
+                    Err(e) => {
+                        log::debug!("Router not ready: {}", e);
+                        tokio::time::sleep(poll_interval).await;
+                    }
+                }
+            }
         };
 
-        let json = executor.execute_json(&args, port, baud).await?;
-
-        Ok(serde_json::from_value(json)?)
+        tokio::time::timeout(max_wait, poll).await.map_err(|_| {
+            log::error!("Router did not respond within {:?}", max_wait);
+            BjigError::timeout_after(max_wait)
+        })?
     }
 
-    /// Get scan mode
+    /// Collect version, scan mode, and module list in one call
+    ///
+    /// The three commands run one after another through the controller's
+    /// command queue. A failing command is reported in its field instead of
+    /// failing the whole snapshot.
+    ///
+    /// # Errors
+    /// Returns `BjigError::PortNotConfigured` without running any command if
+    /// no port is configured.
     ///
     /// # Examples
     ///
@@ -173,70 +298,212 @@ impl<'a> RouterCommands<'a> {
     /// use bjig_controller::BjigController;
     ///
     /// let bjig = BjigController::from_env()?;
-    /// let mode = bjig.router().get_scan_mode().await?;
-    /// println!("Scan mode: {} ({})", mode.mode, mode.mode_name);
+    /// let snapshot = bjig.router().snapshot().await?;
+    /// match &snapshot.version {
+    ///     Ok(version) => println!("Firmware: {}", version.version),
+    ///     Err(e) => println!("Firmware: unavailable ({})", e),
+    /// }
+    /// if let Ok(modules) = &snapshot.modules {
+    ///     println!("{} modules", modules.module_count);
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_scan_mode(&self) -> Result<ScanMode> {
-        self.get_scan_mode_on(None, None).await
+    pub async fn snapshot(&self) -> Result<RouterSnapshot> {
+        self.snapshot_on(None, None).await
     }
 
-    /// Get scan mode on specific port
-    pub async fn get_scan_mode_on(
-        &self,
-        port: Option<&str>,
-        baud: Option<u32>,
-    ) -> Result<ScanMode> {
-        let executor = self.executor();
-        let json = executor
-            .execute_json(&["router", "get-scan-mode"], port, baud)
-            .await?;
+    /// Collect version, scan mode, and module list on specific port in one call
+    ///
+    /// The command queue is held for all three reads, so no other command
+    /// of this controller runs between them. The controller's minimum
+    /// command interval (see `BjigController::with_min_interval`) is kept
+    /// before and between the reads.
+    pub async fn snapshot_on(&self, port: Option<&str>, baud: Option<u32>) -> Result<RouterSnapshot> {
+        crate::env::resolve_port(port, self.controller.config.default_port.as_deref())?;
 
-        Ok(serde_json::from_value(json)?)
+        let min_interval = self.controller.config.min_interval;
+        let _slot = self.controller.config.queue.hold(min_interval).await?;
+        // Commands must bypass the queue while it is held, so pace them here
+        let executor = self.executor().with_queue(None);
+        let version = executor.execute_json(&["router", "get-version"], port, baud).await;
+        tokio::time::sleep(min_interval).await;
+        let scan_mode = executor.execute_json(&["router", "get-scan-mode"], port, baud).await;
+        tokio::time::sleep(min_interval).await;
+        let modules = executor.execute_json(&["router", "get-module-id"], port, baud).await;
+
+        Ok(RouterSnapshot {
+            version: version.and_then(|json| decode("router get-version", json)),
+            scan_mode: scan_mode.and_then(|json| decode("router get-scan-mode", json)),
+            modules: modules.and_then(|json| decode("router get-module-id", json)),
+        })
     }
 
-    /// Set scan mode
+    /// Compare the router firmware against a required minimum version
     ///
-    /// # Arguments
-    /// * `mode` - Scan mode (0 = LongRange, 1 = Legacy)
+    /// Only `major.minor.build` is compared; build metadata is ignored.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # async fn example() -> anyhow::Result<()> {
-    /// use bjig_controller::{BjigController, ScanModeType};
+    /// use bjig_controller::{BjigController, Version};
     ///
     /// let bjig = BjigController::from_env()?;
-    /// let result = bjig.router().set_scan_mode(ScanModeType::LongRange).await?;
-    /// println!("Scan mode set: {}", result.is_success());
+    /// let minimum: Version = "1.2.0".parse()?;
+    /// let status = bjig.router().check_firmware(minimum).await?;
+    /// if status.needs_update {
+    ///     println!("Router runs outdated firmware {}", status.current.version);
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn set_scan_mode(&self, mode: ScanModeType) -> Result<SetScanModeResult> {
-        self.set_scan_mode_on(None, None, mode).await
+    pub async fn check_firmware(&self, minimum: Version) -> Result<FirmwareStatus> {
+        self.check_firmware_on(None, None, minimum).await
     }
 
-    /// Set scan mode on specific port
-    pub async fn set_scan_mode_on(
+    /// Compare the router firmware on specific port against a required minimum version
+    pub async fn check_firmware_on(
         &self,
         port: Option<&str>,
         baud: Option<u32>,
-        mode: ScanModeType,
-    ) -> Result<SetScanModeResult> {
+        minimum: Version,
+    ) -> Result<FirmwareStatus> {
+        let current = self.get_version_on(port, baud).await?;
+        let meets_minimum = current >= minimum;
+
+        Ok(FirmwareStatus {
+            current,
+            meets_minimum,
+            needs_update: !meets_minimum,
+        })
+    }
+
+    /// Query whether the router is currently started
+    ///
+    /// bjig has no dedicated status subcommand, so this is determined
+    /// heuristically by issuing the lightweight `router get-version` command:
+    /// - the command succeeds with version data: the router is running
+    /// - the command fails (non-zero exit) or reports `"result": "error"`:
+    ///   the router is considered stopped
+    ///
+    /// `uptime_secs` is always `None` because bjig does not report it.
+    ///
+    /// # Errors
+    /// Configuration, spawn, and IO errors are returned as-is rather than
+    /// being classified as "stopped", and so are failures with a
+    /// `BjigError::port_fault` (missing or busy serial port).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// if !bjig.router().status().await?.running {
+    ///     bjig.router().start().await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn status(&self) -> Result<RouterStatus> {
+        self.status_on(None, None).await
+    }
+
+    /// Query router status on specific port
+    pub async fn status_on(&self, port: Option<&str>, baud: Option<u32>) -> Result<RouterStatus> {
         let executor = self.executor();
-        let mode_str = mode.to_u8().to_string();
-        let json = executor
-            .execute_json(&["router", "set-scan-mode", &mode_str], port, baud)
-            .await?;
+        let running = match executor
+            .execute_json(&["router", "get-version"], port, baud)
+            .await
+        {
+            Ok(json) => json.get("result").and_then(|r| r.as_str()) != Some("error"),
+            // A missing or busy port says nothing about the router
+            Err(e) if e.port_fault().is_some() => return Err(e),
+            Err(BjigError::CommandFailed(msg)) => {
+                log::debug!("Router status probe failed, treating as stopped: {}", msg);
+                false
+            }
+            Err(e) => return Err(e),
+        };
 
-        Ok(serde_json::from_value(json)?)
+        Ok(RouterStatus {
+            running,
+            uptime_secs: None,
+        })
     }
 
-    /// Remove module ID
+    /// Start the router unless it is already running
+    ///
+    /// Checks `status` first. If the router reports that it is already
+    /// running when started anyway (in the result message or stderr), this is
+    /// treated as already running rather than an error.
+    ///
+    /// # Returns
+    /// `true` if the router was started, `false` if it was already running
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// if bjig.router().ensure_started().await? {
+    ///     println!("Router started");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ensure_started(&self) -> Result<bool> {
+        self.ensure_started_on(None, None).await
+    }
+
+    /// Start the router on specific port unless it is already running
+    pub async fn ensure_started_on(&self, port: Option<&str>, baud: Option<u32>) -> Result<bool> {
+        if self.status_on(port, baud).await?.running {
+            return Ok(false);
+        }
+
+        match self.start_on(port, baud).await {
+            Ok(result) if result.is_success() => Ok(true),
+            Ok(result) if is_already(&result.message) => Ok(false),
+            Ok(result) => Err(BjigError::CommandFailed(result.message)),
+            Err(BjigError::CommandFailed(msg)) if is_already(&msg) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stop the router unless it is already stopped
+    ///
+    /// Counterpart of `ensure_started`.
+    ///
+    /// # Returns
+    /// `true` if the router was stopped, `false` if it was not running
+    pub async fn ensure_stopped(&self) -> Result<bool> {
+        self.ensure_stopped_on(None, None).await
+    }
+
+    /// Stop the router on specific port unless it is already stopped
+    pub async fn ensure_stopped_on(&self, port: Option<&str>, baud: Option<u32>) -> Result<bool> {
+        if !self.status_on(port, baud).await?.running {
+            return Ok(false);
+        }
+
+        match self.stop_on(port, baud).await {
+            Ok(result) if result.is_success() => Ok(true),
+            Ok(result) if is_already(&result.message) => Ok(false),
+            Ok(result) => Err(BjigError::CommandFailed(result.message)),
+            Err(BjigError::CommandFailed(msg)) if is_already(&msg) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get module IDs
     ///
     /// # Arguments
-    /// * `index` - Optional module index (0-99). If None, removes all module IDs.
+    /// * `index` - Optional module index (0-99). If None, returns all module IDs.
     ///
     /// # Examples
     ///
@@ -246,41 +513,51 @@ impl<'a> RouterCommands<'a> {
     ///
     /// let bjig = BjigController::from_env()?;
     ///
-    /// // Remove module at index 0
-    /// let result = bjig.router().remove_module_id(Some(0)).await?;
+    /// // Get all module IDs
+    /// let all_modules = bjig.router().get_module_id(None).await?;
+    /// println!("Found {} modules", all_modules.modules.len());
     ///
-    /// // Remove all modules
-    /// let result = bjig.router().remove_module_id(None).await?;
+    /// // Get specific module at index 0
+    /// let module = bjig.router().get_module_id(Some(0)).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn remove_module_id(&self, index: Option<u8>) -> Result<RemoveResult> {
-        self.remove_module_id_on(None, None, index).await
+    pub async fn get_module_id(&self, index: Option<u8>) -> Result<ModuleIdList> {
+        self.get_module_id_on(None, None, index).await
     }
 
-    /// Remove module ID on specific port
-    pub async fn remove_module_id_on(
+    /// Get module IDs on specific port
+    pub async fn get_module_id_on(
         &self,
         port: Option<&str>,
         baud: Option<u32>,
         index: Option<u8>,
-    ) -> Result<RemoveResult> {
+    ) -> Result<ModuleIdList> {
         let executor = self.executor();
 
         let idx_str;
         let args = if let Some(idx) = index {
             idx_str = idx.to_string();
-            vec!["router", "remove-module-id", idx_str.as_str()]
+            vec!["router", "get-module-id", idx_str.as_str()]
         } else {
-            vec!["router", "remove-module-id"]
+            vec!["router", "get-module-id"]
         };
 
         let json = executor.execute_json(&args, port, baud).await?;
 
-        Ok(serde_json::from_value(json)?)
+        decode("router get-module-id", json)
     }
 
-    /// Send keep-alive signal (time synchronization)
+    /// Get the module ID registered at a single index
+    ///
+    /// # Arguments
+    /// * `index` - Module index (0-99)
+    ///
+    /// # Returns
+    /// The module ID, or `None` if bjig lists no module at the index
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` if `index` is greater than 99
     ///
     /// # Examples
     ///
@@ -289,29 +566,40 @@ impl<'a> RouterCommands<'a> {
     /// use bjig_controller::BjigController;
     ///
     /// let bjig = BjigController::from_env()?;
-    /// let result = bjig.router().keep_alive().await?;
+    /// match bjig.router().get_module_id_single(0).await? {
+    ///     Some(module_id) => println!("Slot 0: {}", module_id),
+    ///     None => println!("Slot 0 is empty"),
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn keep_alive(&self) -> Result<KeepAliveResult> {
-        self.keep_alive_on(None, None).await
+    pub async fn get_module_id_single(&self, index: u8) -> Result<Option<String>> {
+        self.get_module_id_single_on(None, None, index).await
     }
 
-    /// Send keep-alive signal on specific port
-    pub async fn keep_alive_on(
+    /// Get the module ID registered at a single index on specific port
+    pub async fn get_module_id_single_on(
         &self,
         port: Option<&str>,
         baud: Option<u32>,
-    ) -> Result<KeepAliveResult> {
-        let executor = self.executor();
-        let json = executor
-            .execute_json(&["router", "keep-alive"], port, baud)
-            .await?;
+        index: u8,
+    ) -> Result<Option<String>> {
+        if index > MAX_MODULE_INDEX {
+            return Err(BjigError::InvalidParameter(format!(
+                "Module index {} out of range (0-{})",
+                index, MAX_MODULE_INDEX
+            )));
+        }
 
-        Ok(serde_json::from_value(json)?)
+        let list = self.get_module_id_on(port, baud, Some(index)).await?;
+        Ok(list.modules.into_iter().next())
     }
 
-    /// Get supported sensor IDs and capabilities (static, no serial connection required)
+    /// Find module IDs registered more than once
+    ///
+    /// Fetches all module IDs and returns each ID that appears in more than
+    /// one slot, once, in order of first appearance. IDs are compared
+    /// case-insensitively. Returns an empty vector if there are no duplicates.
     ///
     /// # Examples
     ///
@@ -320,27 +608,43 @@ impl<'a> RouterCommands<'a> {
     /// use bjig_controller::BjigController;
     ///
     /// let bjig = BjigController::from_env()?;
-    /// let sensors = bjig.router().get_supported_sensor_id()?;
-    /// for sensor in sensors {
-    ///     println!("{}: {}", sensor.sensor_id, sensor.sensor_name);
+    /// for module_id in bjig.router().detect_duplicate_modules().await? {
+    ///     eprintln!("Module {} is registered more than once", module_id);
     /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_supported_sensor_id(&self) -> Result<Vec<SensorInfo>> {
-        let executor = self.executor();
-        let json = tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current()
-                .block_on(executor.execute_static(&["router", "get-supported-sensor-id"]))
-        })?;
+    pub async fn detect_duplicate_modules(&self) -> Result<Vec<String>> {
+        self.detect_duplicate_modules_on(None, None).await
+    }
+
+    /// Find module IDs registered more than once on specific port
+    pub async fn detect_duplicate_modules_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+    ) -> Result<Vec<String>> {
+        let list = self.get_module_id_on(port, baud, None).await?;
+
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for module_id in list.modules {
+            match counts
+                .iter_mut()
+                .find(|(id, _)| id.eq_ignore_ascii_case(&module_id))
+            {
+                Some((_, count)) => *count += 1,
+                None => counts.push((module_id, 1)),
+            }
+        }
 
-        Ok(serde_json::from_value(json)?)
+        Ok(counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(id, _)| id)
+            .collect())
     }
 
-    /// Get module configuration from YAML file (static, no serial connection required)
-    ///
-    /// # Arguments
-    /// * `file_path` - Path to module config YAML file
+    /// Get scan mode
     ///
     /// # Examples
     ///
@@ -349,36 +653,1010 @@ impl<'a> RouterCommands<'a> {
     /// use bjig_controller::BjigController;
     ///
     /// let bjig = BjigController::from_env()?;
-    /// let config = bjig.router().get_module_config("module-config.yml")?;
+    /// let mode = bjig.router().get_scan_mode().await?;
+    /// println!("Scan mode: {} ({})", mode.mode, mode.mode_name);
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_module_config<P: AsRef<Path>>(&self, file_path: P) -> Result<Vec<ModuleConfig>> {
-        let executor = self.executor();
-        let path_str = file_path.as_ref().to_string_lossy();
+    pub async fn get_scan_mode(&self) -> Result<ScanMode> {
+        self.get_scan_mode_on(None, None).await
+    }
 
-        let json = tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(
-                executor.execute_static(&["router", "get-module-config", "--file", &path_str]),
-            )
-        })?;
+    /// Get scan mode on specific port
+    pub async fn get_scan_mode_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+    ) -> Result<ScanMode> {
+        let executor = self.executor();
+        let json = executor
+            .execute_json(&["router", "get-scan-mode"], port, baud)
+            .await?;
 
-        Ok(serde_json::from_value(json)?)
+        decode("router get-scan-mode", json)
     }
 
-    /// Router DFU (firmware update)
+    /// Get scan mode as `ScanModeType`
     ///
-    /// # Arguments
-    /// * `firmware_path` - Path to firmware file
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` if the router reports a mode
+    /// value without a `ScanModeType`.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # async fn example() -> anyhow::Result<()> {
-    /// use bjig_controller::BjigController;
+    /// use bjig_controller::{BjigController, ScanModeType};
     ///
     /// let bjig = BjigController::from_env()?;
-    /// let result = bjig.router().dfu("router_firmware.bin").await?;
+    /// if bjig.router().get_scan_mode_typed().await? == ScanModeType::Legacy {
+    ///     println!("Router is in legacy scan mode");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_scan_mode_typed(&self) -> Result<ScanModeType> {
+        self.get_scan_mode_typed_on(None, None).await
+    }
+
+    /// Get scan mode as `ScanModeType` on specific port
+    pub async fn get_scan_mode_typed_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+    ) -> Result<ScanModeType> {
+        let mode = self.get_scan_mode_on(port, baud).await?;
+        mode.as_type().ok_or_else(|| {
+            BjigError::InvalidParameter(format!(
+                "Unrecognized scan mode: {} ({})",
+                mode.mode, mode.mode_name
+            ))
+        })
+    }
+
+    /// Set scan mode
+    ///
+    /// # Arguments
+    /// * `mode` - Scan mode (0 = LongRange, 1 = Legacy)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, ScanModeType};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let result = bjig.router().set_scan_mode(ScanModeType::LongRange).await?;
+    /// println!("Scan mode set: {}", result.is_success());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_scan_mode(&self, mode: ScanModeType) -> Result<SetScanModeResult> {
+        self.set_scan_mode_on(None, None, mode).await
+    }
+
+    /// Set scan mode on specific port
+    pub async fn set_scan_mode_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        mode: ScanModeType,
+    ) -> Result<SetScanModeResult> {
+        let executor = self.executor();
+        let mode_str = mode.to_u8().to_string();
+        let json = executor
+            .execute_json(&["router", "set-scan-mode", &mode_str], port, baud)
+            .await?;
+
+        decode("router set-scan-mode", json)
+    }
+
+    /// Set scan mode from its name or numeric value
+    ///
+    /// Accepts the forms parsed by `ScanModeType::from_str`, e.g.
+    /// `"long_range"`, `"Legacy"` or `"0"`.
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` for an unknown mode, without
+    /// running bjig.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.router().set_scan_mode_str("long_range").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_scan_mode_str(&self, mode: &str) -> Result<SetScanModeResult> {
+        self.set_scan_mode(mode.parse()?).await
+    }
+
+    /// Set scan mode and confirm it by reading it back
+    ///
+    /// Some firmware acknowledges `set-scan-mode` without applying it. This
+    /// method sets the mode, then calls `get_scan_mode` and checks that the
+    /// router reports the requested mode. On success the returned result has
+    /// `mode` set to the confirmed value.
+    ///
+    /// # Errors
+    /// Returns `BjigError::VerificationFailed` if the readback does not match.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, ScanModeType};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let result = bjig.router().set_scan_mode_verified(ScanModeType::Legacy).await?;
+    /// println!("Scan mode confirmed: {:?}", result.mode);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_scan_mode_verified(&self, mode: ScanModeType) -> Result<SetScanModeResult> {
+        self.set_scan_mode_verified_on(None, None, mode).await
+    }
+
+    /// Set scan mode and confirm it by reading it back on specific port
+    pub async fn set_scan_mode_verified_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        mode: ScanModeType,
+    ) -> Result<SetScanModeResult> {
+        let mut result = self.set_scan_mode_on(port, baud, mode).await?;
+        let readback = self.get_scan_mode_on(port, baud).await?;
+
+        if readback.mode != mode.to_u8() {
+            return Err(BjigError::VerificationFailed(format!(
+                "Scan mode not applied: requested {} ({}), router reports {} ({})",
+                mode.to_u8(),
+                mode.name(),
+                readback.mode,
+                readback.mode_name
+            )));
+        }
+
+        result.mode = Some(readback.mode);
+        Ok(result)
+    }
+
+    /// Provision the router: set scan mode, clear modules, wait, then verify
+    ///
+    /// Steps not requested by `spec` are skipped. Each executed step is
+    /// recorded in the returned report.
+    ///
+    /// # Errors
+    /// Stops at the first failed step (command error, unsuccessful result,
+    /// or failed verification) and returns `BjigError::ProvisionFailed`
+    /// carrying the partial report.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use std::time::Duration;
+    /// use bjig_controller::{BjigController, ProvisionSpec, ScanModeType};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let spec = ProvisionSpec::new()
+    ///     .with_scan_mode(ScanModeType::LongRange)
+    ///     .with_clear_modules(true)
+    ///     .with_settle_delay(Duration::from_secs(2));
+    ///
+    /// let report = bjig.router().provision(spec).await?;
+    /// for step in &report.steps {
+    ///     println!("{:?}: {}", step.kind, step.message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn provision(&self, spec: ProvisionSpec) -> Result<ProvisionReport> {
+        self.provision_on(None, None, spec).await
+    }
+
+    /// Provision the router on specific port
+    pub async fn provision_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        spec: ProvisionSpec,
+    ) -> Result<ProvisionReport> {
+        let mut report = ProvisionReport::default();
+
+        if let Some(mode) = spec.scan_mode {
+            let outcome = self
+                .set_scan_mode_on(port, baud, mode)
+                .await
+                .and_then(|r| succeeded(r.is_success(), r.message));
+            record_step(&mut report, ProvisionStepKind::SetScanMode, outcome)?;
+        }
+
+        if spec.clear_modules {
+            let outcome = self
+                .remove_module_id_on(port, baud, None)
+                .await
+                .and_then(|r| succeeded(r.is_success(), r.message));
+            record_step(&mut report, ProvisionStepKind::ClearModules, outcome)?;
+        }
+
+        if !spec.settle_delay.is_zero() {
+            tokio::time::sleep(spec.settle_delay).await;
+            let message = format!("Waited {:?}", spec.settle_delay);
+            record_step(&mut report, ProvisionStepKind::Settle, Ok(message))?;
+        }
+
+        if let Some(mode) = spec.scan_mode {
+            let outcome = self.get_scan_mode_on(port, baud).await.and_then(|readback| {
+                if readback.mode == mode.to_u8() {
+                    Ok(format!("Scan mode is {}", readback.mode_name))
+                } else {
+                    Err(BjigError::VerificationFailed(format!(
+                        "Scan mode not applied: requested {}, router reports {}",
+                        mode.name(),
+                        readback.mode_name
+                    )))
+                }
+            });
+            record_step(&mut report, ProvisionStepKind::VerifyScanMode, outcome)?;
+        }
+
+        if spec.clear_modules {
+            let outcome = self.get_module_id_on(port, baud, None).await.and_then(|list| {
+                if list.modules.is_empty() {
+                    Ok("No modules registered".to_string())
+                } else {
+                    Err(BjigError::VerificationFailed(format!(
+                        "Modules still registered: {}",
+                        list.modules.join(", ")
+                    )))
+                }
+            });
+            record_step(&mut report, ProvisionStepKind::VerifyModulesCleared, outcome)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Remove module ID
+    ///
+    /// # Arguments
+    /// * `index` - Optional module index (0-99). If None, removes all module IDs.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    ///
+    /// // Remove module at index 0
+    /// let result = bjig.router().remove_module_id(Some(0)).await?;
+    ///
+    /// // Remove all modules
+    /// let result = bjig.router().remove_module_id(None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove_module_id(&self, index: Option<u8>) -> Result<RemoveResult> {
+        self.remove_module_id_on(None, None, index).await
+    }
+
+    /// Remove module ID on specific port
+    pub async fn remove_module_id_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        index: Option<u8>,
+    ) -> Result<RemoveResult> {
+        let executor = self.executor();
+
+        let idx_str;
+        let args = if let Some(idx) = index {
+            idx_str = idx.to_string();
+            vec!["router", "remove-module-id", idx_str.as_str()]
+        } else {
+            vec!["router", "remove-module-id"]
+        };
+
+        let json = executor.execute_json(&args, port, baud).await?;
+
+        decode("router remove-module-id", json)
+    }
+
+    /// Remove all module IDs and confirm the router is empty
+    ///
+    /// Calls `remove_module_id(None)`, then reads the list back with
+    /// `get_module_id(None)`. Useful in factory-reset workflows, where a
+    /// removal acknowledged but not applied would otherwise go unnoticed.
+    /// Returns the number of modules still registered, which is always 0 on
+    /// success.
+    ///
+    /// # Errors
+    /// Returns `BjigError::CommandFailed` if the router reports an
+    /// unsuccessful removal, and `BjigError::VerificationFailed` listing the
+    /// module IDs if the readback still lists modules.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.router().clear_all_modules_verified().await?;
+    /// println!("All modules removed");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clear_all_modules_verified(&self) -> Result<usize> {
+        self.clear_all_modules_verified_on(None, None).await
+    }
+
+    /// Remove all module IDs and confirm the router is empty on specific port
+    pub async fn clear_all_modules_verified_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+    ) -> Result<usize> {
+        let result = self.remove_module_id_on(port, baud, None).await?;
+        if !result.is_success() {
+            return Err(BjigError::CommandFailed(result.message));
+        }
+
+        let remaining = self.get_module_id_on(port, baud, None).await?.modules;
+        if !remaining.is_empty() {
+            return Err(BjigError::VerificationFailed(format!(
+                "Modules not cleared: {} still registered",
+                remaining.join(", ")
+            )));
+        }
+        Ok(remaining.len())
+    }
+
+    /// Remove a registered module by its ID instead of its index
+    ///
+    /// Looks up the module's index with `get_module_id(None)` and removes
+    /// that slot. IDs are compared case-insensitively.
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` if the module ID is not registered.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let result = bjig.router().remove_module_by_id("2468800203400004").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove_module_by_id(&self, module_id: &str) -> Result<RemoveResult> {
+        self.remove_module_by_id_on(None, None, module_id).await
+    }
+
+    /// Remove a registered module by its ID on specific port
+    pub async fn remove_module_by_id_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        module_id: &str,
+    ) -> Result<RemoveResult> {
+        let list = self.get_module_id_on(port, baud, None).await?;
+
+        let index = list
+            .modules
+            .iter()
+            .position(|id| id.eq_ignore_ascii_case(module_id))
+            .and_then(|i| u8::try_from(i).ok())
+            .ok_or_else(|| {
+                BjigError::InvalidParameter(format!("Module ID not registered: {}", module_id))
+            })?;
+
+        log::debug!(
+            "Removing module {} at index {}",
+            self.controller.config.log_id(module_id),
+            index
+        );
+        self.remove_module_id_on(port, baud, Some(index)).await
+    }
+
+    /// Send keep-alive signal (time synchronization)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let result = bjig.router().keep_alive().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn keep_alive(&self) -> Result<KeepAliveResult> {
+        self.keep_alive_on(None, None).await
+    }
+
+    /// Send keep-alive signal on specific port
+    pub async fn keep_alive_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+    ) -> Result<KeepAliveResult> {
+        let executor = self.executor();
+        let json = executor
+            .execute_status(&["router", "keep-alive"], port, baud)
+            .await?;
+
+        status_from_json("router keep-alive", json, "Keep-alive sent")
+    }
+
+    /// Restart the router automatically after repeated command timeouts
+    ///
+    /// Spawns a task that checks the controller's count of consecutive
+    /// command timeouts (see `BjigController::consecutive_timeouts`). Once it
+    /// reaches `policy.max_consecutive_timeouts`, the router is stopped and,
+    /// after `policy.restart_delay`, started again. Both commands are bounded
+    /// by `policy.command_timeout_secs`. Only a successful start resets the
+    /// count and is counted as a recovery; failures are logged. The loop
+    /// ends once the controller is shut down, and is aborted when the
+    /// returned `WatchdogTask` is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, WatchdogPolicy};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let watchdog = bjig.router().start_watchdog(WatchdogPolicy::default())?;
+    ///
+    /// // Do some work...
+    ///
+    /// println!("Recovered {} times", watchdog.recoveries());
+    /// watchdog.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_watchdog(&self, policy: WatchdogPolicy) -> Result<WatchdogTask> {
+        self.start_watchdog_impl(None, None, policy)
+    }
+
+    /// Restart the router on specific port after repeated command timeouts
+    pub fn start_watchdog_on(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        policy: WatchdogPolicy,
+    ) -> Result<WatchdogTask> {
+        self.start_watchdog_impl(port.map(str::to_string), baud, policy)
+    }
+
+    fn start_watchdog_impl(
+        &self,
+        port: Option<String>,
+        baud: Option<u32>,
+        policy: WatchdogPolicy,
+    ) -> Result<WatchdogTask> {
+        self.controller.config.queue.ensure_open()?;
+        if policy.max_consecutive_timeouts == 0
+            || policy.check_interval.is_zero()
+            || policy.command_timeout_secs == 0
+        {
+            return Err(BjigError::InvalidParameter(
+                "Watchdog timeout threshold, check interval and command timeout must be greater than zero"
+                    .to_string(),
+            ));
+        }
+
+        let config = self.controller.executor_config();
+        let recoveries = Arc::new(AtomicU32::new(0));
+        let task_recoveries = Arc::clone(&recoveries);
+        let (stop_tx, mut stop_rx) = oneshot::channel();
+
+        let task_handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(policy.check_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = &mut stop_rx => break,
+                    _ = ticker.tick() => {}
+                }
+
+                let timeouts = config.timeouts().get();
+                if timeouts < policy.max_consecutive_timeouts {
+                    continue;
+                }
+
+                log::warn!("{} consecutive command timeouts, restarting router", timeouts);
+                let executor = config.executor();
+                let timeout_secs = policy.command_timeout_secs;
+                match executor
+                    .execute_status_with_timeout(&["router", "stop"], port.as_deref(), baud, timeout_secs)
+                    .await
+                {
+                    Err(BjigError::Closed) => break,
+                    Err(e) => log::warn!("Watchdog failed to stop router: {}", e),
+                    Ok(_) => {}
+                }
+                tokio::time::sleep(policy.restart_delay).await;
+                match executor
+                    .execute_status_with_timeout(&["router", "start"], port.as_deref(), baud, timeout_secs)
+                    .await
+                {
+                    Err(BjigError::Closed) => break,
+                    Err(e) => log::warn!("Watchdog failed to start router: {}", e),
+                    Ok(_) => {
+                        log::info!("Router restarted by watchdog");
+                        config.timeouts().reset();
+                        task_recoveries.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            }
+
+            log::debug!("Watchdog stopped");
+            Ok(())
+        });
+
+        Ok(WatchdogTask {
+            stop_tx: Some(stop_tx),
+            task_handle,
+            recoveries,
+        })
+    }
+
+    /// Send keep-alive periodically in a background task
+    ///
+    /// The first keep-alive is sent immediately. Ticks are skipped while a
+    /// DFU or monitor of the same controller holds the port. Failures are
+    /// logged and the loop continues; the loop ends once the controller is
+    /// shut down. The task is aborted when the returned `KeepAliveTask` is
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use std::time::Duration;
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let keep_alive = bjig.router().spawn_keep_alive(Duration::from_secs(600))?;
+    ///
+    /// // Do some work...
+    ///
+    /// keep_alive.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spawn_keep_alive(&self, interval: Duration) -> Result<KeepAliveTask> {
+        self.spawn_keep_alive_impl(None, None, interval)
+    }
+
+    /// Send keep-alive periodically on specific port in a background task
+    pub fn spawn_keep_alive_on(
+        &self,
+        port: &str,
+        baud: u32,
+        interval: Duration,
+    ) -> Result<KeepAliveTask> {
+        self.spawn_keep_alive_impl(Some(port.to_string()), Some(baud), interval)
+    }
+
+    fn spawn_keep_alive_impl(
+        &self,
+        port: Option<String>,
+        baud: Option<u32>,
+        interval: Duration,
+    ) -> Result<KeepAliveTask> {
+        self.controller.config.queue.ensure_open()?;
+        if interval.is_zero() {
+            return Err(BjigError::InvalidParameter(
+                "Keep-alive interval must be greater than zero".to_string(),
+            ));
+        }
+
+        let config = self.controller.executor_config();
+        let (stop_tx, mut stop_rx) = oneshot::channel();
+
+        let task_handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last_error = None;
+
+            loop {
+                tokio::select! {
+                    _ = &mut stop_rx => break,
+                    _ = ticker.tick() => {}
+                }
+
+                if config.port_busy() {
+                    log::debug!("Port held by DFU or monitor, skipping keep-alive");
+                    continue;
+                }
+
+                match config
+                    .executor()
+                    .execute_status(&["router", "keep-alive"], port.as_deref(), baud)
+                    .await
+                {
+                    Ok(_) => log::debug!("Keep-alive sent"),
+                    Err(BjigError::Closed) => {
+                        log::debug!("Controller shut down, stopping keep-alive");
+                        break;
+                    }
+                    Err(e) => {
+                        log::warn!("Keep-alive failed: {}", e);
+                        last_error = Some(e);
+                    }
+                }
+            }
+
+            last_error.map_or(Ok(()), Err)
+        });
+
+        Ok(KeepAliveTask {
+            stop_tx: Some(stop_tx),
+            task_handle,
+        })
+    }
+
+    /// Get supported sensor IDs and capabilities (static, no serial connection required)
+    ///
+    /// Blocks the current runtime worker thread until bjig exits and needs
+    /// the multi-threaded runtime.
+    #[deprecated(note = "blocks a runtime worker thread; use `get_supported_sensor_id_async`")]
+    pub fn get_supported_sensor_id(&self) -> Result<Vec<SensorInfo>> {
+        block_on_static(self.get_supported_sensor_id_async())
+    }
+
+    /// Get supported sensor IDs and capabilities (static, no serial connection required)
+    ///
+    /// Bounded by the controller's response timeout (see
+    /// `BjigControllerBuilder::command_timeout`); dropping the future kills
+    /// the bjig process.
+    ///
+    /// # Errors
+    /// Returns `BjigError::Timeout` if bjig does not answer in time.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let sensors = bjig.router().get_supported_sensor_id_async().await?;
+    /// for sensor in sensors {
+    ///     println!("{}: {}", sensor.sensor_id, sensor.sensor_name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_supported_sensor_id_async(&self) -> Result<Vec<SensorInfo>> {
+        let json = self
+            .executor()
+            .execute_static_array_with_timeout(
+                &["router", "get-supported-sensor-id"],
+                self.controller.response_timeout,
+            )
+            .await?;
+
+        deserialize("router get-supported-sensor-id", serde_json::Value::Array(json))
+    }
+
+    /// Check whether a sensor type supports a capability (static, no serial connection required)
+    ///
+    /// Blocks the current runtime worker thread until bjig exits and needs
+    /// the multi-threaded runtime.
+    #[deprecated(note = "blocks a runtime worker thread; use `sensor_supports_async`")]
+    pub fn sensor_supports(&self, sensor_id: &str, capability: &str) -> Result<bool> {
+        block_on_static(self.sensor_supports_async(sensor_id, capability))
+    }
+
+    /// Check whether a sensor type supports a capability (static, no serial connection required)
+    ///
+    /// Capabilities are matched case-insensitively (see `SensorInfo::supports`).
+    /// Bounded by the controller's response timeout like
+    /// `get_supported_sensor_id_async`.
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` if `sensor_id` is not a
+    /// supported sensor ID.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// if bjig.router().sensor_supports_async("0121", "instant_uplink").await? {
+    ///     bjig.module("0121", "2468800203400004").instant_uplink().await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sensor_supports_async(&self, sensor_id: &str, capability: &str) -> Result<bool> {
+        self.get_supported_sensor_id_async()
+            .await?
+            .iter()
+            .find(|s| s.sensor_id == sensor_id)
+            .map(|s| s.supports(capability))
+            .ok_or_else(|| {
+                BjigError::InvalidParameter(format!("Unsupported sensor ID: {}", sensor_id))
+            })
+    }
+
+    /// Check that an uplink carries the data fields its sensor declares
+    ///
+    /// Looks up the event's sensor in `sensors` (as returned by
+    /// `get_supported_sensor_id_async`, fetched once and reused across
+    /// events) and checks every field implied by its capabilities (see
+    /// `SensorInfo::expected_fields`) is present and not null. No bjig
+    /// command is run.
+    ///
+    /// # Errors
+    /// Returns `BjigError::IncompleteUplink` listing every missing field,
+    /// and `BjigError::InvalidParameter` if the event is not an uplink or
+    /// its sensor ID is not in `sensors`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, MonitorEvent};
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let sensors = bjig.router().get_supported_sensor_id_async().await?;
+    /// let event = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"2468800203400004"}"#);
+    /// if let Err(e) = bjig.router().validate_uplink(&sensors, &event) {
+    ///     eprintln!("{}", e);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_uplink(&self, sensors: &[SensorInfo], event: &MonitorEvent) -> Result<()> {
+        let uplink = event.as_uplink().ok_or_else(|| {
+            BjigError::InvalidParameter("Event is not a sensor uplink".to_string())
+        })?;
+        let sensor = sensors
+            .iter()
+            .find(|s| s.sensor_id == uplink.sensor_id)
+            .ok_or_else(|| {
+                BjigError::InvalidParameter(format!("Unsupported sensor ID: {}", uplink.sensor_id))
+            })?;
+
+        let missing: Vec<String> = sensor
+            .expected_fields()
+            .into_iter()
+            .filter(|field| uplink.extra.get(*field).is_none_or(|v| v.is_null()))
+            .map(str::to_string)
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(BjigError::IncompleteUplink {
+                sensor_id: uplink.sensor_id.clone(),
+                module_id: uplink.module_id.clone(),
+                missing,
+            })
+        }
+    }
+
+    /// Check that an uplink carries the data fields its sensor declares
+    ///
+    /// Fetches the sensor list with `get_supported_sensor_id_async` and
+    /// checks the event like `validate_uplink`. When validating many events,
+    /// fetch the list once and call `validate_uplink` instead.
+    ///
+    /// # Errors
+    /// Same as `validate_uplink`, plus errors from fetching the sensor list.
+    pub async fn validate_uplink_async(&self, event: &MonitorEvent) -> Result<()> {
+        let sensors = self.get_supported_sensor_id_async().await?;
+        self.validate_uplink(&sensors, event)
+    }
+
+    /// Get module configuration from YAML file (static, no serial connection required)
+    ///
+    /// Blocks the current runtime worker thread until bjig exits and needs
+    /// the multi-threaded runtime.
+    #[deprecated(note = "blocks a runtime worker thread; use `get_module_config_async`")]
+    pub fn get_module_config<P: AsRef<Path>>(&self, file_path: P) -> Result<Vec<ModuleConfig>> {
+        block_on_static(self.get_module_config_async(file_path))
+    }
+
+    /// Get module configuration from YAML file (static, no serial connection required)
+    ///
+    /// Bounded by the controller's response timeout like
+    /// `get_supported_sensor_id_async`.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to module config YAML file
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let config = bjig.router().get_module_config_async("module-config.yml").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_module_config_async<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+    ) -> Result<Vec<ModuleConfig>> {
+        let path_str = file_path.as_ref().to_string_lossy();
+
+        let json = self
+            .executor()
+            .execute_static_array_with_timeout(
+                &["router", "get-module-config", "--file", &path_str],
+                self.controller.response_timeout,
+            )
+            .await?;
+
+        deserialize("router get-module-config", serde_json::Value::Array(json))
+    }
+
+    /// Compare a module config file with the modules registered on the router
+    ///
+    /// Loads the desired config like `get_module_config_async` and the current
+    /// registrations with `get_module_id(None)`, and returns the changes
+    /// needed to make the router match the config. Nothing is modified.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let plan = bjig.router().config_plan("module-config.yml").await?;
+    ///
+    /// for entry in &plan.to_add {
+    ///     println!("+ {} ({})", entry.module_id, entry.sensor_id);
+    /// }
+    /// for module_id in &plan.to_remove {
+    ///     println!("- {}", module_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn config_plan<P: AsRef<Path>>(&self, desired_path: P) -> Result<ConfigPlan> {
+        self.config_plan_on(None, None, desired_path).await
+    }
+
+    /// Compare a module config file with the modules registered on specific port
+    pub async fn config_plan_on<P: AsRef<Path>>(
+        &self,
+        port: Option<&str>,
+        baud: Option<u32>,
+        desired_path: P,
+    ) -> Result<ConfigPlan> {
+        let desired = self.get_module_config_async(desired_path).await?;
+        let current = self.get_module_id_on(port, baud, None).await?.modules;
+
+        let mut plan = ConfigPlan::default();
+        for entry in desired {
+            if current.iter().any(|id| id.eq_ignore_ascii_case(&entry.module_id)) {
+                plan.unchanged.push(entry.module_id);
+            } else {
+                plan.to_add.push(entry);
+            }
+        }
+        plan.to_remove = current
+            .into_iter()
+            .filter(|id| !plan.unchanged.iter().any(|u| u.eq_ignore_ascii_case(id)))
+            .collect();
+
+        Ok(plan)
+    }
+
+    /// Load module configuration and validate it against supported sensors
+    ///
+    /// Blocks the current runtime worker thread until bjig exits and needs
+    /// the multi-threaded runtime.
+    #[deprecated(note = "blocks a runtime worker thread; use `load_validated_module_config_async`")]
+    pub fn load_validated_module_config<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+    ) -> Result<Vec<ModuleConfig>> {
+        block_on_static(self.load_validated_module_config_async(file_path))
+    }
+
+    /// Load module configuration and validate it against supported sensors
+    ///
+    /// Loads the YAML file like `get_module_config_async`, then checks that every
+    /// entry's `sensor_id` appears in `get_supported_sensor_id_async` and that no
+    /// `module_id` is listed more than once.
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` listing all unknown sensor IDs
+    /// and duplicate module IDs.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let config = bjig
+    ///     .router()
+    ///     .load_validated_module_config_async("module-config.yml")
+    ///     .await?;
+    /// println!("{} modules configured", config.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn load_validated_module_config_async<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+    ) -> Result<Vec<ModuleConfig>> {
+        let config = self.get_module_config_async(file_path).await?;
+        let supported = self.get_supported_sensor_id_async().await?;
+
+        let mut unknown_sensors: Vec<&str> = Vec::new();
+        let mut duplicate_modules: Vec<&str> = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for entry in &config {
+            if !supported.iter().any(|s| s.sensor_id == entry.sensor_id)
+                && !unknown_sensors.contains(&entry.sensor_id.as_str())
+            {
+                unknown_sensors.push(&entry.sensor_id);
+            }
+            if !seen.insert(entry.module_id.as_str())
+                && !duplicate_modules.contains(&entry.module_id.as_str())
+            {
+                duplicate_modules.push(&entry.module_id);
+            }
+        }
+
+        let mut problems = Vec::new();
+        if !unknown_sensors.is_empty() {
+            problems.push(format!("unknown sensor IDs: {}", unknown_sensors.join(", ")));
+        }
+        if !duplicate_modules.is_empty() {
+            problems.push(format!("duplicate module IDs: {}", duplicate_modules.join(", ")));
+        }
+        if !problems.is_empty() {
+            return Err(BjigError::InvalidParameter(format!(
+                "Invalid module config: {}",
+                problems.join("; ")
+            )));
+        }
+
+        Ok(config)
+    }
+
+    /// Router DFU (firmware update)
+    ///
+    /// # Arguments
+    /// * `firmware_path` - Path to firmware file
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let result = bjig.router().dfu("router_firmware.bin").await?;
     /// # Ok(())
     /// # }
     /// ```
@@ -386,6 +1664,57 @@ impl<'a> RouterCommands<'a> {
         self.dfu_on(None, None, firmware_path).await
     }
 
+    /// Router DFU reporting progress as a stream
+    ///
+    /// Yields a `DfuUpdate::Progress` for each progress line printed by bjig
+    /// and ends with `DfuUpdate::Finished` carrying the `DfuResult`. Lines
+    /// that are neither are skipped. If bjig fails or exits without a result,
+    /// the last item is an error. Dropping the stream aborts the DFU and
+    /// kills the bjig process.
+    ///
+    /// The DFU waits for queued commands to finish and holds the command
+    /// queue until the stream ends, so commands issued meanwhile run after
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::{BjigController, DfuUpdate};
+    /// use tokio_stream::StreamExt;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let mut updates = bjig.router().dfu_progress_stream("router_firmware.bin")?;
+    ///
+    /// while let Some(update) = updates.next().await {
+    ///     match update? {
+    ///         DfuUpdate::Progress(p) => println!("{}: {}%", p.phase, p.percentage),
+    ///         DfuUpdate::Finished(result) => println!("Done: {}", result.is_success()),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dfu_progress_stream<P: AsRef<Path>>(
+        &self,
+        firmware_path: P,
+    ) -> Result<impl Stream<Item = Result<DfuUpdate>>> {
+        let path = firmware_path.as_ref();
+
+        if !path.exists() {
+            return Err(BjigError::FileNotFound(path.to_path_buf()));
+        }
+
+        let path_str = path.to_string_lossy();
+        let lines = self
+            .executor()
+            .spawn_queued_line_stream(&["router", "dfu", "--file", &path_str], None, None)?;
+
+        Ok(DfuProgressStream {
+            lines: Some(lines),
+        })
+    }
+
     /// Router DFU on specific port
     pub async fn dfu_on<P: AsRef<Path>>(
         &self,
@@ -402,10 +1731,1194 @@ impl<'a> RouterCommands<'a> {
         let executor = self.executor();
         let path_str = path.to_string_lossy();
 
+        let _port = executor.hold_port();
         let json = executor
             .execute_json(&["router", "dfu", "--file", &path_str], port, baud)
             .await?;
 
-        Ok(serde_json::from_value(json)?)
+        decode("router dfu", json)
+    }
+}
+
+/// Handle for a background keep-alive loop
+///
+/// Created by `RouterCommands::spawn_keep_alive`. The loop is aborted when
+/// the handle is dropped; use `stop()` to let an in-flight keep-alive
+/// finish first.
+pub struct KeepAliveTask {
+    stop_tx: Option<oneshot::Sender<()>>,
+    task_handle: tokio::task::JoinHandle<Result<()>>,
+}
+
+impl KeepAliveTask {
+    /// Stop the loop gracefully
+    ///
+    /// # Errors
+    ///
+    /// Returns the last keep-alive error, if any keep-alive failed.
+    pub async fn stop(mut self) -> Result<()> {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+
+        match (&mut self.task_handle).await {
+            Ok(result) => result,
+            Err(e) => Err(BjigError::CommandFailed(format!("Keep-alive task panicked: {}", e))),
+        }
+    }
+
+    /// Check if the loop is still running
+    pub fn is_running(&self) -> bool {
+        !self.task_handle.is_finished()
+    }
+}
+
+impl Drop for KeepAliveTask {
+    fn drop(&mut self) {
+        self.task_handle.abort();
+    }
+}
+
+/// Handle of a background watchdog loop
+///
+/// Created by `RouterCommands::start_watchdog`. The loop is aborted when
+/// the handle is dropped; use `stop()` to let an in-flight recovery finish
+/// first.
+pub struct WatchdogTask {
+    stop_tx: Option<oneshot::Sender<()>>,
+    task_handle: tokio::task::JoinHandle<Result<()>>,
+    recoveries: Arc<AtomicU32>,
+}
+
+impl WatchdogTask {
+    /// Stop the loop gracefully
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the watchdog task panicked.
+    pub async fn stop(mut self) -> Result<()> {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+
+        match (&mut self.task_handle).await {
+            Ok(result) => result,
+            Err(e) => Err(BjigError::CommandFailed(format!("Watchdog task panicked: {}", e))),
+        }
+    }
+
+    /// Check if the loop is still running
+    pub fn is_running(&self) -> bool {
+        !self.task_handle.is_finished()
+    }
+
+    /// Number of stop/start recoveries performed so far
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for WatchdogTask {
+    fn drop(&mut self) {
+        self.task_handle.abort();
+    }
+}
+
+/// Stream decoding DFU output lines into `DfuUpdate`s
+///
+/// The line receiver is dropped once the stream ends, which kills bjig if
+/// it is still running.
+struct DfuProgressStream {
+    lines: Option<mpsc::Receiver<Result<String>>>,
+}
+
+impl Stream for DfuProgressStream {
+    type Item = Result<DfuUpdate>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let lines = match self.lines.as_mut() {
+                Some(lines) => lines,
+                None => return Poll::Ready(None),
+            };
+
+            let item = match lines.poll_recv(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(line))) => match decode_dfu_line(&line) {
+                    Some(update @ DfuUpdate::Progress(_)) => Ok(update),
+                    Some(update) => {
+                        self.lines = None;
+                        Ok(update)
+                    }
+                    None => {
+                        log::debug!("Skipping DFU output line: {}", line);
+                        continue;
+                    }
+                },
+                Poll::Ready(Some(Err(e))) => {
+                    self.lines = None;
+                    Err(e)
+                }
+                Poll::Ready(None) => {
+                    self.lines = None;
+                    Err(BjigError::CommandFailed(
+                        "DFU exited without reporting a result".to_string(),
+                    ))
+                }
+            };
+
+            return Poll::Ready(Some(item));
+        }
+    }
+}
+
+/// Turn a status result into its message, failing if it was unsuccessful
+pub(super) fn succeeded(success: bool, message: String) -> Result<String> {
+    if success {
+        Ok(message)
+    } else {
+        Err(BjigError::CommandFailed(message))
+    }
+}
+
+/// Append a provisioning step, failing with the report so far on error
+pub(super) fn record_step(
+    report: &mut ProvisionReport,
+    kind: ProvisionStepKind,
+    outcome: Result<String>,
+) -> Result<()> {
+    match outcome {
+        Ok(message) => {
+            report.steps.push(ProvisionStep {
+                kind,
+                success: true,
+                message,
+            });
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Provisioning step {:?} failed: {}", kind, e);
+            report.steps.push(ProvisionStep {
+                kind,
+                success: false,
+                message: e.to_string(),
+            });
+            Err(BjigError::ProvisionFailed {
+                report: std::mem::take(report),
+                source: Box::new(e),
+            })
+        }
+    }
+}
+
+/// Check whether a start/stop failure says the router is already in the target state
+fn is_already(message: &str) -> bool {
+    message.to_lowercase().contains("already")
+}
+
+/// Check whether an error persists no matter how long the router is polled
+fn is_permanent(error: &BjigError) -> bool {
+    match error {
+        BjigError::Closed
+        | BjigError::PortNotConfigured
+        | BjigError::BaudNotConfigured
+        | BjigError::BinaryNotFound(_) => true,
+        // bjig itself could not be started
+        BjigError::IoError(e) => matches!(
+            e.kind(),
+            std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied
+        ),
+        _ => false,
+    }
+}
+
+/// Run a static command future to completion from synchronous code
+///
+/// Blocks the current worker thread; requires the multi-threaded runtime.
+fn block_on_static<T>(future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
+
+/// Decode a DFU output line as progress or final result
+fn decode_dfu_line(line: &str) -> Option<DfuUpdate> {
+    if let Ok(progress) = serde_json::from_str::<DfuProgress>(line) {
+        return Some(DfuUpdate::Progress(progress));
+    }
+    serde_json::from_str::<DfuResult>(line)
+        .ok()
+        .map(DfuUpdate::Finished)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{assert_process_gone, fake_bjig, temp_file};
+    use tokio_stream::StreamExt;
+
+    /// Fake bjig that accepts any scan mode but always reports `reported`
+    fn scan_mode_bjig(reported: u8) -> BjigController {
+        let bin = fake_bjig(&format!(
+            r#"case "$*" in
+  *set-scan-mode*) echo '{{"result":"success","message":"Scan mode set"}}' ;;
+  *get-scan-mode*) echo '{{"mode":{},"mode_name":"Mode{}"}}' ;;
+esac"#,
+            reported, reported
+        ));
+        BjigController::new(bin).unwrap().with_port("/dev/null")
+    }
+
+    #[tokio::test]
+    async fn test_set_scan_mode_verified_match() {
+        let bjig = scan_mode_bjig(1);
+        let result = bjig
+            .router()
+            .set_scan_mode_verified(ScanModeType::Legacy)
+            .await
+            .unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(result.mode, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_set_scan_mode_verified_mismatch() {
+        let bjig = scan_mode_bjig(1);
+        let result = bjig
+            .router()
+            .set_scan_mode_verified(ScanModeType::LongRange)
+            .await;
+
+        assert!(matches!(result, Err(BjigError::VerificationFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_set_scan_mode_str() {
+        let (bjig, mock) = ensure_controller(crate::runner::MockRunner::new().on(
+            &["router", "set-scan-mode"],
+            r#"{"result":"success","message":"ok"}"#,
+        ));
+        let router = bjig.router();
+
+        assert!(router.set_scan_mode_str("LongRange").await.unwrap().is_success());
+        assert!(router.set_scan_mode_str("legacy").await.unwrap().is_success());
+        assert!(router.set_scan_mode_str("0").await.unwrap().is_success());
+        assert!(matches!(
+            router.set_scan_mode_str("turbo").await,
+            Err(BjigError::InvalidParameter(_))
+        ));
+
+        let modes: Vec<String> = mock.calls().iter().map(|argv| argv[6].clone()).collect();
+        assert_eq!(modes, vec!["0", "1", "0"]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_reports_partial_failure() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "get-version"], r#"{"major":1,"minor":2,"build":3,"version":"1.2.3"}"#)
+                .on_failure(&["router", "get-scan-mode"], "Serial port busy")
+                .on(&["router", "get-module-id"], r#"{"module_count":1,"modules":["2468800203400004"]}"#),
+        );
+
+        let snapshot = bjig.router().snapshot().await.unwrap();
+        assert_eq!(snapshot.version.unwrap().version, "1.2.3");
+        assert!(matches!(snapshot.scan_mode, Err(BjigError::CommandFailed(_))));
+        assert_eq!(snapshot.modules.unwrap().modules, vec!["2468800203400004"]);
+
+        let verbs: Vec<String> = mock.calls().iter().map(|argv| argv[5].clone()).collect();
+        assert_eq!(verbs, vec!["get-version", "get-scan-mode", "get-module-id"]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_reads_are_not_interleaved() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "get-version"], r#"{"major":1,"minor":2,"build":3,"version":"1.2.3"}"#)
+                .on(&["router", "get-scan-mode"], r#"{"mode":0,"mode_name":"LongRange"}"#)
+                .on(&["router", "get-module-id"], r#"{"module_count":0,"modules":[]}"#)
+                .on(&["router", "keep-alive"], r#"{"result":"success","message":"ok"}"#),
+        );
+        let router = bjig.router();
+
+        let (snapshot, keep_alive) = tokio::join!(router.snapshot(), async {
+            tokio::task::yield_now().await;
+            router.keep_alive().await
+        });
+        assert!(snapshot.unwrap().version.is_ok());
+        assert!(keep_alive.unwrap().is_success());
+
+        let verbs: Vec<String> = mock.calls().iter().map(|argv| argv[5].clone()).collect();
+        assert_eq!(verbs, vec!["get-version", "get-scan-mode", "get-module-id", "keep-alive"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_snapshot_keeps_min_interval() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "get-version"], r#"{"major":1,"minor":2,"build":3,"version":"1.2.3"}"#)
+                .on(&["router", "get-scan-mode"], r#"{"mode":0,"mode_name":"LongRange"}"#)
+                .on(&["router", "get-module-id"], r#"{"module_count":0,"modules":[]}"#),
+        );
+        let bjig = bjig.with_min_interval(Duration::from_millis(200));
+
+        bjig.router().get_version().await.unwrap();
+        let started = tokio::time::Instant::now();
+        bjig.router().snapshot().await.unwrap();
+
+        // One gap before the snapshot and one between each of its reads
+        assert!(started.elapsed() >= Duration::from_millis(600), "{:?}", started.elapsed());
+        assert_eq!(mock.calls().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_check_firmware() {
+        let (bjig, _mock) = ensure_controller(crate::runner::MockRunner::new().on(
+            &["router", "get-version"],
+            r#"{"major":1,"minor":2,"build":3,"version":"1.2.3+a1b2c3d"}"#,
+        ));
+        let router = bjig.router();
+
+        for (minimum, meets) in [("1.2.4", false), ("1.2.3", true), ("1.1.9", true), ("2.0.0", false)] {
+            let status = router.check_firmware(minimum.parse().unwrap()).await.unwrap();
+            assert_eq!(status.current.version, "1.2.3+a1b2c3d");
+            assert_eq!(status.meets_minimum, meets, "{}", minimum);
+            assert_eq!(status.needs_update, !meets, "{}", minimum);
+        }
+    }
+
+    #[test]
+    fn test_validate_uplink_lists_missing_fields() {
+        let sensors: Vec<SensorInfo> = serde_json::from_str(
+            r#"[{"sensor_id":"0121","sensor_name":"Illuminance","capabilities":["instant_uplink","illuminance","temperature"]}]"#,
+        )
+        .unwrap();
+        let bjig = BjigController::new(fake_bjig("exit 1")).unwrap();
+        let router = bjig.router();
+
+        let complete = MonitorEvent::parse(
+            r#"{"sensor_id":"0121","module_id":"A","lux":120.5,"temperature":21.0}"#,
+        );
+        router.validate_uplink(&sensors, &complete).unwrap();
+
+        let deficient = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","temperature":null}"#);
+        match router.validate_uplink(&sensors, &deficient) {
+            Err(BjigError::IncompleteUplink { sensor_id, module_id, missing }) => {
+                assert_eq!((sensor_id.as_str(), module_id.as_str()), ("0121", "A"));
+                assert_eq!(missing, vec!["lux", "temperature"]);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let unknown = MonitorEvent::parse(r#"{"sensor_id":"9999","module_id":"A"}"#);
+        assert!(matches!(
+            router.validate_uplink(&sensors, &unknown),
+            Err(BjigError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            router.validate_uplink(&sensors, &MonitorEvent::parse("router ready")),
+            Err(BjigError::InvalidParameter(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_validate_uplink_async() {
+        let bjig = config_bjig();
+        let router = bjig.router();
+        let event = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A"}"#);
+        router.validate_uplink_async(&event).await.unwrap();
+
+        let unknown = MonitorEvent::parse(r#"{"sensor_id":"9999","module_id":"A"}"#);
+        assert!(matches!(
+            router.validate_uplink_async(&unknown).await,
+            Err(BjigError::InvalidParameter(_))
+        ));
+    }
+
+    /// Fake bjig supporting sensor 0121 that serves the config file as-is
+    fn config_bjig() -> BjigController {
+        let bin = fake_bjig(
+            r#"case "$*" in
+  *get-supported-sensor-id*) echo '[{"sensor_id":"0121","sensor_name":"Illuminance","capabilities":["instant_uplink","dfu"]}]' ;;
+  *get-module-config*) cat "$4" ;;
+esac"#,
+        );
+        BjigController::new(bin).unwrap()
+    }
+
+    fn write_config(json: &str) -> std::path::PathBuf {
+        temp_file("module-config.json", json)
+    }
+
+    #[tokio::test]
+    async fn test_load_validated_module_config_ok() {
+        let path = write_config(
+            r#"[{"module_id":"A","sensor_id":"0121"},{"module_id":"B","sensor_id":"0121"}]"#,
+        );
+        let config = config_bjig()
+            .router()
+            .load_validated_module_config_async(&path)
+            .await
+            .unwrap();
+        assert_eq!(config.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_load_validated_module_config_unknown_sensor() {
+        let path = write_config(
+            r#"[{"module_id":"A","sensor_id":"0121"},{"module_id":"B","sensor_id":"9999"}]"#,
+        );
+        let err = config_bjig()
+            .router()
+            .load_validated_module_config_async(&path)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BjigError::InvalidParameter(ref m) if m.contains("9999")));
+    }
+
+    #[tokio::test]
+    async fn test_load_validated_module_config_duplicate() {
+        let path = write_config(
+            r#"[{"module_id":"A","sensor_id":"0121"},{"module_id":"A","sensor_id":"0121"}]"#,
+        );
+        let err = config_bjig()
+            .router()
+            .load_validated_module_config_async(&path)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BjigError::InvalidParameter(ref m) if m.contains("duplicate module IDs: A")));
+    }
+
+    #[tokio::test]
+    async fn test_config_plan_adds_and_removes() {
+        let bin = fake_bjig(
+            r#"case "$*" in
+  *get-module-config*) cat "$4" ;;
+  *get-module-id*) echo '{"module_count":3,"modules":["aaaa000000000001","CCCC000000000003","DDDD000000000004"]}' ;;
+esac"#,
+        );
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        let path = write_config(
+            r#"[{"module_id":"AAAA000000000001","sensor_id":"0121"},{"module_id":"BBBB000000000002","sensor_id":"0122"}]"#,
+        );
+
+        let plan = bjig.router().config_plan(&path).await.unwrap();
+
+        assert!(!plan.is_empty());
+        let to_add: Vec<&str> = plan.to_add.iter().map(|e| e.module_id.as_str()).collect();
+        assert_eq!(to_add, ["BBBB000000000002"]);
+        assert_eq!(plan.to_remove, ["CCCC000000000003", "DDDD000000000004"]);
+        assert_eq!(plan.unchanged, ["AAAA000000000001"]);
+    }
+
+    #[tokio::test]
+    async fn test_empty_output_on_success() {
+        let (bjig, _mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "stop"], "")
+                .on(&["router", "keep-alive"], "  \n")
+                .on(&["router", "get-version"], ""),
+        );
+
+        let stopped = bjig.router().stop().await.unwrap();
+        assert!(stopped.is_success());
+        assert_eq!(stopped.message, "Router stopped");
+        assert!(bjig.router().keep_alive().await.unwrap().is_success());
+
+        assert_eq!(
+            bjig.executor().execute_status(&["router", "stop"], None, None).await.unwrap(),
+            serde_json::Value::Null
+        );
+        // Other commands keep treating empty output as invalid JSON
+        assert!(matches!(
+            bjig.executor().execute_json(&["router", "stop"], None, None).await,
+            Err(BjigError::JsonParseError(_))
+        ));
+        // Commands that must return data fail instead of decoding `Null`
+        assert!(matches!(
+            bjig.router().get_version().await,
+            Err(BjigError::JsonParseError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sensor_supports() {
+        let bjig = config_bjig();
+        let router = bjig.router();
+        assert!(router.sensor_supports_async("0121", "DFU").await.unwrap());
+        assert!(!router.sensor_supports_async("0121", "set_parameter").await.unwrap());
+        assert!(matches!(
+            router.sensor_supports_async("9999", "dfu").await,
+            Err(BjigError::InvalidParameter(ref m)) if m.contains("9999")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_status_running() {
+        let bin = fake_bjig(r#"echo '{"major":1,"minor":2,"build":3,"version":"1.2.3"}'"#);
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+
+        let status = bjig.router().status().await.unwrap();
+        assert!(status.running);
+        assert_eq!(status.uptime_secs, None);
+    }
+
+    #[tokio::test]
+    async fn test_status_stopped() {
+        // Error reported on stdout with success exit
+        let bin = fake_bjig(r#"echo '{"result":"error","message":"Router is not running"}'"#);
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        assert!(!bjig.router().status().await.unwrap().running);
+
+        // Error reported via exit code
+        let bin = fake_bjig("echo 'Router is not running' >&2\nexit 1");
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        assert!(!bjig.router().status().await.unwrap().running);
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_port_faults() {
+        let bin = fake_bjig("echo 'open /dev/ttyACM0: No such file or directory' >&2\nexit 1");
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        let err = bjig.router().status().await.unwrap_err();
+        assert_eq!(err.port_fault(), Some(crate::PortFault::DeviceNotFound));
+
+        let bin = fake_bjig("echo 'Resource busy' >&2\nexit 1");
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        let err = bjig.router().status().await.unwrap_err();
+        assert_eq!(err.port_fault(), Some(crate::PortFault::PortBusy));
+    }
+
+    #[tokio::test]
+    async fn test_clear_all_modules_verified() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "remove-module-id"], r#"{"result":"success","message":"Removed all"}"#)
+                .on(&["router", "get-module-id"], r#"{"module_count":0,"modules":[]}"#),
+        );
+        assert_eq!(bjig.router().clear_all_modules_verified().await.unwrap(), 0);
+        let verbs: Vec<String> = mock.calls().iter().map(|argv| argv[5].clone()).collect();
+        assert_eq!(verbs, ["remove-module-id", "get-module-id"]);
+
+        let (bjig, _) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "remove-module-id"], r#"{"result":"success","message":"Removed all"}"#)
+                .on(
+                    &["router", "get-module-id"],
+                    r#"{"module_count":1,"modules":["2468800203400004"]}"#,
+                ),
+        );
+        assert!(matches!(
+            bjig.router().clear_all_modules_verified().await,
+            Err(BjigError::VerificationFailed(ref m)) if m.contains("2468800203400004")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_ready_polls_get_version() {
+        let bin = fake_bjig(
+            r#"echo x >> "$0.polls"
+if [ "$(wc -l < "$0.polls")" -le 2 ]; then echo 'Router not ready' >&2; exit 1; fi
+echo '{"major":1,"minor":2,"build":3,"version":"1.2.3"}'"#,
+        );
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let version = bjig
+            .router()
+            .wait_until_ready(Duration::from_millis(10), Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(version.version, "1.2.3");
+        let polls = std::fs::read_to_string(bin.with_extension("polls")).unwrap();
+        assert_eq!(polls.lines().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_ready_times_out() {
+        let (bjig, _) = ensure_controller(
+            crate::runner::MockRunner::new().on_failure(&["router", "get-version"], "Router not ready"),
+        );
+        let result = bjig
+            .router()
+            .wait_until_ready(Duration::from_millis(10), Duration::from_millis(100))
+            .await;
+
+        // Sub-second waits round up instead of reporting 0 seconds
+        assert!(matches!(result, Err(BjigError::Timeout { secs: 1 })));
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_ready_fails_fast_without_bjig() {
+        let bin = fake_bjig("exit 0");
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        std::fs::remove_file(&bin).unwrap();
+
+        let started = std::time::Instant::now();
+        let result = bjig
+            .router()
+            .wait_until_ready(Duration::from_millis(10), Duration::from_secs(30))
+            .await;
+
+        assert!(matches!(result, Err(BjigError::IoError(ref e)) if e.kind() == std::io::ErrorKind::NotFound));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    fn ensure_controller(mock: crate::runner::MockRunner) -> (BjigController, std::sync::Arc<crate::runner::MockRunner>) {
+        let mock = std::sync::Arc::new(mock);
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+        (bjig, mock)
+    }
+
+    #[tokio::test]
+    async fn test_static_command_timeout() {
+        let bin = fake_bjig(r#"echo $$ > "$0.pid"; exec sleep 30"#);
+        let bjig = BjigController::builder()
+            .bin_path(&bin)
+            .command_timeout(1)
+            .build()
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let result = bjig.router().get_supported_sensor_id_async().await;
+        assert!(matches!(result, Err(BjigError::Timeout { secs: 1 })));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_process_gone(&bin).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[allow(deprecated)]
+    async fn test_blocking_static_command_timeout() {
+        let bin = fake_bjig("sleep 30");
+        let bjig = BjigController::builder()
+            .bin_path(&bin)
+            .command_timeout(1)
+            .build()
+            .unwrap();
+
+        let result = bjig.router().sensor_supports("0121", "dfu");
+        assert!(matches!(result, Err(BjigError::Timeout { secs: 1 })));
+    }
+
+    #[tokio::test]
+    async fn test_get_scan_mode_typed() {
+        let (bjig, _) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["get-scan-mode"], r#"{"mode":1,"mode_name":"Legacy"}"#),
+        );
+        assert_eq!(bjig.router().get_scan_mode_typed().await.unwrap(), ScanModeType::Legacy);
+
+        let (bjig, _) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["get-scan-mode"], r#"{"mode":9,"mode_name":"Unknown"}"#),
+        );
+        let err = bjig.router().get_scan_mode_typed().await.unwrap_err();
+        assert!(matches!(&err, BjigError::InvalidParameter(m) if m.contains("9 (Unknown)")));
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_restarts_after_consecutive_timeouts() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on_timeout(&["router", "keep-alive"], 30)
+                .on(&["router", "stop"], r#"{"result":"success","message":"stopped"}"#)
+                .on(&["router", "start"], r#"{"result":"success","message":"started"}"#),
+        );
+        let policy = WatchdogPolicy {
+            max_consecutive_timeouts: 3,
+            restart_delay: Duration::from_millis(1),
+            check_interval: Duration::from_millis(10),
+            command_timeout_secs: 5,
+        };
+        let watchdog = bjig.router().start_watchdog(policy).unwrap();
+
+        for _ in 0..2 {
+            assert!(matches!(bjig.router().keep_alive().await, Err(BjigError::Timeout { secs: 30 })));
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(watchdog.recoveries(), 0);
+        assert_eq!(bjig.consecutive_timeouts(), 2);
+
+        assert!(bjig.router().keep_alive().await.is_err());
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while watchdog.recoveries() == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        watchdog.stop().await.unwrap();
+
+        assert_eq!(bjig.consecutive_timeouts(), 0);
+        let subcommands: Vec<String> = mock.calls().iter().map(|argv| argv[5].clone()).collect();
+        assert_eq!(subcommands, vec!["keep-alive", "keep-alive", "keep-alive", "stop", "start"]);
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_counts_only_successful_restarts() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on_timeout(&["router", "keep-alive"], 30)
+                .on(&["router", "stop"], r#"{"result":"success","message":"stopped"}"#)
+                .on_failure(&["router", "start"], "Router busy"),
+        );
+        let policy = WatchdogPolicy {
+            max_consecutive_timeouts: 1,
+            restart_delay: Duration::from_millis(1),
+            check_interval: Duration::from_millis(10),
+            command_timeout_secs: 5,
+        };
+        let watchdog = bjig.router().start_watchdog(policy).unwrap();
+
+        assert!(bjig.router().keep_alive().await.is_err());
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while !mock.calls().iter().any(|argv| argv[5] == "start") {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(watchdog.recoveries(), 0);
+        watchdog.stop().await.unwrap();
+    }
+
+    const VERSION_JSON: &str = r#"{"major":1,"minor":2,"build":3,"version":"1.2.3"}"#;
+
+    #[tokio::test]
+    async fn test_ensure_started() {
+        // Already running: no start issued
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new().on(&["router", "get-version"], VERSION_JSON),
+        );
+        assert!(!bjig.router().ensure_started().await.unwrap());
+        assert_eq!(mock.calls().len(), 1);
+
+        // Stopped: started
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on_failure(&["router", "get-version"], "Router is not running")
+                .on(&["router", "start"], r#"{"result":"success","message":"Router started"}"#),
+        );
+        assert!(bjig.router().ensure_started().await.unwrap());
+        assert_eq!(mock.calls().len(), 2);
+
+        // Probe says stopped but firmware reports already running
+        let (bjig, _) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on_failure(&["router", "get-version"], "Router is not running")
+                .on_failure(&["router", "start"], "Router already running"),
+        );
+        assert!(!bjig.router().ensure_started().await.unwrap());
+
+        let (bjig, _) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on_failure(&["router", "get-version"], "Router is not running")
+                .on(&["router", "start"], r#"{"result":"error","message":"Serial port busy"}"#),
+        );
+        assert!(matches!(
+            bjig.router().ensure_started().await,
+            Err(BjigError::CommandFailed(ref m)) if m == "Serial port busy"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_stopped() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on_failure(&["router", "get-version"], "Router is not running"),
+        );
+        assert!(!bjig.router().ensure_stopped().await.unwrap());
+        assert_eq!(mock.calls().len(), 1);
+
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "get-version"], VERSION_JSON)
+                .on(&["router", "stop"], r#"{"result":"success","message":"Router stopped"}"#),
+        );
+        assert!(bjig.router().ensure_stopped().await.unwrap());
+        assert_eq!(mock.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_provision_full_sequence() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "set-scan-mode"], r#"{"result":"success","message":"Scan mode set"}"#)
+                .on(&["router", "remove-module-id"], r#"{"result":"success","message":"Removed all"}"#)
+                .on(&["router", "get-scan-mode"], r#"{"mode":1,"mode_name":"Legacy"}"#)
+                .on(&["router", "get-module-id"], r#"{"module_count":0,"modules":[]}"#),
+        );
+        let spec = ProvisionSpec::new()
+            .with_scan_mode(ScanModeType::Legacy)
+            .with_clear_modules(true)
+            .with_settle_delay(Duration::from_millis(10));
+
+        let report = bjig.router().provision(spec).await.unwrap();
+        assert!(report.is_success());
+        let kinds: Vec<_> = report.steps.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ProvisionStepKind::SetScanMode,
+                ProvisionStepKind::ClearModules,
+                ProvisionStepKind::Settle,
+                ProvisionStepKind::VerifyScanMode,
+                ProvisionStepKind::VerifyModulesCleared,
+            ]
+        );
+
+        let subcommands: Vec<String> = mock.calls().iter().map(|argv| argv[5].clone()).collect();
+        assert_eq!(
+            subcommands,
+            vec!["set-scan-mode", "remove-module-id", "get-scan-mode", "get-module-id"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provision_stops_on_failure() {
+        let (bjig, mock) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "set-scan-mode"], r#"{"result":"success","message":"Scan mode set"}"#)
+                .on(&["router", "remove-module-id"], r#"{"result":"error","message":"Router busy"}"#),
+        );
+        let spec = ProvisionSpec::new()
+            .with_scan_mode(ScanModeType::LongRange)
+            .with_clear_modules(true);
+
+        match bjig.router().provision(spec).await {
+            Err(BjigError::ProvisionFailed { report, source }) => {
+                assert_eq!(report.steps.len(), 2);
+                assert!(report.steps[0].success);
+                assert_eq!(report.steps[1].kind, ProvisionStepKind::ClearModules);
+                assert!(!report.steps[1].success);
+                assert!(matches!(*source, BjigError::CommandFailed(ref m) if m == "Router busy"));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert_eq!(mock.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_provision_verification_failure() {
+        let (bjig, _) = ensure_controller(
+            crate::runner::MockRunner::new()
+                .on(&["router", "remove-module-id"], r#"{"result":"success","message":"Removed all"}"#)
+                .on(
+                    &["router", "get-module-id"],
+                    r#"{"module_count":1,"modules":["2468800203400004"]}"#,
+                ),
+        );
+        let spec = ProvisionSpec::new().with_clear_modules(true);
+
+        match bjig.router().provision(spec).await {
+            Err(BjigError::ProvisionFailed { report, source }) => {
+                assert_eq!(report.steps[1].kind, ProvisionStepKind::VerifyModulesCleared);
+                assert!(matches!(
+                    *source,
+                    BjigError::VerificationFailed(ref m) if m.contains("2468800203400004")
+                ));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_start_with_timeout_expires() {
+        let bin = fake_bjig("sleep 10");
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+
+        let result = bjig.router().start_with_timeout(1).await;
+        assert!(matches!(result, Err(BjigError::Timeout { secs: 1 })));
+    }
+
+    #[tokio::test]
+    async fn test_stop_with_timeout_completes() {
+        let bin = fake_bjig(r#"echo '{"result":"success","message":"Router stopped"}'"#);
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+
+        let result = bjig.router().stop_with_timeout(5).await.unwrap();
+        assert!(result.is_success());
+    }
+
+    fn module_list_controller() -> (BjigController, std::sync::Arc<crate::runner::MockRunner>) {
+        let mock = std::sync::Arc::new(
+            crate::runner::MockRunner::new()
+                .on(
+                    &["router", "get-module-id"],
+                    r#"{"module_count":3,"modules":["AAAA000000000001","2468800203400004","BBBB000000000002"]}"#,
+                )
+                .on(&["router", "remove-module-id"], r#"{"result":"success","message":"removed"}"#),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .baud(38400)
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+        (bjig, mock)
+    }
+
+    fn keep_alive_controller(output_ok: bool) -> (BjigController, std::sync::Arc<crate::runner::MockRunner>) {
+        let mock = crate::runner::MockRunner::new();
+        let mock = std::sync::Arc::new(if output_ok {
+            mock.on(&["router", "keep-alive"], r#"{"result":"success","message":"ok"}"#)
+        } else {
+            mock.on_failure(&["router", "keep-alive"], "Serial port busy")
+        });
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+        (bjig, mock)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_dropping_keep_alive_task_stops_loop() {
+        let (bjig, mock) = keep_alive_controller(true);
+
+        // Ticks at 0, 20, 40 and 60 ms
+        let task = bjig.router().spawn_keep_alive(Duration::from_millis(20)).unwrap();
+        tokio::time::sleep(Duration::from_millis(70)).await;
+        assert!(task.is_running());
+        drop(task);
+
+        assert_eq!(mock.calls().len(), 4);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(mock.calls().len(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keep_alive_stop_returns_last_error() {
+        let (bjig, _) = keep_alive_controller(true);
+        let task = bjig.router().spawn_keep_alive(Duration::from_millis(20)).unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        task.stop().await.unwrap();
+
+        let (bjig, mock) = keep_alive_controller(false);
+        let task = bjig.router().spawn_keep_alive(Duration::from_millis(20)).unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(matches!(task.stop().await, Err(BjigError::CommandFailed(_))));
+        assert!(!mock.calls().is_empty());
+
+        assert!(matches!(
+            bjig.router().spawn_keep_alive(Duration::ZERO),
+            Err(BjigError::InvalidParameter(_))
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keep_alive_skips_ticks_while_port_held() {
+        let (bjig, mock) = keep_alive_controller(true);
+
+        // Simulated DFU holding the port
+        let dfu = bjig.config.port_activity.begin_exclusive();
+        let task = bjig.router().spawn_keep_alive(Duration::from_millis(10)).unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(mock.calls().is_empty());
+
+        drop(dfu);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(!mock.calls().is_empty());
+        task.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dfu_holds_port_while_running() {
+        let firmware = temp_file("router.bin", "firmware");
+        let bin = fake_bjig(r#"sleep 0.3; echo '{"result":"success","message":"done"}'"#);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let router = bjig.router();
+        let dfu = router.dfu(&firmware);
+        let check = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            bjig.config.port_activity.is_exclusive()
+        };
+        let (result, held) = tokio::join!(dfu, check);
+
+        assert!(result.unwrap().is_success());
+        assert!(held);
+        assert!(!bjig.config.port_activity.is_exclusive());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keep_alive_ends_on_shutdown() {
+        let (bjig, _) = keep_alive_controller(true);
+        let task = bjig.router().spawn_keep_alive(Duration::from_millis(10)).unwrap();
+
+        bjig.shutdown().await;
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while task.is_running() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(task.stop().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_module_id_single() {
+        let mock = std::sync::Arc::new(
+            crate::runner::MockRunner::new()
+                .on(&["get-module-id", "3"], r#"{"module_count":1,"modules":["2468800203400004"]}"#)
+                .on(&["get-module-id", "4"], r#"{"module_count":0,"modules":[]}"#),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+        let router = bjig.router();
+
+        assert_eq!(
+            router.get_module_id_single(3).await.unwrap().as_deref(),
+            Some("2468800203400004")
+        );
+        assert_eq!(router.get_module_id_single(4).await.unwrap(), None);
+
+        assert!(matches!(
+            router.get_module_id_single(100).await,
+            Err(BjigError::InvalidParameter(_))
+        ));
+        assert_eq!(mock.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_detect_duplicate_modules() {
+        let (bjig, _) = module_list_controller();
+        assert!(bjig.router().detect_duplicate_modules().await.unwrap().is_empty());
+
+        let mock = crate::runner::MockRunner::new().on(
+            &["router", "get-module-id"],
+            r#"{"module_count":5,"modules":["AAAA000000000001","2468800203400004","aaaa000000000001","BBBB000000000002","AAAA000000000001"]}"#,
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock)
+            .build()
+            .unwrap();
+        assert_eq!(
+            bjig.router().detect_duplicate_modules().await.unwrap(),
+            vec!["AAAA000000000001"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_module_by_id() {
+        let (bjig, mock) = module_list_controller();
+
+        let result = bjig.router().remove_module_by_id("2468800203400004").await.unwrap();
+        assert!(result.is_success());
+
+        let calls = mock.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1][4..], ["router", "remove-module-id", "1"]);
+    }
+
+    #[tokio::test]
+    async fn test_remove_module_by_id_not_registered() {
+        let (bjig, mock) = module_list_controller();
+
+        let result = bjig.router().remove_module_by_id("CCCC000000000003").await;
+        assert!(matches!(result, Err(BjigError::InvalidParameter(_))));
+        assert_eq!(mock.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dfu_progress_stream_decodes_lines() {
+        let bin = fake_bjig(
+            r#"echo '[INFO] Starting DFU'
+echo '{"phase":"transfer","chunk_number":1,"total_chunks":2,"percentage":50}'
+echo '{"phase":"transfer","chunk_number":2,"total_chunks":2,"percentage":100}'
+echo '{"result":"success","message":"DFU complete"}'"#,
+        );
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        let firmware = temp_file("router.bin", "firmware");
+
+        let updates: Vec<_> = bjig
+            .router()
+            .dfu_progress_stream(&firmware)
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(updates.len(), 3);
+        let percentages: Vec<_> = updates[..2]
+            .iter()
+            .map(|u| match u {
+                Ok(DfuUpdate::Progress(p)) => p.percentage,
+                other => panic!("unexpected update: {:?}", other),
+            })
+            .collect();
+        assert_eq!(percentages, [50, 100]);
+        assert!(matches!(&updates[2], Ok(DfuUpdate::Finished(r)) if r.is_success()));
+    }
+
+    #[tokio::test]
+    async fn test_dfu_progress_stream_reports_failure() {
+        let bin = fake_bjig(
+            r#"echo '{"phase":"erase","chunk_number":0,"total_chunks":2,"percentage":0}'
+echo 'Error: device disconnected' >&2
+exit 2"#,
+        );
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        let firmware = temp_file("router.bin", "firmware");
+
+        let updates: Vec<_> = bjig
+            .router()
+            .dfu_progress_stream(&firmware)
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(updates.len(), 2);
+        assert!(matches!(updates[0], Ok(DfuUpdate::Progress(_))));
+        assert!(matches!(updates[1], Err(BjigError::CommandFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dfu_progress_stream_drop_kills_process() {
+        let bin = fake_bjig(
+            r#"echo $$ > "$0.pid"
+echo '{"phase":"transfer","chunk_number":1,"total_chunks":9,"percentage":11}'
+sleep 10"#,
+        );
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let firmware = temp_file("router.bin", "firmware");
+
+        let mut updates = Box::pin(bjig.router().dfu_progress_stream(&firmware).unwrap());
+        assert!(matches!(updates.next().await, Some(Ok(DfuUpdate::Progress(_)))));
+        drop(updates);
+
+        assert_process_gone(&bin).await;
+    }
+
+    #[tokio::test]
+    async fn test_dfu_progress_stream_holds_queue() {
+        let bin = fake_bjig(
+            r#"case "$6" in
+  dfu) echo "dfu start" >> "$0.log"; sleep 0.3; echo "dfu end" >> "$0.log"
+       echo '{"result":"success","message":"DFU complete"}' ;;
+  *) echo "$6" >> "$0.log"; echo '{"result":"success","message":"ok"}' ;;
+esac"#,
+        );
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        let firmware = temp_file("router.bin", "firmware");
+
+        let router = bjig.router();
+        let updates = router.dfu_progress_stream(&firmware).unwrap();
+        let (updates, keep_alive): (Vec<_>, _) = tokio::join!(updates.collect(), router.keep_alive());
+
+        assert!(matches!(updates.last(), Some(Ok(DfuUpdate::Finished(_)))));
+        assert!(keep_alive.unwrap().is_success());
+        let log = std::fs::read_to_string(bin.with_extension("log")).unwrap();
+        assert_eq!(log.lines().collect::<Vec<_>>(), ["dfu start", "dfu end", "keep-alive"]);
     }
 }
@@ -1,10 +1,74 @@
 //! Core BjigController implementation
 
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::commands::{MonitorCommand, ModuleCommands, RouterCommands};
+use tokio::sync::mpsc;
+
+use crate::commands::monitor::ControlMessage;
+use crate::commands::{BatchModuleCommands, MonitorCommand, ModuleCommands, RouterCommands};
 use crate::env;
-use crate::types::{BjigError, Result};
+use crate::executor::{CommandExecutor, CommandQueue, LogId, PortActivity, TimeoutCounter};
+use crate::metrics::{CommandObserver, MetricsSink, StderrObserver};
+use crate::ports;
+use crate::resilient::ResilientController;
+use crate::runner::CommandRunner;
+use crate::types::{BjigError, CommandOutput, HealthReport, Result};
+
+/// Retry policy for failed commands
+///
+/// Applies to read-only JSON commands (version, module IDs, scan mode,
+/// keep-alive, debug dump, parameter reads and instant uplink) that fail
+/// with `BjigError::CommandFailed` or `BjigError::Timeout`. Commands with
+/// side effects (set-scan-mode, remove-module-id, set-parameter, control,
+/// restart, start/stop, DFU) and raw commands outside that list run once
+/// regardless of the policy, because a failed attempt may already have
+/// been applied. Streaming commands (monitor) are not retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: u32,
+    /// Delay between attempts
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Buffering of streaming command output (monitor, DFU progress)
+///
+/// stdout is read through a buffer of `read_capacity` bytes by a reader
+/// task that queues up to `max_pending_lines` lines for the consumer. When
+/// the queue is full, reading pauses until the callback catches up, so the
+/// OS pipe fills and bjig blocks instead of lines being dropped.
+///
+/// Memory use per stream is roughly `read_capacity` plus
+/// `max_pending_lines` times the line length. Larger values absorb longer
+/// uplink bursts without stalling bjig at the cost of more memory. Values
+/// of 0 are treated as 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamBufferConfig {
+    /// Capacity of the stdout read buffer in bytes
+    pub read_capacity: usize,
+    /// Maximum number of lines read ahead of the consumer
+    pub max_pending_lines: usize,
+}
+
+impl Default for StreamBufferConfig {
+    fn default() -> Self {
+        Self {
+            read_capacity: 8 * 1024,
+            max_pending_lines: 64,
+        }
+    }
+}
 
 /// Main controller for bjig CLI operations
 ///
@@ -30,13 +94,36 @@ use crate::types::{BjigError, Result};
 /// }
 /// ```
 pub struct BjigController {
-    pub(crate) bjig_path: PathBuf,
-    pub(crate) default_port: Option<String>,
-    pub(crate) default_baud: Option<u32>,
+    pub(crate) config: ExecutorConfig,
     pub(crate) module_config_path: Option<PathBuf>,
+    pub(crate) response_timeout: u64,
+    pub(crate) usb_ids: Option<(u16, u16)>,
+    pub(crate) monitors: Mutex<Vec<mpsc::WeakSender<ControlMessage>>>,
 }
 
 impl BjigController {
+    /// Create a builder for configuring a controller in one place
+    ///
+    /// Unlike `new`/`from_env` with chained `with_*` calls, all environment
+    /// fallbacks are resolved and the binary is validated in `build()`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::{BjigController, RetryPolicy};
+    ///
+    /// let bjig = BjigController::builder()
+    ///     .port("/dev/ttyACM0")
+    ///     .baud(115200)
+    ///     .command_timeout(60)
+    ///     .retry(RetryPolicy::default())
+    ///     .build()?;
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn builder() -> BjigControllerBuilder {
+        BjigControllerBuilder::default()
+    }
+
     /// Create new controller with explicit bjig binary path
     ///
     /// # Arguments
@@ -60,12 +147,38 @@ impl BjigController {
             return Err(BjigError::BinaryNotFound(path));
         }
 
-        Ok(Self {
-            bjig_path: path,
-            default_port: None,
-            default_baud: None,
+        Ok(Self::with_unchecked_path(path))
+    }
+
+    /// Create controller without checking that the binary exists
+    fn with_unchecked_path(bjig_path: PathBuf) -> Self {
+        Self {
+            config: ExecutorConfig {
+                bjig_path,
+                working_dir: None,
+                default_port: None,
+                default_baud: None,
+                lenient_json: false,
+                normalize_fields: false,
+                require_explicit_baud: false,
+                allowed_bauds: None,
+                redact_logs: false,
+                min_interval: Duration::ZERO,
+                stream_buffer: StreamBufferConfig::default(),
+                retry: None,
+                runner: None,
+                metrics: None,
+                observer: None,
+                stderr_observer: None,
+                queue: Arc::new(CommandQueue::default()),
+                port_activity: Arc::new(PortActivity::default()),
+                timeouts: Arc::new(TimeoutCounter::default()),
+            },
             module_config_path: None,
-        })
+            response_timeout: env::DEFAULT_RESPONSE_TIMEOUT_SECS,
+            usb_ids: None,
+            monitors: Mutex::new(Vec::new()),
+        }
     }
 
     /// Create controller with auto-detection from environment variables
@@ -74,6 +187,7 @@ impl BjigController {
     /// - `BJIG_CLI_BIN_PATH` - bjig binary path (default: "./bin/bjig")
     /// - `BJIG_CLI_PORT` - serial port
     /// - `BJIG_CLI_BAUD` - baud rate
+    /// - `BJIG_CLI_USB_ID` - router USB ID for `autodetect_port` (`VID:PID` in hex)
     ///
     /// # Examples
     ///
@@ -90,15 +204,39 @@ impl BjigController {
 
         // Auto-load from environment
         if let Some(port) = env::get_port_from_env() {
-            controller.default_port = Some(port);
+            controller.config.default_port = Some(port);
         }
         if let Some(baud) = env::get_baud_from_env() {
-            controller.default_baud = Some(baud);
+            controller.config.default_baud = Some(baud);
         }
+        controller.usb_ids = env::get_usb_ids_from_env();
 
         Ok(controller)
     }
 
+    /// Create controller from environment variables, detecting the port if unset
+    ///
+    /// Like `from_env`, but when `BJIG_CLI_PORT` is not set the serial port
+    /// is found by the USB vendor/product ID in `BJIG_CLI_USB_ID`. Use
+    /// `with_usb_ids` followed by `autodetect_port` to set the IDs in code.
+    ///
+    /// # Errors
+    /// Returns `BjigError::UsbIdsNotConfigured` if neither variable is set,
+    /// and `BjigError::NoMatchingPort` or `BjigError::AmbiguousPort` if not
+    /// exactly one matching port is connected.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env_autodetect()?;
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn from_env_autodetect() -> Result<Self> {
+        Self::from_env()?.autodetect_port()
+    }
+
     /// Set default serial port
     ///
     /// This port will be used for all commands unless overridden.
@@ -113,7 +251,7 @@ impl BjigController {
     /// # Ok::<(), bjig_controller::BjigError>(())
     /// ```
     pub fn with_port(mut self, port: impl Into<String>) -> Self {
-        self.default_port = Some(port.into());
+        self.config.default_port = Some(port.into());
         self
     }
 
@@ -131,7 +269,7 @@ impl BjigController {
     /// # Ok::<(), bjig_controller::BjigError>(())
     /// ```
     pub fn with_baud(mut self, baud: u32) -> Self {
-        self.default_baud = Some(baud);
+        self.config.default_baud = Some(baud);
         self
     }
 
@@ -151,6 +289,368 @@ impl BjigController {
         self
     }
 
+    /// Set working directory for every bjig process
+    ///
+    /// bjig resolves relative resources such as `module-config.yml` against
+    /// its working directory. A relative bjig binary path is made absolute
+    /// first, so it still refers to the same binary.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::new("./bin/bjig")?
+    ///     .with_working_dir("/var/lib/bjig");
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_working_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        if self.config.bjig_path.is_relative() {
+            if let Ok(path) = std::path::absolute(&self.config.bjig_path) {
+                self.config.bjig_path = path;
+            }
+        }
+        self.config.working_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set default response timeout for module commands
+    ///
+    /// Used by `instant_uplink`, `get_parameter`, `set_parameter`, `restart`,
+    /// and `control` unless a timeout is passed explicitly via the
+    /// `*_with_timeout` variants. Defaults to 30 seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::new("./bin/bjig")?
+    ///     .with_response_timeout(90);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_response_timeout(mut self, timeout_secs: u64) -> Self {
+        self.response_timeout = timeout_secs;
+        self
+    }
+
+    /// Enable lenient JSON parsing of command output
+    ///
+    /// Some bjig builds print log lines before the JSON response. When
+    /// enabled, the first balanced JSON object or array is extracted from
+    /// stdout before parsing (see `extract_json`). Disabled by default, in
+    /// which case stdout must be exactly one JSON value.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::new("./bin/bjig")?
+    ///     .with_lenient_json(true);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_lenient_json(mut self, lenient: bool) -> Self {
+        self.config.lenient_json = lenient;
+        self
+    }
+
+    /// Rewrite JSON object keys of command output to snake_case
+    ///
+    /// Some firmware versions report `moduleId` where others report
+    /// `module_id`. When enabled, every key of a parsed response is
+    /// rewritten recursively (e.g. `moduleId` and `ModuleID` become
+    /// `module_id`) before typed deserialization, so the typed results work
+    /// with both. Disabled by default.
+    ///
+    /// The pass walks and rebuilds the whole response, which costs an extra
+    /// allocation per key; this is negligible next to spawning bjig but not
+    /// free for very large responses. Keys inside free-form values such as
+    /// module parameters are rewritten too. If an object holds both spellings
+    /// of a key, the one already in snake_case is kept and the other dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::new("./bin/bjig")?
+    ///     .with_field_normalization(true);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_field_normalization(mut self, normalize: bool) -> Self {
+        self.config.normalize_fields = normalize;
+        self
+    }
+
+    /// Mask module IDs in the logs of this crate
+    ///
+    /// When enabled, module IDs are shown as their first 4 and last 2
+    /// characters (e.g. `2468**********04`) in logged argv, in log messages
+    /// naming a module, and in logged output of non-streaming commands.
+    /// bjig itself still receives the full ID. Error values, lines passed to
+    /// callbacks and stderr of streaming commands are not redacted. Disabled
+    /// by default.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::new("./bin/bjig")?
+    ///     .with_log_redaction(true);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_log_redaction(mut self, redact: bool) -> Self {
+        self.config.redact_logs = redact;
+        self
+    }
+
+    /// Enforce a minimum gap between consecutive commands
+    ///
+    /// The next command starts no earlier than `interval` after the previous
+    /// one completed, which keeps bursts from overwhelming a marginal radio
+    /// link. Streaming commands (monitor, DFU progress) are exempt. Disabled
+    /// (zero) by default.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?
+    ///     .with_min_interval(Duration::from_millis(500));
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.config.min_interval = interval;
+        self
+    }
+
+    /// Restrict baud rates to `bauds`
+    ///
+    /// Commands with any other baud rate fail with
+    /// `BjigError::InvalidParameter` before bjig is spawned. Without an
+    /// allowlist, rates outside `STANDARD_BAUD_RATES` only log a warning.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?
+    ///     .with_allowed_bauds([38400, 115200]);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_allowed_bauds(mut self, bauds: impl IntoIterator<Item = u32>) -> Self {
+        self.config.allowed_bauds = Some(bauds.into_iter().collect());
+        self
+    }
+
+    /// Require a baud rate to be configured instead of defaulting to 38400
+    ///
+    /// When enabled, commands fail with `BjigError::BaudNotConfigured` if no
+    /// baud rate is given explicitly, set on the controller, or set in
+    /// `BJIG_CLI_BAUD`. Disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?
+    ///     .require_explicit_baud(true);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn require_explicit_baud(mut self, require: bool) -> Self {
+        self.config.require_explicit_baud = require;
+        self
+    }
+
+    /// Retry failed commands according to `policy`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::{BjigController, RetryPolicy};
+    /// use std::time::Duration;
+    ///
+    /// let bjig = BjigController::new("./bin/bjig")?
+    ///     .with_retry(RetryPolicy { max_retries: 2, delay: Duration::from_millis(500) });
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.config.retry = Some(policy);
+        self
+    }
+
+    /// Run non-streaming commands through `runner` instead of spawning bjig
+    ///
+    /// Intended for tests (see `MockRunner`). Monitor commands still spawn
+    /// the bjig binary.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::{BjigController, MockRunner};
+    ///
+    /// let mock = MockRunner::new().on(&["router", "keep-alive"], r#"{"result":"success","message":"ok"}"#);
+    /// let bjig = BjigController::new("./bin/bjig")?.with_runner(mock);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_runner(mut self, runner: impl CommandRunner + 'static) -> Self {
+        self.config.runner = Some(Arc::new(runner));
+        self
+    }
+
+    /// Set buffering of streaming command output
+    ///
+    /// See `StreamBufferConfig` for the memory tradeoff.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::{BjigController, StreamBufferConfig};
+    ///
+    /// let bjig = BjigController::from_env()?.with_stream_buffer(StreamBufferConfig {
+    ///     read_capacity: 64 * 1024,
+    ///     max_pending_lines: 1024,
+    /// });
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_stream_buffer(mut self, config: StreamBufferConfig) -> Self {
+        self.config.stream_buffer = config;
+        self
+    }
+
+    /// Report count, latency and outcome of every command to `sink`
+    ///
+    /// The sink is called after each non-streaming command; monitor
+    /// sessions are not reported.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use bjig_controller::{BjigController, MetricsSink};
+    ///
+    /// struct LogMetrics;
+    ///
+    /// impl MetricsSink for LogMetrics {
+    ///     fn record(&self, subcommand: &str, duration: Duration, success: bool) {
+    ///         println!("{} took {:?} (ok: {})", subcommand, duration, success);
+    ///     }
+    /// }
+    ///
+    /// let bjig = BjigController::from_env()?.with_metrics(LogMetrics);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn with_metrics(mut self, sink: impl MetricsSink + 'static) -> Self {
+        self.config.metrics = Some(Arc::new(sink));
+        self
+    }
+
+    /// Call `observer` with a `CommandRecord` after every command
+    ///
+    /// Unlike `with_metrics`, the record carries the full argv, which is
+    /// masked like the debug logs when `with_log_redaction` is enabled.
+    /// Only non-streaming commands are observed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use bjig_controller::{BjigController, CommandRecord};
+    ///
+    /// let bjig = BjigController::from_env()?
+    ///     .with_log_redaction(true)
+    ///     .on_command(Arc::new(|record: &CommandRecord| {
+    ///         println!("{:?} -> ok: {} in {:?}", record.argv, record.success, record.duration);
+    ///     }));
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn on_command(mut self, observer: CommandObserver) -> Self {
+        self.config.observer = Some(observer);
+        self
+    }
+
+    /// Call `observer` with each stderr line of streaming commands (e.g. monitor)
+    ///
+    /// stderr is read while the command runs, so warnings logged by the
+    /// router during a long monitor session are seen as they happen. Without
+    /// an observer, the lines are logged with `log::warn!`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?
+    ///     .on_stderr(Arc::new(|line: &str| eprintln!("router: {}", line)));
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn on_stderr(mut self, observer: StderrObserver) -> Self {
+        self.config.stderr_observer = Some(observer);
+        self
+    }
+
+    /// Set USB vendor/product ID used by `autodetect_port`
+    ///
+    /// The library has no built-in IDs for the router; check them with
+    /// `lsusb` or `available_usb_ports`.
+    pub fn with_usb_ids(mut self, vid: u16, pid: u16) -> Self {
+        self.usb_ids = Some((vid, pid));
+        self
+    }
+
+    /// Detect the serial port by USB ID if no default port is set
+    ///
+    /// # Errors
+    /// Returns `BjigError::UsbIdsNotConfigured` if no port is set and the
+    /// USB IDs were not set with `with_usb_ids` or `BJIG_CLI_USB_ID`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?
+    ///     .with_usb_ids(0x2fe3, 0x0100)
+    ///     .autodetect_port()?;
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn autodetect_port(mut self) -> Result<Self> {
+        if self.config.default_port.is_none() {
+            let (vid, pid) = self.usb_ids.ok_or(BjigError::UsbIdsNotConfigured)?;
+            self.config.default_port = Some(ports::detect_port(vid, pid)?);
+        }
+        Ok(self)
+    }
+
+    /// Wrap the controller to re-detect its port when the device re-enumerates
+    ///
+    /// See `ResilientController`; the port is re-detected by the USB IDs
+    /// set with `with_usb_ids`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env_autodetect()?.resilient();
+    /// let modules = bjig
+    ///     .run(|c| Box::pin(async move { c.router().get_module_id(None).await }))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resilient(self) -> ResilientController {
+        ResilientController::new(self)
+    }
+
     /// Get router commands interface
     ///
     /// # Examples
@@ -164,7 +664,7 @@ impl BjigController {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn router(&self) -> RouterCommands {
+    pub fn router(&self) -> RouterCommands<'_> {
         RouterCommands::new(self)
     }
 
@@ -174,6 +674,9 @@ impl BjigController {
     /// * `sensor_id` - Sensor ID (e.g., "0121" for illuminance sensor)
     /// * `module_id` - Module ID (16-digit hex string)
     ///
+    /// Commands fail with `BjigError::InvalidParameter` if either ID starts
+    /// with `-`, as bjig would parse it as a flag.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -187,11 +690,15 @@ impl BjigController {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn module(&self, sensor_id: &str, module_id: &str) -> ModuleCommands {
+    pub fn module(&self, sensor_id: &str, module_id: &str) -> ModuleCommands<'_> {
         ModuleCommands::new(self, sensor_id, module_id)
     }
 
-    /// Get monitor command interface
+    /// Get batch commands interface for several modules of one sensor type
+    ///
+    /// # Arguments
+    /// * `sensor_id` - Sensor ID shared by all modules
+    /// * `module_ids` - Module IDs, addressed in the given order
     ///
     /// # Examples
     ///
@@ -200,27 +707,888 @@ impl BjigController {
     ///
     /// # async fn example() -> anyhow::Result<()> {
     /// let bjig = BjigController::from_env()?;
-    /// bjig.monitor().start_with_ttl(60).await?;  // Monitor for 60 seconds
+    /// let parameters = bjig.modules("0121", ["2468800203400004", "2468800203400005"])
+    ///     .get_parameters_all()
+    ///     .await;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn monitor(&self) -> MonitorCommand {
-        MonitorCommand::new(self)
+    pub fn modules<I, S>(&self, sensor_id: &str, module_ids: I) -> BatchModuleCommands<'_>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        BatchModuleCommands::new(self, sensor_id, module_ids.into_iter().map(Into::into).collect())
     }
-}
+
+    /// Get monitor command interface
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::BjigController;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let bjig = BjigController::from_env()?;
+    /// bjig.monitor().start_with_ttl(60).await?;  // Monitor for 60 seconds
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn monitor(&self) -> MonitorCommand<'_> {
+        MonitorCommand::new(self)
+    }
+
+    /// Run an arbitrary bjig subcommand and return its JSON output
+    ///
+    /// This is an escape hatch for bjig subcommands that do not have a typed
+    /// wrapper yet. The default port and baud rate are added automatically.
+    ///
+    /// No response validation is performed: the output is only parsed as
+    /// JSON and returned as-is.
+    ///
+    /// # Arguments
+    /// * `args` - Subcommand arguments (without --port and --baud)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let dump = bjig.raw_command(&["router", "debug-dump"]).await?;
+    /// println!("{}", dump);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_command(&self, args: &[&str]) -> Result<serde_json::Value> {
+        self.executor().execute_json(args, None, None).await
+    }
+
+    /// Run an arbitrary bjig subcommand without port/baud (static commands)
+    ///
+    /// Like `raw_command`, no response validation is performed.
+    ///
+    /// # Arguments
+    /// * `args` - Subcommand arguments
+    pub async fn raw_command_static(&self, args: &[&str]) -> Result<serde_json::Value> {
+        self.executor().execute_static(args).await
+    }
+
+    /// Run an arbitrary bjig subcommand and return its raw exit status and output
+    ///
+    /// Unlike `raw_command`, nothing is parsed, a non-zero exit is not an
+    /// error and the command is never retried. It still runs through the
+    /// command queue, the injected runner, metrics and command observers.
+    ///
+    /// # Arguments
+    /// * `args` - Subcommand arguments (without --port and --baud)
+    /// * `port` - Port override (uses the default port if `None`)
+    /// * `baud` - Baud override (uses the default baud if `None`)
+    ///
+    /// # Errors
+    /// Fails only if no port is configured or bjig cannot be spawned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let output = bjig.raw_output(&["router", "get-version"], None, None).await?;
+    /// println!("exit: {:?}", output.status);
+    /// println!("stdout: {}", output.stdout);
+    /// println!("stderr: {}", output.stderr);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_output(
+        &self,
+        args: &[&str],
+        port: Option<&str>,
+        baud: Option<u32>,
+    ) -> Result<CommandOutput> {
+        self.executor().execute_raw(args, port, baud).await
+    }
+
+    /// Get the bjig CLI version reported by `bjig --version`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// println!("{}", bjig.cli_version().await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cli_version(&self) -> Result<String> {
+        self.executor().execute_text_static(&["--version"]).await
+    }
+
+    /// Check the binary, serial port, and router in one call
+    ///
+    /// Runs `cli_version`, opens the default serial port (on tokio's blocking
+    /// pool), and calls `get_version`. Every check runs regardless of earlier
+    /// failures and its outcome is recorded in the report; this method never
+    /// fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let report = bjig.health_check().await;
+    /// for problem in &report.problems {
+    ///     eprintln!("{}", problem);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn health_check(&self) -> HealthReport {
+        let mut problems = Vec::new();
+
+        let binary_ok = match self.cli_version().await {
+            Ok(_) => true,
+            Err(e) => {
+                problems.push(format!("bjig binary: {}", e));
+                false
+            }
+        };
+
+        let port_ok = match self.config.default_port.as_deref() {
+            Some(port) => match tokio::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(port)
+                .await
+            {
+                Ok(_) => true,
+                Err(e) => {
+                    problems.push(format!("serial port {}: {}", port, e));
+                    false
+                }
+            },
+            None => {
+                problems.push(format!("serial port: {}", BjigError::PortNotConfigured));
+                false
+            }
+        };
+
+        let router_version = match self.router().get_version().await {
+            Ok(version) => Some(version),
+            Err(e) => {
+                problems.push(format!("router: {}", e));
+                None
+            }
+        };
+
+        HealthReport {
+            binary_ok,
+            port_ok,
+            router_version,
+            problems,
+        }
+    }
+
+    /// Stop accepting commands and wait for queued commands to finish
+    ///
+    /// Commands submitted before the call still run. After this returns,
+    /// every command fails with `BjigError::Closed`. Monitors started with a
+    /// handle are sent a stop signal; await `MonitorHandle::stop` to wait for
+    /// them to exit.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use bjig_controller::BjigController;
+    ///
+    /// let bjig = BjigController::from_env()?;
+    /// let handle = bjig.monitor().start_with_handle().await?;
+    ///
+    /// // On termination: stop new commands and let the current one finish
+    /// bjig.shutdown().await;
+    /// handle.stop().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn shutdown(&self) {
+        self.config.queue.close();
+
+        let monitors: Vec<_> = self.monitors.lock().unwrap().drain(..).collect();
+        for monitor in monitors.iter().filter_map(|m| m.upgrade()) {
+            let _ = monitor.send(ControlMessage::Stop).await;
+        }
+
+        self.config.queue.drain().await;
+        log::debug!("Controller shut down");
+    }
+
+    /// Get the number of consecutive commands that timed out
+    ///
+    /// Reset by any successful command; other failures leave it unchanged.
+    /// Streaming commands are not counted. See
+    /// `RouterCommands::start_watchdog` for automatic recovery.
+    pub fn consecutive_timeouts(&self) -> u32 {
+        self.config.timeouts.get()
+    }
+
+    /// Track a monitor so that `shutdown` can stop it
+    ///
+    /// # Errors
+    /// Returns `BjigError::Closed` if the controller was shut down.
+    pub(crate) fn register_monitor(&self, control_tx: &mpsc::Sender<ControlMessage>) -> Result<()> {
+        self.config.queue.ensure_open()?;
+
+        let mut monitors = self.monitors.lock().unwrap();
+        monitors.retain(|m| m.strong_count() > 0);
+        monitors.push(control_tx.downgrade());
+        Ok(())
+    }
+
+    /// Get command executor configured from this controller
+    pub(crate) fn executor(&self) -> CommandExecutor<'_> {
+        self.config.executor()
+    }
+
+    /// Get an owned copy of the executor configuration for background tasks
+    pub(crate) fn executor_config(&self) -> ExecutorConfig {
+        self.config.clone()
+    }
+}
+
+/// Settings every `CommandExecutor` of a controller is built from
+///
+/// Held by the controller and cloned into background tasks (see
+/// `BjigController::executor_config`). Clones share the controller's queue,
+/// so their commands are ordered with all other commands and rejected after
+/// `shutdown`.
+#[derive(Clone)]
+pub(crate) struct ExecutorConfig {
+    pub(crate) bjig_path: PathBuf,
+    pub(crate) working_dir: Option<PathBuf>,
+    pub(crate) default_port: Option<String>,
+    pub(crate) default_baud: Option<u32>,
+    pub(crate) lenient_json: bool,
+    pub(crate) normalize_fields: bool,
+    pub(crate) require_explicit_baud: bool,
+    pub(crate) allowed_bauds: Option<Vec<u32>>,
+    pub(crate) redact_logs: bool,
+    pub(crate) min_interval: Duration,
+    pub(crate) stream_buffer: StreamBufferConfig,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) runner: Option<Arc<dyn CommandRunner>>,
+    pub(crate) metrics: Option<Arc<dyn MetricsSink>>,
+    pub(crate) observer: Option<CommandObserver>,
+    pub(crate) stderr_observer: Option<StderrObserver>,
+    pub(crate) queue: Arc<CommandQueue>,
+    pub(crate) port_activity: Arc<PortActivity>,
+    pub(crate) timeouts: Arc<TimeoutCounter>,
+}
+
+impl ExecutorConfig {
+    /// Check whether a DFU or monitor currently holds the port
+    pub(crate) fn port_busy(&self) -> bool {
+        self.port_activity.is_exclusive()
+    }
+
+    /// Shared count of consecutive command timeouts
+    pub(crate) fn timeouts(&self) -> &TimeoutCounter {
+        &self.timeouts
+    }
+
+    /// Format a module ID for logs according to the redaction setting
+    pub(crate) fn log_id<'i>(&self, module_id: &'i str) -> LogId<'i> {
+        LogId {
+            id: module_id,
+            redact: self.redact_logs,
+        }
+    }
+
+    pub(crate) fn executor(&self) -> CommandExecutor<'_> {
+        CommandExecutor::new(
+            &self.bjig_path,
+            self.default_port.as_deref(),
+            self.default_baud,
+        )
+        .with_working_dir(self.working_dir.as_deref())
+        .with_lenient_json(self.lenient_json)
+        .with_field_normalization(self.normalize_fields)
+        .with_require_explicit_baud(self.require_explicit_baud)
+        .with_allowed_bauds(self.allowed_bauds.as_deref())
+        .with_log_redaction(self.redact_logs)
+        .with_min_interval(self.min_interval)
+        .with_stream_buffer(self.stream_buffer)
+        .with_retry(self.retry)
+        .with_runner(self.runner.clone())
+        .with_metrics(self.metrics.clone())
+        .with_observer(self.observer.clone())
+        .with_stderr_observer(self.stderr_observer.clone())
+        .with_port_activity(Some(Arc::clone(&self.port_activity)))
+        .with_timeout_counter(Some(Arc::clone(&self.timeouts)))
+        .with_queue(Some(&self.queue))
+    }
+}
+
+/// Builder for `BjigController`
+///
+/// Created by `BjigController::builder()`. Settings that are not given
+/// explicitly fall back to environment variables when `build()` is called:
+/// - `bin_path` - `BJIG_CLI_BIN_PATH`, then "./bin/bjig"
+/// - `port` - `BJIG_CLI_PORT`
+/// - `baud` - `BJIG_CLI_BAUD`
+/// - `module_config` - `BJIG_CLI_MODULE_CONFIG`, then "module-config.yml"
+#[derive(Debug, Clone, Default)]
+pub struct BjigControllerBuilder {
+    bin_path: Option<PathBuf>,
+    port: Option<String>,
+    baud: Option<u32>,
+    module_config: Option<PathBuf>,
+    command_timeout: Option<u64>,
+    require_explicit_baud: bool,
+    retry: Option<RetryPolicy>,
+    runner: Option<Arc<dyn CommandRunner>>,
+}
+
+impl BjigControllerBuilder {
+    /// Set bjig binary path
+    pub fn bin_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.bin_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set default serial port
+    pub fn port(mut self, port: impl Into<String>) -> Self {
+        self.port = Some(port.into());
+        self
+    }
+
+    /// Set default baud rate
+    pub fn baud(mut self, baud: u32) -> Self {
+        self.baud = Some(baud);
+        self
+    }
+
+    /// Set module config file path
+    pub fn module_config<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.module_config = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set default response timeout for module commands in seconds
+    pub fn command_timeout(mut self, timeout_secs: u64) -> Self {
+        self.command_timeout = Some(timeout_secs);
+        self
+    }
+
+    /// Require a baud rate instead of defaulting to 38400 (see `BjigController::require_explicit_baud`)
+    pub fn require_explicit_baud(mut self, require: bool) -> Self {
+        self.require_explicit_baud = require;
+        self
+    }
+
+    /// Set retry policy for failed commands
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Run non-streaming commands through `runner` (see `BjigController::with_runner`)
+    pub fn runner(mut self, runner: impl CommandRunner + 'static) -> Self {
+        self.runner = Some(Arc::new(runner));
+        self
+    }
+
+    /// Resolve environment fallbacks and build the controller
+    ///
+    /// The binary is not checked when a runner was injected.
+    ///
+    /// # Errors
+    /// Returns `BjigError::BinaryNotFound` if the binary doesn't exist
+    pub fn build(self) -> Result<BjigController> {
+        let bin_path = self.bin_path.unwrap_or_else(env::get_bjig_binary_path);
+        let mut controller = match self.runner {
+            Some(runner) => {
+                let mut controller = BjigController::with_unchecked_path(bin_path);
+                controller.config.runner = Some(runner);
+                controller
+            }
+            None => BjigController::new(bin_path)?,
+        };
+
+        controller.config.default_port = self.port.or_else(env::get_port_from_env);
+        controller.config.default_baud = self.baud.or_else(env::get_baud_from_env);
+        controller.usb_ids = env::get_usb_ids_from_env();
+        controller.module_config_path = Some(
+            self.module_config
+                .unwrap_or_else(|| PathBuf::from(env::get_module_config_from_env())),
+        );
+        if let Some(timeout) = self.command_timeout {
+            controller.response_timeout = timeout;
+        }
+        controller.config.require_explicit_baud = self.require_explicit_baud;
+        controller.config.retry = self.retry;
+
+        Ok(controller)
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::fake_bjig;
 
     #[test]
     fn test_controller_builder() {
-        let bjig = BjigController::new("./bin/bjig")
+        let bjig = BjigController::new(fake_bjig("exit 0"))
+            .unwrap()
+            .with_port("/dev/ttyACM0")
+            .with_baud(115200);
+
+        assert_eq!(bjig.config.default_port, Some("/dev/ttyACM0".to_string()));
+        assert_eq!(bjig.config.default_baud, Some(115200));
+    }
+
+    #[test]
+    fn test_autodetect_requires_usb_ids() {
+        let result = BjigController::new(fake_bjig("exit 0")).unwrap().autodetect_port();
+        assert!(matches!(result, Err(BjigError::UsbIdsNotConfigured)));
+    }
+
+    #[test]
+    fn test_autodetect_keeps_configured_port() {
+        let bjig = BjigController::new(fake_bjig("exit 0"))
+            .unwrap()
+            .with_port("/dev/ttyACM7")
+            .with_usb_ids(0xFFFF, 0xFFFF)
+            .autodetect_port()
+            .unwrap();
+        assert_eq!(bjig.config.default_port.as_deref(), Some("/dev/ttyACM7"));
+        assert_eq!(bjig.usb_ids, Some((0xFFFF, 0xFFFF)));
+    }
+
+    #[test]
+    fn test_builder() {
+        let bin = fake_bjig("exit 0");
+        let bjig = BjigController::builder()
+            .bin_path(&bin)
+            .port("/dev/ttyACM0")
+            .baud(115200)
+            .module_config("/etc/bjig/modules.yml")
+            .command_timeout(90)
+            .retry(RetryPolicy::default())
+            .build()
+            .unwrap();
+
+        assert_eq!(bjig.config.bjig_path, bin);
+        assert_eq!(bjig.config.default_port.as_deref(), Some("/dev/ttyACM0"));
+        assert_eq!(bjig.config.default_baud, Some(115200));
+        assert_eq!(bjig.module_config_path, Some(PathBuf::from("/etc/bjig/modules.yml")));
+        assert_eq!(bjig.response_timeout, 90);
+        assert_eq!(bjig.config.retry, Some(RetryPolicy::default()));
+    }
+
+    #[test]
+    fn test_builder_missing_binary() {
+        let result = BjigController::builder()
+            .bin_path("/nonexistent/bjig")
+            .build();
+        assert!(matches!(result, Err(BjigError::BinaryNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_from_transient_failure() {
+        // Fails on the first invocation only
+        let bin = fake_bjig(
+            r#"if [ ! -f "$0.failed" ]; then touch "$0.failed"; exit 1; fi
+echo '{"result":"success","message":"ok"}'"#,
+        );
+        let retry = RetryPolicy {
+            max_retries: 2,
+            delay: Duration::from_millis(10),
+        };
+
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+        assert!(bjig.router().keep_alive().await.is_err());
+
+        let _ = std::fs::remove_file(bin.with_extension("failed"));
+        let bjig = bjig.with_retry(retry);
+        assert!(bjig.router().keep_alive().await.unwrap().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_commands_with_side_effects() {
+        let mock = Arc::new(
+            crate::runner::MockRunner::new()
+                .on_failure(&["router", "set-scan-mode"], "Error: timeout")
+                .on_failure(&["router", "get-version"], "Error: timeout"),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(Arc::clone(&mock))
+            .build()
+            .unwrap()
+            .with_retry(RetryPolicy {
+                max_retries: 2,
+                delay: Duration::from_millis(1),
+            });
+
+        assert!(bjig.router().set_scan_mode(crate::types::ScanModeType::LongRange).await.is_err());
+        assert!(bjig.router().get_version().await.is_err());
+
+        let calls = mock.calls();
+        let count = |verb: &str| calls.iter().filter(|argv| argv.iter().any(|a| a == verb)).count();
+        assert_eq!(count("set-scan-mode"), 1);
+        assert_eq!(count("get-version"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_raw_command_passes_args() {
+        let bin = fake_bjig(r#"printf '{"argv":"%s"}' "$*""#);
+        let bjig = BjigController::new(bin)
             .unwrap()
             .with_port("/dev/ttyACM0")
             .with_baud(115200);
 
-        assert_eq!(bjig.default_port, Some("/dev/ttyACM0".to_string()));
-        assert_eq!(bjig.default_baud, Some(115200));
+        let json = bjig.raw_command(&["router", "debug-dump"]).await.unwrap();
+        assert_eq!(
+            json["argv"],
+            "--port /dev/ttyACM0 --baud 115200 router debug-dump"
+        );
+
+        let json = bjig.raw_command_static(&["router", "debug-dump"]).await.unwrap();
+        assert_eq!(json["argv"], "router debug-dump");
+    }
+
+    #[tokio::test]
+    async fn test_min_interval_separates_commands() {
+        use crate::runner::RunFuture;
+        use std::time::Instant;
+
+        /// Runner recording when each command started and finished
+        #[derive(Debug, Default)]
+        struct TimingRunner {
+            spans: Mutex<Vec<(Instant, Instant)>>,
+        }
+
+        impl CommandRunner for TimingRunner {
+            fn run<'a>(&'a self, _argv: &'a [String]) -> RunFuture<'a> {
+                Box::pin(async move {
+                    let started = Instant::now();
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    self.spans.lock().unwrap().push((started, Instant::now()));
+                    Ok(r#"{"result":"success","message":"ok"}"#.to_string())
+                })
+            }
+        }
+
+        let interval = Duration::from_millis(200);
+        let runner = Arc::new(TimingRunner::default());
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(Arc::clone(&runner))
+            .build()
+            .unwrap()
+            .with_min_interval(interval);
+
+        let router = bjig.router();
+        let started = Instant::now();
+        let (a, b) = tokio::join!(router.keep_alive(), router.keep_alive());
+        a.unwrap();
+        b.unwrap();
+
+        let spans = runner.spans.lock().unwrap();
+        assert_eq!(spans.len(), 2);
+        // The first command is not delayed
+        assert!(spans[0].0 - started < interval);
+        let gap = spans[1].0 - spans[0].1;
+        assert!(gap >= interval, "{:?}", gap);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_bauds() {
+        let mock = Arc::new(
+            crate::runner::MockRunner::new()
+                .on(&["router", "keep-alive"], r#"{"result":"success","message":"ok"}"#),
+        );
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .baud(115200)
+            .runner(Arc::clone(&mock))
+            .build()
+            .unwrap()
+            .with_allowed_bauds([38400, 115200]);
+        let router = bjig.router();
+
+        assert!(router.keep_alive().await.unwrap().is_success());
+        assert!(matches!(
+            router.keep_alive_on(Some("/dev/null"), Some(57600)).await,
+            Err(BjigError::InvalidParameter(_))
+        ));
+        assert_eq!(mock.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_raw_output_keeps_failure() {
+        let bin = fake_bjig(
+            r#"echo "partial $*"; echo "Serial port busy" >&2; exit 3"#,
+        );
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/ttyACM0");
+
+        let output = bjig
+            .raw_output(&["router", "start"], Some("/dev/ttyACM1"), Some(115200))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            CommandOutput {
+                status: Some(3),
+                stdout: "partial --port /dev/ttyACM1 --baud 115200 router start\n".to_string(),
+                stderr: "Serial port busy\n".to_string(),
+            }
+        );
+        assert!(!output.is_success());
+
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&records);
+        let mock = Arc::new(
+            crate::runner::MockRunner::new().on_failure(&["router", "start"], "Serial port busy"),
+        );
+        let mocked = BjigController::builder()
+            .bin_path("/nonexistent/bjig")
+            .port("/dev/null")
+            .runner(Arc::clone(&mock))
+            .build()
+            .unwrap()
+            .on_command(Arc::new(move |record: &crate::metrics::CommandRecord| {
+                sink.lock().unwrap().push(record.success)
+            }));
+        let output = mocked.raw_output(&["router", "start"], None, None).await.unwrap();
+        assert_eq!(output.status, Some(1));
+        assert_eq!(output.stderr, "Serial port busy");
+        assert_eq!(mock.calls().len(), 1);
+        assert_eq!(*records.lock().unwrap(), [false]);
+        assert!(matches!(
+            mocked.raw_output(&["router", "stop"], None, None).await,
+            Err(BjigError::CommandFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_health_check_failure_combinations() {
+        use crate::runner::MockRunner;
+
+        for binary_ok in [true, false] {
+            for port_ok in [true, false] {
+                for router_ok in [true, false] {
+                    let mut mock = MockRunner::new();
+                    mock = if binary_ok {
+                        mock.on(&["--version"], "bjig 1.0.0\n")
+                    } else {
+                        mock.on_failure(&["--version"], "not executable")
+                    };
+                    if router_ok {
+                        mock = mock.on(
+                            &["router", "get-version"],
+                            r#"{"major":1,"minor":2,"build":3,"version":"1.2.3"}"#,
+                        );
+                    }
+                    let port = if port_ok { "/dev/null" } else { "/nonexistent/ttyACM0" };
+
+                    let bjig = BjigController::builder()
+                        .port(port)
+                        .baud(38400)
+                        .runner(mock)
+                        .build()
+                        .unwrap();
+                    let report = bjig.health_check().await;
+
+                    assert_eq!(report.binary_ok, binary_ok);
+                    assert_eq!(report.port_ok, port_ok);
+                    assert_eq!(report.router_version.is_some(), router_ok);
+                    let failures = [binary_ok, port_ok, router_ok].iter().filter(|ok| !**ok).count();
+                    assert_eq!(report.problems.len(), failures, "{:?}", report.problems);
+                    assert_eq!(report.is_healthy(), failures == 0);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_without_port() {
+        let bjig = BjigController::new(fake_bjig("exit 1"))
+            .unwrap()
+            .with_runner(crate::runner::MockRunner::new().on(&["--version"], "bjig 1.0.0"));
+
+        let report = bjig.health_check().await;
+        assert!(report.binary_ok);
+        assert!(!report.port_ok);
+        assert!(report.router_version.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_in_flight_command() {
+        let bin = fake_bjig(r#"sleep 0.3; echo '{"result":"success","message":"ok"}'"#);
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let router = bjig.router();
+        let (in_flight, _) = tokio::join!(router.keep_alive(), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            bjig.shutdown().await;
+        });
+
+        assert!(in_flight.unwrap().is_success());
+        assert!(matches!(bjig.router().keep_alive().await, Err(BjigError::Closed)));
+        assert!(matches!(bjig.monitor().start().await, Err(BjigError::Closed)));
+        assert!(matches!(bjig.monitor().start_with_handle().await, Err(BjigError::Closed)));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_monitor_handles() {
+        let bin = fake_bjig("while true; do echo '{}'; sleep 0.05; done");
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let handle = bjig.monitor().start_with_handle().await.unwrap();
+        bjig.shutdown().await;
+
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while handle.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        handle.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_log_redaction_masks_module_id() {
+        crate::test_support::captured_logs();
+        let bin = fake_bjig(r#"echo '{"interval":60}'"#);
+        let bjig = BjigController::new(&bin)
+            .unwrap()
+            .with_port("/dev/null")
+            .with_log_redaction(true);
+
+        let params = bjig.module("0121", "5EC2E7A1B2C3D4F9").get_parameter().await.unwrap();
+        assert_eq!(params["interval"], 60);
+
+        let logs: Vec<String> = crate::test_support::captured_logs()
+            .into_iter()
+            .filter(|line| line.contains("5EC2") && line.starts_with("DEBUG Executing"))
+            .collect();
+        assert!(!logs.is_empty());
+        for line in &logs {
+            assert!(line.contains(r#""--module-id", "5EC2**********F9""#), "{}", line);
+            assert!(!line.contains("5EC2E7A1B2C3D4F9"), "{}", line);
+        }
+
+        // Output and log messages naming the module
+        let bin = fake_bjig(
+            r#"case "$6" in
+  restart) echo '{"result":"success","message":"ok"}' ;;
+  *) echo 'module 5EC2E7A1B2C3D4F9 not responding' ;;
+esac"#,
+        );
+        let bjig = BjigController::new(&bin)
+            .unwrap()
+            .with_port("/dev/null")
+            .with_log_redaction(true);
+        let module = bjig.module("0121", "5EC2E7A1B2C3D4F9");
+        assert!(module.get_parameter().await.is_err());
+        assert!(module
+            .restart_and_wait(Duration::from_millis(10), Duration::from_millis(30))
+            .await
+            .is_err());
+
+        let logs = crate::test_support::captured_logs();
+        assert!(logs
+            .iter()
+            .any(|line| line.contains("Failed to parse JSON output: module 5EC2**********F9")));
+        assert!(logs.iter().any(|line| line.contains("Module 5EC2**********F9 did not come back")));
+        assert!(logs.iter().all(|line| !line.contains("5EC2E7A1B2C3D4F9")));
+    }
+
+    #[test]
+    fn test_mask_module_id() {
+        use crate::executor::{mask_module_id, LogArgs, LogText};
+
+        assert_eq!(mask_module_id("2468800203400004"), "2468**********04");
+        assert_eq!(mask_module_id("ABCDEFG"), "ABCD*FG");
+        assert_eq!(mask_module_id("ABCDEF"), "******");
+
+        let text = "id 2468800203400004, sensor 0121, hash 2468800203400004ff";
+        assert_eq!(
+            LogText { text, redact: true }.to_string(),
+            "id 2468**********04, sensor 0121, hash 2468800203400004ff"
+        );
+        assert_eq!(LogText { text, redact: false }.to_string(), text);
+
+        let args = ["module", "--module-id", "2468800203400004"];
+        assert_eq!(
+            format!("{:?}", LogArgs { args: &args, redact: false }),
+            r#"["module", "--module-id", "2468800203400004"]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_working_dir_applies_to_all_spawns() {
+        let bin = fake_bjig(r#"pwd >> "$0.cwd"; echo '{"result":"success","message":"ok"}'"#);
+        let dir = crate::test_support::temp_dir();
+        let bjig = BjigController::new(&bin)
+            .unwrap()
+            .with_port("/dev/null")
+            .with_working_dir(&dir);
+
+        bjig.router().keep_alive().await.unwrap();
+        bjig.monitor()
+            .start_with_callback(|_| Ok(true))
+            .await
+            .unwrap();
+
+        let cwd = std::fs::read_to_string(bin.with_extension("cwd")).unwrap();
+        let expected = format!("{}\n", dir.canonicalize().unwrap().display());
+        assert_eq!(cwd, expected.repeat(2));
+    }
+
+    #[tokio::test]
+    async fn test_dropping_controller_keeps_monitor_running() {
+        let bin = fake_bjig("while true; do echo '{}'; sleep 0.05; done");
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let handle = bjig.monitor().start_with_handle().await.unwrap();
+        drop(bjig);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(handle.is_running());
+        handle.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_commands_run_in_submission_order() {
+        // The first command is slowest, so completion order would differ
+        // from submission order if commands were not queued
+        let bin = fake_bjig(
+            r#"n="$5"; sleep "0.$((4 - n))"; echo "$n" >> "$0.log"; echo '{}'"#,
+        );
+        let bjig = BjigController::new(&bin).unwrap().with_port("/dev/null");
+
+        let (a, b, c) = tokio::join!(
+            bjig.raw_command(&["1"]),
+            bjig.raw_command(&["2"]),
+            bjig.raw_command(&["3"]),
+        );
+        a.unwrap();
+        b.unwrap();
+        c.unwrap();
+
+        let log = std::fs::read_to_string(bin.with_extension("log")).unwrap();
+        assert_eq!(log.lines().collect::<Vec<_>>(), ["1", "2", "3"]);
     }
 }
@@ -14,12 +14,21 @@ pub const ENV_BJIG_CLI_PORT: &str = "BJIG_CLI_PORT";
 /// Environment variable for baud rate
 pub const ENV_BJIG_CLI_BAUD: &str = "BJIG_CLI_BAUD";
 
+/// Environment variable for the router's USB vendor/product ID (`VID:PID` in hex)
+pub const ENV_BJIG_CLI_USB_ID: &str = "BJIG_CLI_USB_ID";
+
 /// Environment variable for module config file path
 pub const ENV_BJIG_CLI_MODULE_CONFIG: &str = "BJIG_CLI_MODULE_CONFIG";
 
 /// Default baud rate (matches bjig_cli_rust default)
 pub const DEFAULT_BAUD: u32 = 38400;
 
+/// Standard baud rates supported by the router
+pub const STANDARD_BAUD_RATES: [u32; 5] = [9600, 19200, 38400, 57600, 115200];
+
+/// Default response timeout in seconds for module commands
+pub const DEFAULT_RESPONSE_TIMEOUT_SECS: u64 = 30;
+
 /// Default module config file name
 pub const DEFAULT_MODULE_CONFIG: &str = "module-config.yml";
 
@@ -50,6 +59,25 @@ pub fn get_baud_from_env() -> Option<u32> {
         .and_then(|s| s.parse().ok())
 }
 
+/// Get USB vendor/product ID from environment variable
+///
+/// The value is `VID:PID` in hex as printed by `lsusb` (e.g. `2fe3:0100`).
+/// Returns `None` if the variable is unset or malformed.
+pub fn get_usb_ids_from_env() -> Option<(u16, u16)> {
+    env::var(ENV_BJIG_CLI_USB_ID)
+        .ok()
+        .and_then(|s| parse_usb_ids(&s))
+}
+
+/// Parse a `VID:PID` hex pair
+fn parse_usb_ids(value: &str) -> Option<(u16, u16)> {
+    let (vid, pid) = value.trim().split_once(':')?;
+    Some((
+        u16::from_str_radix(vid, 16).ok()?,
+        u16::from_str_radix(pid, 16).ok()?,
+    ))
+}
+
 /// Get module config path from environment or default
 pub fn get_module_config_from_env() -> String {
     env::var(ENV_BJIG_CLI_MODULE_CONFIG).unwrap_or_else(|_| DEFAULT_MODULE_CONFIG.to_string())
@@ -61,14 +89,41 @@ pub fn get_module_config_from_env() -> String {
 /// * `explicit` - Explicitly provided port (highest priority)
 /// * `default` - Default port from controller (medium priority)
 ///
+/// If the port is a symlink (e.g. a udev rule's `/dev/bravejig`), the real
+/// device is logged at debug level for diagnostics; the path is only
+/// canonicalized when debug logging is enabled. The path is returned as
+/// given, so bjig still opens the symlink.
+///
 /// # Returns
 /// Port string if found, otherwise PortNotConfigured error
 pub fn resolve_port(explicit: Option<&str>, default: Option<&str>) -> Result<String> {
-    explicit
+    let port = explicit
         .map(String::from)
         .or_else(|| default.map(String::from))
         .or_else(get_port_from_env)
-        .ok_or(BjigError::PortNotConfigured)
+        .ok_or(BjigError::PortNotConfigured)?;
+
+    if log::log_enabled!(log::Level::Debug) {
+        if let Some(device) = symlink_target(&port) {
+            log::debug!("Port {} resolves to {}", port, device.display());
+        }
+    }
+    Ok(port)
+}
+
+/// Get the canonical device path of `port` if it differs from `port`
+///
+/// Returns `None` if the path cannot be canonicalized (e.g. it does not
+/// exist) or is not a symlink.
+#[cfg(unix)]
+pub(crate) fn symlink_target(port: &str) -> Option<std::path::PathBuf> {
+    let device = std::fs::canonicalize(port).ok()?;
+    (device.as_os_str() != port).then_some(device)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn symlink_target(_port: &str) -> Option<std::path::PathBuf> {
+    None
 }
 
 /// Resolve baud with priority: explicit > default > env > DEFAULT_BAUD
@@ -80,16 +135,83 @@ pub fn resolve_port(explicit: Option<&str>, default: Option<&str>) -> Result<Str
 /// # Returns
 /// Baud rate (always returns a value, using DEFAULT_BAUD as fallback)
 pub fn resolve_baud(explicit: Option<u32>, default: Option<u32>) -> u32 {
-    explicit
-        .or(default)
-        .or_else(get_baud_from_env)
-        .unwrap_or(DEFAULT_BAUD)
+    resolve_baud_with_env(explicit, default, get_baud_from_env()).unwrap_or(DEFAULT_BAUD)
+}
+
+/// Resolve baud with priority: explicit > default > env, without a fallback
+///
+/// # Arguments
+/// * `explicit` - Explicitly provided baud rate (highest priority)
+/// * `default` - Default baud rate from controller (medium priority)
+///
+/// # Returns
+/// Baud rate if found, otherwise BaudNotConfigured error
+pub fn resolve_baud_strict(explicit: Option<u32>, default: Option<u32>) -> Result<u32> {
+    resolve_baud_with_env(explicit, default, get_baud_from_env()).ok_or(BjigError::BaudNotConfigured)
+}
+
+/// Resolve baud with priority: explicit > default > `env`
+///
+/// `env` is the value read from `BJIG_CLI_BAUD`, passed in so callers can
+/// resolve against a fixed environment.
+pub(crate) fn resolve_baud_with_env(
+    explicit: Option<u32>,
+    default: Option<u32>,
+    env: Option<u32>,
+) -> Option<u32> {
+    explicit.or(default).or(env)
+}
+
+/// Check a baud rate against an allowlist
+///
+/// With `allowed` set, rates not in the list are rejected. Without it,
+/// rates outside `STANDARD_BAUD_RATES` only log a warning.
+///
+/// # Returns
+/// The baud rate if accepted, otherwise InvalidParameter error
+pub fn validate_baud(baud: u32, allowed: Option<&[u32]>) -> Result<u32> {
+    match allowed {
+        Some(allowed) if !allowed.contains(&baud) => Err(BjigError::InvalidParameter(format!(
+            "Baud rate {} not allowed (allowed: {:?})",
+            baud, allowed
+        ))),
+        Some(_) => Ok(baud),
+        None => {
+            if !STANDARD_BAUD_RATES.contains(&baud) {
+                log::warn!("Non-standard baud rate {}, bjig may fail to open the port", baud);
+            }
+            Ok(baud)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_port_keeps_symlink_path() {
+        let dir = crate::test_support::temp_dir();
+        let device = dir.join("ttyACM0");
+        std::fs::write(&device, "").unwrap();
+        let link = dir.join("bravejig");
+        std::os::unix::fs::symlink(&device, &link).unwrap();
+        let link = link.to_str().unwrap();
+
+        assert_eq!(resolve_port(Some(link), None).unwrap(), link);
+        assert_eq!(symlink_target(link), Some(std::fs::canonicalize(&device).unwrap()));
+        assert_eq!(symlink_target("/nonexistent/bravejig"), None);
+    }
+
+    #[test]
+    fn test_parse_usb_ids() {
+        assert_eq!(parse_usb_ids("2fe3:0100"), Some((0x2fe3, 0x0100)));
+        assert_eq!(parse_usb_ids(" 1915:521A\n"), Some((0x1915, 0x521a)));
+        assert_eq!(parse_usb_ids("2fe3"), None);
+        assert_eq!(parse_usb_ids("2fe3:xyz"), None);
+    }
+
     #[test]
     fn test_resolve_port_explicit() {
         let result = resolve_port(Some("/dev/ttyACM0"), Some("/dev/ttyACM1")).unwrap();
@@ -119,4 +241,27 @@ mod tests {
         let result = resolve_baud(None, None);
         assert_eq!(result, DEFAULT_BAUD);
     }
+
+    #[test]
+    fn test_resolve_baud_strict() {
+        assert_eq!(resolve_baud_strict(Some(115200), Some(9600)).unwrap(), 115200);
+        assert_eq!(resolve_baud_strict(None, Some(9600)).unwrap(), 9600);
+        assert!(matches!(
+            resolve_baud_strict(None, None),
+            Err(BjigError::BaudNotConfigured)
+        ));
+    }
+
+    #[test]
+    fn test_validate_baud() {
+        assert_eq!(validate_baud(115200, None).unwrap(), 115200);
+        assert_eq!(validate_baud(12345, None).unwrap(), 12345);
+
+        let allowed = [9600, 38400];
+        assert_eq!(validate_baud(38400, Some(&allowed)).unwrap(), 38400);
+        assert!(matches!(
+            validate_baud(115200, Some(&allowed)),
+            Err(BjigError::InvalidParameter(_))
+        ));
+    }
 }
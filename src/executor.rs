@@ -1,19 +1,353 @@
 //! Command executor for running bjig binary
 
+use std::future::Future;
 use std::path::Path;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
-use tokio::sync::mpsc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, ChildStderr, Command};
+use tokio::sync::{mpsc, oneshot};
 
 use crate::commands::monitor::ControlMessage;
-use crate::env::{resolve_baud, resolve_port};
-use crate::types::{BjigError, Result};
+use crate::controller::{RetryPolicy, StreamBufferConfig};
+use crate::env::{get_baud_from_env, resolve_baud_with_env, resolve_port, validate_baud, DEFAULT_BAUD};
+use crate::metrics::{CommandObserver, MeteredRunner, MetricsSink, ObservedRunner, StderrObserver};
+use crate::runner::{CommandRunner, ProcessRunner};
+use crate::types::{BjigError, CommandOutput, Result};
+
+/// Reason a streaming session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StreamOutcome {
+    /// Stopped by a control signal or because the control channel closed
+    Stopped,
+    /// Stopped because the callback returned `Ok(false)`
+    CallbackStopped,
+    /// Child process closed stdout and exited on its own (exit code, if any)
+    Exited(Option<i32>),
+}
+
+/// Work item processed by the command queue worker
+enum QueuedJob {
+    /// Run a command and send its output back
+    Command {
+        argv: Vec<String>,
+        stdin: Option<Vec<u8>>,
+        runner: Arc<dyn CommandRunner>,
+        /// Minimum gap after the previous command completed
+        min_interval: Duration,
+        /// Run with `CommandRunner::run_output`, keeping a non-zero exit
+        raw: bool,
+        reply: oneshot::Sender<Result<CommandOutput>>,
+    },
+    /// Signal once every previously submitted job has been processed
+    Barrier(oneshot::Sender<()>),
+    /// Hand out a `QueueSlot` and wait until it is dropped
+    Exclusive {
+        grant: oneshot::Sender<QueueSlot>,
+        /// Minimum gap after the previous command completed
+        min_interval: Duration,
+    },
+}
+
+/// Exclusive use of the command queue, released when dropped
+///
+/// Obtained from `CommandQueue::reserve`. While a slot is held, queued
+/// commands wait behind it.
+pub(crate) struct QueueSlot {
+    _release: oneshot::Sender<()>,
+}
+
+/// Per-controller FIFO queue that runs commands one at a time
+///
+/// Commands are executed by a single worker task in submission order, so
+/// commands issued from multiple tasks never interleave on the serial port.
+/// The worker is spawned on first use and exits when the queue is dropped.
+/// A command whose caller stopped waiting (e.g. timed out) is skipped, or
+/// cancelled if it is already running. A command submitted with a
+/// `min_interval` does not start until that long after the previous command
+/// completed.
+#[derive(Debug, Default)]
+pub(crate) struct CommandQueue {
+    closed: AtomicBool,
+    tx: Mutex<Option<mpsc::UnboundedSender<QueuedJob>>>,
+}
+
+impl CommandQueue {
+    /// Reject new commands with `BjigError::Closed`
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Fail with `BjigError::Closed` once the queue is closed
+    pub fn ensure_open(&self) -> Result<()> {
+        if self.is_closed() {
+            return Err(BjigError::Closed);
+        }
+        Ok(())
+    }
+
+    /// Queue a command and wait for its output
+    pub async fn submit(
+        &self,
+        runner: Arc<dyn CommandRunner>,
+        argv: Vec<String>,
+        stdin: Option<Vec<u8>>,
+        min_interval: Duration,
+    ) -> Result<String> {
+        let output = self
+            .enqueue(runner, argv, stdin, min_interval, false)
+            .await?;
+        Ok(output.stdout)
+    }
+
+    /// Queue a command and wait for its exit status and output
+    ///
+    /// Like `submit`, but a non-zero exit is returned in the output rather
+    /// than as an error (see `CommandRunner::run_output`).
+    pub async fn submit_output(
+        &self,
+        runner: Arc<dyn CommandRunner>,
+        argv: Vec<String>,
+        min_interval: Duration,
+    ) -> Result<CommandOutput> {
+        self.enqueue(runner, argv, None, min_interval, true).await
+    }
+
+    async fn enqueue(
+        &self,
+        runner: Arc<dyn CommandRunner>,
+        argv: Vec<String>,
+        stdin: Option<Vec<u8>>,
+        min_interval: Duration,
+        raw: bool,
+    ) -> Result<CommandOutput> {
+        self.ensure_open()?;
+
+        let (reply, response) = oneshot::channel();
+        self.send(QueuedJob::Command {
+            argv,
+            stdin,
+            runner,
+            min_interval,
+            raw,
+            reply,
+        })?;
+
+        response
+            .await
+            .map_err(|_| BjigError::CommandFailed("Command queue stopped".to_string()))?
+    }
+
+    /// Reserve the queue for an operation that outlives a single command
+    ///
+    /// The returned receiver yields the slot once every command submitted
+    /// before it has completed and `min_interval` has passed since the last
+    /// one. Commands submitted later wait until the slot is dropped.
+    pub fn reserve(&self, min_interval: Duration) -> Result<oneshot::Receiver<QueueSlot>> {
+        self.ensure_open()?;
+
+        let (grant, granted) = oneshot::channel();
+        self.send(QueuedJob::Exclusive { grant, min_interval })?;
+        Ok(granted)
+    }
+
+    /// Reserve the queue and wait until the slot is granted (see `reserve`)
+    pub async fn hold(&self, min_interval: Duration) -> Result<QueueSlot> {
+        self.reserve(min_interval)?
+            .await
+            .map_err(|_| BjigError::CommandFailed("Command queue stopped".to_string()))
+    }
+
+    /// Wait until every command submitted so far has completed
+    pub async fn drain(&self) {
+        let (done, finished) = oneshot::channel();
+        if self.send(QueuedJob::Barrier(done)).is_ok() {
+            let _ = finished.await;
+        }
+    }
+
+    /// Send a job to the worker, (re)spawning it if it is not running
+    fn send(&self, job: QueuedJob) -> Result<()> {
+        let mut tx = self.tx.lock().unwrap();
+        let sender = match tx.as_ref() {
+            Some(sender) if !sender.is_closed() => sender,
+            _ => {
+                let (sender, rx) = mpsc::unbounded_channel();
+                tokio::spawn(Self::worker(rx));
+                tx.insert(sender)
+            }
+        };
+
+        sender
+            .send(job)
+            .map_err(|_| BjigError::CommandFailed("Command queue stopped".to_string()))
+    }
+
+    async fn worker(mut rx: mpsc::UnboundedReceiver<QueuedJob>) {
+        let mut last_completed: Option<Instant> = None;
+
+        while let Some(job) = rx.recv().await {
+            let (argv, stdin, runner, min_interval, raw, mut reply) = match job {
+                QueuedJob::Command {
+                    argv,
+                    stdin,
+                    runner,
+                    min_interval,
+                    raw,
+                    reply,
+                } => (argv, stdin, runner, min_interval, raw, reply),
+                QueuedJob::Barrier(done) => {
+                    let _ = done.send(());
+                    continue;
+                }
+                QueuedJob::Exclusive { grant, min_interval } => {
+                    throttle(last_completed, min_interval).await;
+                    let (release, released) = oneshot::channel();
+                    if grant.send(QueueSlot { _release: release }).is_ok() {
+                        // Resolves with an error once the slot is dropped
+                        let _ = released.await;
+                        last_completed = Some(Instant::now());
+                    }
+                    continue;
+                }
+            };
+
+            throttle(last_completed, min_interval).await;
+
+            if reply.is_closed() {
+                log::debug!("Skipping cancelled command: {}", command_fields(&argv).0);
+                continue;
+            }
+
+            let run = async {
+                if raw {
+                    runner.run_output(&argv).await
+                } else {
+                    run_with(&*runner, &argv, stdin.as_deref())
+                        .await
+                        .map(|stdout| CommandOutput {
+                            status: Some(0),
+                            stdout,
+                            stderr: String::new(),
+                        })
+                }
+            };
+            let result = tokio::select! {
+                result = instrument(run, &argv) => Some(result),
+                _ = reply.closed() => None,
+            };
+            last_completed = Some(Instant::now());
+            match result {
+                Some(result) => {
+                    let _ = reply.send(result);
+                }
+                None => log::debug!("Cancelled in-flight command: {}", command_fields(&argv).0),
+            }
+        }
+    }
+}
+
+/// Wait until `min_interval` has passed since `last_completed`
+async fn throttle(last_completed: Option<Instant>, min_interval: Duration) {
+    if let Some(last) = last_completed {
+        let wait = min_interval.saturating_sub(last.elapsed());
+        if !wait.is_zero() {
+            log::debug!("Throttling command for {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Tracks operations that use the serial port for a long time (DFU, monitor)
+///
+/// Background loops such as keep-alive check `is_exclusive` and skip their
+/// work while such an operation is in flight, instead of interleaving with
+/// it on the port.
+#[derive(Debug, Default)]
+pub(crate) struct PortActivity {
+    exclusive: AtomicUsize,
+}
+
+impl PortActivity {
+    /// Mark an exclusive operation as in flight until the guard is dropped
+    pub fn begin_exclusive(self: &Arc<Self>) -> ExclusiveGuard {
+        self.exclusive.fetch_add(1, Ordering::SeqCst);
+        ExclusiveGuard(Arc::clone(self))
+    }
+
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive.load(Ordering::SeqCst) > 0
+    }
+}
+
+/// Guard returned by `PortActivity::begin_exclusive`
+pub(crate) struct ExclusiveGuard(Arc<PortActivity>);
+
+impl Drop for ExclusiveGuard {
+    fn drop(&mut self) {
+        self.0.exclusive.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Counts consecutive non-streaming commands that timed out
+///
+/// Incremented on `BjigError::Timeout` and reset by any successful command,
+/// so other failures neither add to nor break a run of timeouts. Read by
+/// the router watchdog.
+#[derive(Debug, Default)]
+pub(crate) struct TimeoutCounter {
+    consecutive: AtomicU32,
+}
+
+impl TimeoutCounter {
+    /// Update the count from a command result
+    pub fn record<T>(&self, result: &Result<T>) {
+        match result {
+            Ok(_) => self.reset(),
+            Err(BjigError::Timeout { .. }) => self.increment(),
+            Err(_) => {}
+        }
+    }
+
+    pub fn increment(&self) {
+        self.consecutive.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn get(&self) -> u32 {
+        self.consecutive.load(Ordering::SeqCst)
+    }
+
+    pub fn reset(&self) {
+        self.consecutive.store(0, Ordering::SeqCst);
+    }
+}
 
 /// Command executor that handles bjig binary execution
 pub(crate) struct CommandExecutor<'a> {
     pub bjig_path: &'a Path,
+    pub working_dir: Option<&'a Path>,
     pub default_port: Option<&'a str>,
     pub default_baud: Option<u32>,
+    pub lenient_json: bool,
+    pub normalize_fields: bool,
+    pub require_explicit_baud: bool,
+    pub allowed_bauds: Option<&'a [u32]>,
+    pub redact_logs: bool,
+    pub min_interval: Duration,
+    pub stream_buffer: StreamBufferConfig,
+    pub retry: Option<RetryPolicy>,
+    pub runner: Option<Arc<dyn CommandRunner>>,
+    pub metrics: Option<Arc<dyn MetricsSink>>,
+    pub observer: Option<CommandObserver>,
+    pub stderr_observer: Option<StderrObserver>,
+    pub port_activity: Option<Arc<PortActivity>>,
+    pub timeouts: Option<Arc<TimeoutCounter>>,
+    pub queue: Option<&'a CommandQueue>,
 }
 
 impl<'a> CommandExecutor<'a> {
@@ -25,11 +359,144 @@ impl<'a> CommandExecutor<'a> {
     ) -> Self {
         Self {
             bjig_path,
+            working_dir: None,
             default_port,
             default_baud,
+            lenient_json: false,
+            normalize_fields: false,
+            require_explicit_baud: false,
+            allowed_bauds: None,
+            redact_logs: false,
+            min_interval: Duration::ZERO,
+            stream_buffer: StreamBufferConfig::default(),
+            retry: None,
+            runner: None,
+            metrics: None,
+            observer: None,
+            stderr_observer: None,
+            port_activity: None,
+            timeouts: None,
+            queue: None,
+        }
+    }
+
+    /// Spawn bjig with `dir` as its working directory
+    pub fn with_working_dir(mut self, dir: Option<&'a Path>) -> Self {
+        self.working_dir = dir;
+        self
+    }
+
+    /// Run non-streaming commands through `queue` and honor its shutdown
+    pub fn with_queue(mut self, queue: Option<&'a CommandQueue>) -> Self {
+        self.queue = queue;
+        self
+    }
+
+    /// Run non-streaming commands through `runner` instead of spawning bjig
+    pub fn with_runner(mut self, runner: Option<Arc<dyn CommandRunner>>) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Report every non-streaming command to `sink`
+    pub fn with_metrics(mut self, sink: Option<Arc<dyn MetricsSink>>) -> Self {
+        self.metrics = sink;
+        self
+    }
+
+    /// Pass a record of every non-streaming command to `observer`
+    pub fn with_observer(mut self, observer: Option<CommandObserver>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// Pass stderr lines of streaming commands to `observer` instead of logging them
+    pub fn with_stderr_observer(mut self, observer: Option<StderrObserver>) -> Self {
+        self.stderr_observer = observer;
+        self
+    }
+
+    /// Retry failed JSON commands according to `policy`
+    pub fn with_retry(mut self, policy: Option<RetryPolicy>) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Fail with `BaudNotConfigured` instead of falling back to `DEFAULT_BAUD`
+    pub fn with_require_explicit_baud(mut self, require: bool) -> Self {
+        self.require_explicit_baud = require;
+        self
+    }
+
+    /// Reject baud rates not in `bauds` (see `validate_baud`)
+    pub fn with_allowed_bauds(mut self, bauds: Option<&'a [u32]>) -> Self {
+        self.allowed_bauds = bauds;
+        self
+    }
+
+    /// Keep at least `interval` between queued commands (streaming commands are exempt)
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Mask module IDs in logged argv (see `LogArgs`)
+    pub fn with_log_redaction(mut self, redact: bool) -> Self {
+        self.redact_logs = redact;
+        self
+    }
+
+    /// Format `args` for logging according to the redaction setting
+    fn log_args<'s, S: AsRef<str>>(&self, args: &'s [S]) -> LogArgs<'s, S> {
+        LogArgs {
+            args,
+            redact: self.redact_logs,
+        }
+    }
+
+    /// Format a module ID for logs according to the redaction setting
+    pub fn log_id<'s>(&self, module_id: &'s str) -> LogId<'s> {
+        LogId {
+            id: module_id,
+            redact: self.redact_logs,
         }
     }
 
+    /// Mark streaming commands as exclusive port operations in `activity`
+    pub fn with_port_activity(mut self, activity: Option<Arc<PortActivity>>) -> Self {
+        self.port_activity = activity;
+        self
+    }
+
+    /// Count consecutive timeouts of non-streaming commands in `counter`
+    pub fn with_timeout_counter(mut self, counter: Option<Arc<TimeoutCounter>>) -> Self {
+        self.timeouts = counter;
+        self
+    }
+
+    /// Hold the port exclusively for as long as the guard lives
+    pub fn hold_port(&self) -> Option<ExclusiveGuard> {
+        self.port_activity.as_ref().map(PortActivity::begin_exclusive)
+    }
+
+    /// Buffer streaming output according to `config`
+    pub fn with_stream_buffer(mut self, config: StreamBufferConfig) -> Self {
+        self.stream_buffer = config;
+        self
+    }
+
+    /// Parse JSON leniently in `execute_json` (see `extract_json`)
+    pub fn with_lenient_json(mut self, lenient: bool) -> Self {
+        self.lenient_json = lenient;
+        self
+    }
+
+    /// Rewrite object keys to snake_case in `execute_json` (see `normalize_keys`)
+    pub fn with_field_normalization(mut self, normalize: bool) -> Self {
+        self.normalize_fields = normalize;
+        self
+    }
+
     /// Execute bjig command and parse JSON output
     ///
     /// # Arguments
@@ -41,17 +508,157 @@ impl<'a> CommandExecutor<'a> {
         args: &[&str],
         port_override: Option<&str>,
         baud_override: Option<u32>,
+    ) -> Result<serde_json::Value> {
+        self.execute_json_impl(args, port_override, baud_override, false)
+            .await
+    }
+
+    /// Execute a status command and parse JSON output
+    ///
+    /// Like `execute_json`, but empty (or whitespace-only) stdout from a
+    /// successful command yields `serde_json::Value::Null` instead of a
+    /// parse error. Only for commands known to do this on some firmware
+    /// (`router start`, `router stop`, `router keep-alive`,
+    /// `module restart`); their typed layer maps `Null` to a success result
+    /// (see `status_from_json`).
+    pub async fn execute_status(
+        &self,
+        args: &[&str],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
+    ) -> Result<serde_json::Value> {
+        self.execute_json_impl(args, port_override, baud_override, true)
+            .await
+    }
+
+    /// Execute a status command like `execute_status`, bounded by a timeout
+    ///
+    /// The child process is killed if the timeout expires.
+    ///
+    /// # Errors
+    /// Returns `BjigError::Timeout` if the command does not complete in time.
+    pub async fn execute_status_with_timeout(
+        &self,
+        args: &[&str],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
+        timeout_secs: u64,
+    ) -> Result<serde_json::Value> {
+        tokio::time::timeout(
+            Duration::from_secs(timeout_secs),
+            self.execute_status(args, port_override, baud_override),
+        )
+        .await
+        .map_err(|_| self.timed_out(args, timeout_secs))?
+    }
+
+    async fn execute_json_impl(
+        &self,
+        args: &[&str],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
+        allow_empty: bool,
+    ) -> Result<serde_json::Value> {
+        let json = self
+            .retrying(args, || async {
+                let full_args = self.build_args(args, port_override, baud_override)?;
+                let output = self.run_command(&full_args).await?;
+
+                if allow_empty && is_empty_output(&output) {
+                    return Ok(serde_json::Value::Null);
+                }
+                if self.lenient_json {
+                    parse_json_lenient(&output, self.redact_logs)
+                } else {
+                    parse_json_strict(&output, self.redact_logs)
+                }
+            })
+            .await?;
+
+        Ok(self.normalized(json))
+    }
+
+    /// Apply key normalization if enabled
+    fn normalized(&self, json: serde_json::Value) -> serde_json::Value {
+        if self.normalize_fields {
+            normalize_keys(json)
+        } else {
+            json
+        }
+    }
+
+    /// Execute bjig command with `stdin` piped to the child and parse JSON output
+    ///
+    /// Stdin is closed after the payload is written. Output is parsed like
+    /// `execute_json`, including lenient parsing, key normalization and
+    /// retries.
+    ///
+    /// # Arguments
+    /// * `args` - Command arguments (without --port and --baud, added automatically)
+    /// * `stdin` - Bytes written to the child's stdin
+    /// * `port_override` - Optional port override
+    /// * `baud_override` - Optional baud override
+    pub async fn execute_json_with_stdin(
+        &self,
+        args: &[&str],
+        stdin: &[u8],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
     ) -> Result<serde_json::Value> {
         let full_args = self.build_args(args, port_override, baud_override)?;
-        let output = self.run_command(&full_args).await?;
 
-        // Parse JSON output
-        let json: serde_json::Value = serde_json::from_str(&output).map_err(|e| {
-            log::error!("Failed to parse JSON output: {}", output);
-            e
-        })?;
+        let json = self.retrying(args, || async {
+            let output = self.run_command_with_stdin(&full_args, Some(stdin)).await?;
+            if self.lenient_json {
+                parse_json_lenient(&output, self.redact_logs)
+            } else {
+                parse_json_strict(&output, self.redact_logs)
+            }
+        })
+        .await?;
 
-        Ok(json)
+        Ok(self.normalized(json))
+    }
+
+    /// Record an expired timeout of `args` and build its error
+    fn timed_out(&self, args: &[&str], timeout_secs: u64) -> BjigError {
+        log::error!(
+            "Command timed out after {} seconds: {:?}",
+            timeout_secs,
+            self.log_args(args)
+        );
+        // The aborted command never reached `run_command_with_stdin`'s count
+        if let Some(timeouts) = &self.timeouts {
+            timeouts.increment();
+        }
+        BjigError::Timeout { secs: timeout_secs }
+    }
+
+    /// Execute bjig command and return its exit status, stdout and stderr
+    ///
+    /// Runs like any other command (queue, runner, metrics and observer),
+    /// but without retries, and a non-zero exit is not an error.
+    ///
+    /// # Errors
+    /// Fails only if the arguments cannot be built or bjig cannot be spawned.
+    pub async fn execute_raw(
+        &self,
+        args: &[&str],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
+    ) -> Result<CommandOutput> {
+        let full_args = self.build_args(args, port_override, baud_override)?;
+        let runner = self.runner();
+
+        let result = match self.queue {
+            Some(queue) => queue.submit_output(runner, full_args, self.min_interval).await,
+            None => instrument(runner.run_output(&full_args), &full_args).await,
+        };
+
+        if let Some(timeouts) = &self.timeouts {
+            timeouts.record(&result);
+        }
+        result
     }
 
     /// Execute bjig command without port/baud (for static commands)
@@ -63,11 +670,52 @@ impl<'a> CommandExecutor<'a> {
         let output = self.run_command(&args_vec).await?;
 
         // Parse JSON output
-        let json: serde_json::Value = serde_json::from_str(&output)?;
+        let json_str = if self.lenient_json {
+            extract_json(&output).unwrap_or(&output)
+        } else {
+            &output
+        };
+        let json: serde_json::Value = serde_json::from_str(json_str)?;
 
         Ok(json)
     }
 
+    /// Execute bjig command without port/baud and parse JSON output as an array
+    ///
+    /// A top-level array is returned as-is; any other value (e.g. a single
+    /// object) is wrapped in a one-element vector.
+    pub async fn execute_static_array(&self, args: &[&str]) -> Result<Vec<serde_json::Value>> {
+        let json = self.execute_static(args).await?;
+        Ok(into_json_array(json))
+    }
+
+    /// Execute bjig command without port/baud as a JSON array, bounded by a timeout
+    ///
+    /// The child process is killed if the timeout expires.
+    ///
+    /// # Errors
+    /// Returns `BjigError::Timeout` if the command does not complete in time.
+    pub async fn execute_static_array_with_timeout(
+        &self,
+        args: &[&str],
+        timeout_secs: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        tokio::time::timeout(Duration::from_secs(timeout_secs), self.execute_static_array(args))
+            .await
+            .map_err(|_| self.timed_out(args, timeout_secs))?
+    }
+
+    /// Execute bjig command without port/baud and return stdout as text
+    ///
+    /// # Arguments
+    /// * `args` - Command arguments
+    pub async fn execute_text_static(&self, args: &[&str]) -> Result<String> {
+        let args_vec: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let output = self.run_command(&args_vec).await?;
+
+        Ok(output.trim().to_string())
+    }
+
     /// Execute bjig command and stream stdout line by line
     ///
     /// This is used for commands that produce continuous output (like monitor).
@@ -101,33 +749,50 @@ impl<'a> CommandExecutor<'a> {
         args: &[&str],
         port_override: Option<&str>,
         baud_override: Option<u32>,
-        mut callback: F,
+        callback: F,
     ) -> Result<()>
     where
         F: FnMut(&str) -> Result<bool>,
     {
+        if let Some(queue) = self.queue {
+            queue.ensure_open()?;
+        }
+
         let full_args = self.build_args(args, port_override, baud_override)?;
-        log::debug!("Executing (streaming): {:?} {:?}", self.bjig_path, full_args);
+        let _port = self.hold_port();
+        instrument(self.streaming_with_callback_impl(&full_args, callback), &full_args).await
+    }
+
+    async fn streaming_with_callback_impl<F>(&self, full_args: &[String], mut callback: F) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<bool>,
+    {
+        log::debug!("Executing (streaming): {:?} {:?}", self.bjig_path, self.log_args(full_args));
+        let started = Instant::now();
 
-        let mut child = Command::new(self.bjig_path)
-            .args(&full_args)
+        let mut child = bjig_command(self.bjig_path, self.working_dir)
+            .args(full_args)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| {
                 log::error!("Failed to spawn bjig command: {}", e);
                 e
             })?;
 
+        let stderr = child
+            .stderr
+            .take()
+            .map(|stderr| spawn_stderr_drain(stderr, self.stderr_observer.clone()));
         let mut should_continue = true;
 
         // Stream stdout
         if let Some(stdout) = child.stdout.take() {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
+            let mut lines = spawn_line_reader(stdout, self.stream_buffer);
 
-            while let Some(line) = lines.next_line().await? {
-                should_continue = callback(&line)?;
+            while let Some(line) = lines.recv().await {
+                should_continue = callback(&line?)?;
                 if !should_continue {
                     // Kill the child process
                     log::debug!("Terminating child process");
@@ -139,6 +804,7 @@ impl<'a> CommandExecutor<'a> {
 
         // Wait for process to complete
         let status = child.wait().await?;
+        trace_completion(started, status.code());
 
         // If we stopped intentionally, don't treat it as an error
         if !should_continue {
@@ -147,13 +813,9 @@ impl<'a> CommandExecutor<'a> {
         }
 
         if !status.success() {
-            let stderr = if let Some(mut stderr) = child.stderr.take() {
-                let mut buf = Vec::new();
-                use tokio::io::AsyncReadExt;
-                stderr.read_to_end(&mut buf).await?;
-                String::from_utf8_lossy(&buf).to_string()
-            } else {
-                String::new()
+            let stderr = match stderr {
+                Some(drain) => drain.await.unwrap_or_default(),
+                None => String::new(),
             };
 
             log::error!("Streaming command failed - stderr: {}", stderr);
@@ -168,6 +830,91 @@ impl<'a> CommandExecutor<'a> {
         Ok(())
     }
 
+    /// Spawn bjig command and forward its stdout lines over a channel
+    ///
+    /// Lines are read by a background task. When stdout closes and the
+    /// process exited unsuccessfully, a final `BjigError::CommandFailed` is
+    /// sent. Dropping the receiver kills the child process.
+    pub fn spawn_line_stream(
+        &self,
+        args: &[&str],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
+    ) -> Result<mpsc::Receiver<Result<String>>> {
+        if let Some(queue) = self.queue {
+            queue.ensure_open()?;
+        }
+
+        let full_args = self.build_args(args, port_override, baud_override)?;
+        log::debug!("Executing (line stream): {:?} {:?}", self.bjig_path, self.log_args(&full_args));
+
+        let child = spawn_piped(self.bjig_path, self.working_dir, &full_args)?;
+        let (tx, rx) = mpsc::channel(self.stream_buffer.max_pending_lines.max(1));
+        tokio::spawn(forward_lines(
+            child,
+            full_args,
+            self.stream_buffer,
+            self.stderr_observer.clone(),
+            self.hold_port(),
+            tx,
+        ));
+
+        Ok(rx)
+    }
+
+    /// Like `spawn_line_stream`, but holds the command queue until the stream ends
+    ///
+    /// bjig is spawned once every command queued before the stream has
+    /// completed, and commands queued meanwhile wait until the process exits
+    /// or the receiver is dropped. Failing to spawn bjig is reported as the
+    /// only item of the stream.
+    pub fn spawn_queued_line_stream(
+        &self,
+        args: &[&str],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
+    ) -> Result<mpsc::Receiver<Result<String>>> {
+        let queue = match self.queue {
+            Some(queue) => queue,
+            None => return self.spawn_line_stream(args, port_override, baud_override),
+        };
+
+        let full_args = self.build_args(args, port_override, baud_override)?;
+        let slot = queue.reserve(self.min_interval)?;
+        log::debug!("Executing (queued line stream): {:?} {:?}", self.bjig_path, self.log_args(&full_args));
+
+        let bjig_path = self.bjig_path.to_path_buf();
+        let working_dir = self.working_dir.map(Path::to_path_buf);
+        let stream_buffer = self.stream_buffer;
+        let stderr_observer = self.stderr_observer.clone();
+        let port = self.hold_port();
+        let (tx, rx) = mpsc::channel(stream_buffer.max_pending_lines.max(1));
+        tokio::spawn(async move {
+            let _slot = tokio::select! {
+                slot = slot => match slot {
+                    Ok(slot) => slot,
+                    Err(_) => {
+                        let error = BjigError::CommandFailed("Command queue stopped".to_string());
+                        let _ = tx.send(Err(error)).await;
+                        return;
+                    }
+                },
+                _ = tx.closed() => return,
+            };
+
+            match spawn_piped(&bjig_path, working_dir.as_deref(), &full_args) {
+                Ok(child) => {
+                    forward_lines(child, full_args, stream_buffer, stderr_observer, port, tx).await
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
     /// Execute bjig command and stream stdout with control messages (stop/pause/resume)
     ///
     /// This variant allows external code to control the streaming with pause/resume/stop.
@@ -178,41 +925,56 @@ impl<'a> CommandExecutor<'a> {
     /// * `port_override` - Optional port override
     /// * `baud_override` - Optional baud override
     /// * `control_rx` - Receiver for control messages
+    /// * `paused` - Pause state, updated as control messages arrive
     pub async fn execute_streaming_with_control(
         &self,
         args: &[&str],
         port_override: Option<&str>,
         baud_override: Option<u32>,
-        mut control_rx: mpsc::Receiver<ControlMessage>,
+        control_rx: mpsc::Receiver<ControlMessage>,
+        paused: &AtomicBool,
     ) -> Result<()> {
         let full_args = self.build_args(args, port_override, baud_override)?;
-        log::debug!("Executing (streaming with control): {:?} {:?}", self.bjig_path, full_args);
+        let _port = self.hold_port();
+        instrument(self.streaming_with_control_impl(&full_args, control_rx, paused), &full_args).await
+    }
+
+    async fn streaming_with_control_impl(
+        &self,
+        full_args: &[String],
+        mut control_rx: mpsc::Receiver<ControlMessage>,
+        paused: &AtomicBool,
+    ) -> Result<()> {
+        log::debug!("Executing (streaming with control): {:?} {:?}", self.bjig_path, self.log_args(full_args));
+        let started = Instant::now();
 
-        let mut child = Command::new(self.bjig_path)
-            .args(&full_args)
+        let mut child = bjig_command(self.bjig_path, self.working_dir)
+            .args(full_args)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| {
                 log::error!("Failed to spawn bjig command: {}", e);
                 e
             })?;
 
-        let mut paused = false;
+        if let Some(stderr) = child.stderr.take() {
+            spawn_stderr_drain(stderr, self.stderr_observer.clone());
+        }
         let mut stopped = false;
 
         // Stream stdout
         if let Some(stdout) = child.stdout.take() {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
+            let mut lines = spawn_line_reader(stdout, self.stream_buffer);
 
             loop {
                 tokio::select! {
                     // Line received from monitor
-                    line_result = lines.next_line() => {
-                        match line_result? {
+                    line_result = lines.recv() => {
+                        match line_result.transpose()? {
                             Some(line) => {
-                                if !paused {
+                                if !paused.load(Ordering::SeqCst) {
                                     println!("{}", line);
                                 }
                                 // If paused, data is discarded (router buffers it)
@@ -228,14 +990,8 @@ impl<'a> CommandExecutor<'a> {
                                 stopped = true;
                                 break;
                             }
-                            Some(ControlMessage::Pause) => {
-                                log::info!("Pause signal received");
-                                paused = true;
-                            }
-                            Some(ControlMessage::Resume) => {
-                                log::info!("Resume signal received");
-                                paused = false;
-                            }
+                            Some(ControlMessage::Pause) => set_paused(paused, true),
+                            Some(ControlMessage::Resume) => set_paused(paused, false),
                             None => {
                                 log::debug!("Control channel closed");
                                 break;
@@ -248,7 +1004,8 @@ impl<'a> CommandExecutor<'a> {
 
         // Kill the child process
         let _ = child.kill().await;
-        let _ = child.wait().await;
+        let status = child.wait().await;
+        trace_completion(started, status.ok().and_then(|s| s.code()));
 
         if stopped {
             log::debug!("Streaming stopped by control signal");
@@ -268,6 +1025,7 @@ impl<'a> CommandExecutor<'a> {
     /// * `baud_override` - Optional baud override
     /// * `callback` - Function called for each line. Returns Ok(true) to continue, Ok(false) to stop.
     /// * `control_rx` - Receiver for control messages
+    /// * `paused` - Pause state, updated as control messages arrive
     pub async fn execute_streaming_with_callback_and_control<F>(
         &self,
         args: &[&str],
@@ -275,44 +1033,94 @@ impl<'a> CommandExecutor<'a> {
         baud_override: Option<u32>,
         mut callback: F,
         mut control_rx: mpsc::Receiver<ControlMessage>,
+        paused: &AtomicBool,
     ) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<bool>,
+    {
+        self.execute_streaming_session(
+            args,
+            port_override,
+            baud_override,
+            &mut callback,
+            &mut control_rx,
+            paused,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Run a single streaming session with callback and control messages
+    ///
+    /// Unlike `execute_streaming_with_callback_and_control`, the control
+    /// receiver and pause state are borrowed so they can outlive the child
+    /// process (e.g. across reconnects). The returned outcome tells the caller
+    /// why the session ended.
+    pub async fn execute_streaming_session<F>(
+        &self,
+        args: &[&str],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
+        callback: &mut F,
+        control_rx: &mut mpsc::Receiver<ControlMessage>,
+        paused: &AtomicBool,
+    ) -> Result<StreamOutcome>
     where
         F: FnMut(&str) -> Result<bool>,
     {
         let full_args = self.build_args(args, port_override, baud_override)?;
-        log::debug!("Executing (streaming with callback and control): {:?} {:?}", self.bjig_path, full_args);
+        let _port = self.hold_port();
+        instrument(
+            self.streaming_session_impl(&full_args, callback, control_rx, paused),
+            &full_args,
+        )
+        .await
+    }
+
+    async fn streaming_session_impl<F>(
+        &self,
+        full_args: &[String],
+        callback: &mut F,
+        control_rx: &mut mpsc::Receiver<ControlMessage>,
+        paused: &AtomicBool,
+    ) -> Result<StreamOutcome>
+    where
+        F: FnMut(&str) -> Result<bool>,
+    {
+        log::debug!("Executing (streaming with callback and control): {:?} {:?}", self.bjig_path, self.log_args(full_args));
+        let started = Instant::now();
 
-        let mut child = Command::new(self.bjig_path)
-            .args(&full_args)
+        let mut child = bjig_command(self.bjig_path, self.working_dir)
+            .args(full_args)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| {
                 log::error!("Failed to spawn bjig command: {}", e);
                 e
             })?;
 
-        let mut should_continue = true;
-        let mut paused = false;
-        let mut stopped = false;
+        if let Some(stderr) = child.stderr.take() {
+            spawn_stderr_drain(stderr, self.stderr_observer.clone());
+        }
+        let mut outcome = None;
 
         // Stream stdout
         if let Some(stdout) = child.stdout.take() {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
+            let mut lines = spawn_line_reader(stdout, self.stream_buffer);
 
             loop {
                 tokio::select! {
                     // Line received from monitor
-                    line_result = lines.next_line() => {
-                        let line_opt: Option<String> = line_result?;
+                    line_result = lines.recv() => {
+                        let line_opt: Option<String> = line_result.transpose()?;
                         match line_opt {
                             Some(line) => {
-                                if !paused {
-                                    should_continue = callback(&line)?;
-                                    if !should_continue {
-                                        break;
-                                    }
+                                if !paused.load(Ordering::SeqCst) && !callback(&line)? {
+                                    outcome = Some(StreamOutcome::CallbackStopped);
+                                    break;
                                 }
                                 // If paused, data is discarded (router buffers it)
                             }
@@ -324,19 +1132,14 @@ impl<'a> CommandExecutor<'a> {
                         match msg {
                             Some(ControlMessage::Stop) => {
                                 log::info!("Stop signal received, terminating monitor");
-                                stopped = true;
+                                outcome = Some(StreamOutcome::Stopped);
                                 break;
                             }
-                            Some(ControlMessage::Pause) => {
-                                log::info!("Pause signal received");
-                                paused = true;
-                            }
-                            Some(ControlMessage::Resume) => {
-                                log::info!("Resume signal received");
-                                paused = false;
-                            }
+                            Some(ControlMessage::Pause) => set_paused(paused, true),
+                            Some(ControlMessage::Resume) => set_paused(paused, false),
                             None => {
                                 log::debug!("Control channel closed");
+                                outcome = Some(StreamOutcome::Stopped);
                                 break;
                             }
                         }
@@ -345,17 +1148,58 @@ impl<'a> CommandExecutor<'a> {
             }
         }
 
-        // Kill the child process
-        let _ = child.kill().await;
-        let _ = child.wait().await;
+        if let Some(outcome) = outcome {
+            // Kill the child process
+            let _ = child.kill().await;
+            let status = child.wait().await;
+            trace_completion(started, status.ok().and_then(|s| s.code()));
 
-        if stopped {
-            log::debug!("Streaming stopped by control signal");
-        } else if !should_continue {
-            log::debug!("Streaming stopped by callback");
+            if outcome == StreamOutcome::Stopped {
+                log::debug!("Streaming stopped by control signal");
+            } else {
+                log::debug!("Streaming stopped by callback");
+            }
+
+            return Ok(outcome);
         }
 
-        Ok(())
+        // Stdout closed, the child exited on its own
+        let status = child.wait().await?;
+        trace_completion(started, status.code());
+        log::debug!("Streaming child exited: {:?}", status.code());
+
+        Ok(StreamOutcome::Exited(status.code()))
+    }
+
+    /// Run an operation, retrying command failures and timeouts per the retry policy
+    ///
+    /// Only subcommands listed in `READ_ONLY_SUBCOMMANDS` are retried; any
+    /// other command runs exactly once.
+    async fn retrying<T, F, Fut>(&self, args: &[&str], mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let retry = self.retry.filter(|_| is_read_only(args));
+        let mut attempt = 0;
+        loop {
+            let error = match operation().await {
+                Err(e @ (BjigError::CommandFailed(_) | BjigError::Timeout { .. })) => e,
+                result => return result,
+            };
+
+            match retry {
+                Some(policy) if attempt < policy.max_retries => {
+                    attempt += 1;
+                    log::warn!(
+                        "Command failed ({}), retrying in {:?} (retry {}/{})",
+                        error, policy.delay, attempt, policy.max_retries
+                    );
+                    tokio::time::sleep(policy.delay).await;
+                }
+                _ => return Err(error),
+            }
+        }
     }
 
     /// Build full command arguments with port and baud
@@ -364,9 +1208,26 @@ impl<'a> CommandExecutor<'a> {
         subcommand: &[&str],
         port_override: Option<&str>,
         baud_override: Option<u32>,
+    ) -> Result<Vec<String>> {
+        self.build_args_with_env(subcommand, port_override, baud_override, get_baud_from_env())
+    }
+
+    /// Build full command arguments, with `env_baud` standing in for `BJIG_CLI_BAUD`
+    fn build_args_with_env(
+        &self,
+        subcommand: &[&str],
+        port_override: Option<&str>,
+        baud_override: Option<u32>,
+        env_baud: Option<u32>,
     ) -> Result<Vec<String>> {
         let port = resolve_port(port_override, self.default_port)?;
-        let baud = resolve_baud(baud_override, self.default_baud);
+        let baud = match resolve_baud_with_env(baud_override, self.default_baud, env_baud) {
+            Some(baud) => baud,
+            None if self.require_explicit_baud => return Err(BjigError::BaudNotConfigured),
+            None => DEFAULT_BAUD,
+        };
+        let baud = validate_baud(baud, self.allowed_bauds)?;
+        validate_id_args(subcommand)?;
 
         let mut args = vec![
             "--port".to_string(),
@@ -382,41 +1243,761 @@ impl<'a> CommandExecutor<'a> {
 
     /// Run bjig command with given arguments
     async fn run_command(&self, args: &[String]) -> Result<String> {
-        log::debug!("Executing: {:?} {:?}", self.bjig_path, args);
+        self.run_command_with_stdin(args, None).await
+    }
 
-        let output = Command::new(self.bjig_path)
-            .args(args)
-            .output()
-            .await
-            .map_err(|e| {
-                log::error!("Failed to execute bjig command: {}", e);
-                e
-            })?;
+    /// Run bjig command with given arguments and optional stdin payload
+    ///
+    /// Uses the injected runner if any, otherwise spawns the bjig binary.
+    /// With a queue, the command waits for its turn behind earlier commands.
+    async fn run_command_with_stdin(&self, args: &[String], stdin: Option<&[u8]>) -> Result<String> {
+        let runner = self.runner();
+        let result = match self.queue {
+            Some(queue) => {
+                queue
+                    .submit(
+                        runner,
+                        args.to_vec(),
+                        stdin.map(<[u8]>::to_vec),
+                        self.min_interval,
+                    )
+                    .await
+            }
+            None => instrument(run_with(&*runner, args, stdin), args).await,
+        };
+
+        if let Some(timeouts) = &self.timeouts {
+            timeouts.record(&result);
+        }
+        result
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
+    /// Get the runner for non-streaming commands, wrapped with metrics and observer
+    fn runner(&self) -> Arc<dyn CommandRunner> {
+        let mut runner: Arc<dyn CommandRunner> = match &self.runner {
+            Some(runner) => Arc::clone(runner),
+            None => Arc::new(
+                ProcessRunner::new(self.bjig_path)
+                    .with_working_dir(self.working_dir)
+                    .with_log_redaction(self.redact_logs),
+            ),
+        };
+        if let Some(sink) = &self.metrics {
+            runner = Arc::new(MeteredRunner {
+                inner: runner,
+                sink: Arc::clone(sink),
+            });
+        }
+        if let Some(observer) = &self.observer {
+            runner = Arc::new(ObservedRunner {
+                inner: runner,
+                observer: Arc::clone(observer),
+                redact: self.redact_logs,
+            });
+        }
+        runner
+    }
+}
 
-            log::error!("Command failed - stdout: {}, stderr: {}", stdout, stderr);
+/// Read lines from a child's stdout on a separate task
+///
+/// Up to `config.max_pending_lines` lines are queued ahead of the consumer.
+/// When the queue is full the reader waits, so a slow consumer applies
+/// backpressure to the child instead of losing lines. The task ends at EOF,
+/// after a read error (sent as the last item), or when the receiver is
+/// dropped.
+fn spawn_line_reader<R>(stdout: R, config: StreamBufferConfig) -> mpsc::Receiver<std::io::Result<String>>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(config.max_pending_lines.max(1));
 
-            return Err(BjigError::CommandFailed(format!(
-                "Exit code: {:?}, stderr: {}",
-                output.status.code(),
-                stderr
-            )));
+    tokio::spawn(async move {
+        let mut lines = BufReader::with_capacity(config.read_capacity.max(1), stdout).lines();
+        loop {
+            let item = match lines.next_line().await {
+                Ok(Some(line)) => Ok(line),
+                Ok(None) => return,
+                Err(e) => Err(e),
+            };
+            let failed = item.is_err();
+            if tx.send(item).await.is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    rx
+}
+
+/// Spawn bjig with stdout and stderr piped, killed when the child is dropped
+fn spawn_piped(bjig_path: &Path, working_dir: Option<&Path>, args: &[String]) -> Result<Child> {
+    let child = bjig_command(bjig_path, working_dir)
+        .args(args)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            log::error!("Failed to spawn bjig command: {}", e);
+            e
+        })?;
+    Ok(child)
+}
+
+/// Send the stdout lines of `child` to `tx` until it exits or `tx` is dropped
+///
+/// Sends a final error if the child exits unsuccessfully, and kills it when
+/// the receiver is dropped. `port` is held until the child is gone.
+async fn forward_lines(
+    mut child: Child,
+    full_args: Vec<String>,
+    stream_buffer: StreamBufferConfig,
+    stderr_observer: Option<StderrObserver>,
+    port: Option<ExclusiveGuard>,
+    tx: mpsc::Sender<Result<String>>,
+) {
+    let _port = port;
+    let started = Instant::now();
+    let stderr = child
+        .stderr
+        .take()
+        .map(|stderr| spawn_stderr_drain(stderr, stderr_observer));
+    let stdout = match child.stdout.take() {
+        Some(stdout) => stdout,
+        None => return,
+    };
+    let mut lines = BufReader::with_capacity(stream_buffer.read_capacity.max(1), stdout).lines();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = match line {
+                    Ok(Some(line)) => line,
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = tx.send(Err(e.into())).await;
+                        return;
+                    }
+                };
+                if tx.send(Ok(line)).await.is_err() {
+                    log::debug!("Line stream dropped, terminating child process");
+                    let _ = child.kill().await;
+                    return;
+                }
+            }
+            _ = tx.closed() => {
+                log::debug!("Line stream dropped, terminating child process");
+                let _ = child.kill().await;
+                return;
+            }
+        }
+    }
+
+    let status = match child.wait().await {
+        Ok(status) => status,
+        Err(e) => {
+            let _ = tx.send(Err(e.into())).await;
+            return;
+        }
+    };
+    trace_completion(started, status.code());
+
+    if !status.success() {
+        let stderr = match stderr {
+            Some(drain) => drain.await.unwrap_or_default(),
+            None => String::new(),
+        };
+        log::error!("Streaming command failed - stderr: {}", stderr);
+        let _ = tx.send(Err(classify_failure(&full_args, status.code(), &stderr))).await;
+    }
+}
+
+/// Number of trailing stderr lines kept for the error of a failed streaming command
+const STDERR_TAIL_LINES: usize = 50;
+
+/// Spawn a task that drains the stderr of a streaming command
+///
+/// Reading stderr while stdout is streamed keeps the child from blocking on
+/// a full stderr pipe. Each line is passed to `observer`, or logged as a
+/// warning without one. The task ends when stderr closes and yields the
+/// last `STDERR_TAIL_LINES` lines for error reporting.
+fn spawn_stderr_drain(
+    stderr: ChildStderr,
+    observer: Option<StderrObserver>,
+) -> tokio::task::JoinHandle<String> {
+    tokio::spawn(async move {
+        let mut segments = BufReader::new(stderr).split(b'\n');
+        let mut tail = std::collections::VecDeque::with_capacity(STDERR_TAIL_LINES);
+
+        while let Ok(Some(segment)) = segments.next_segment().await {
+            let line = String::from_utf8_lossy(&segment).trim_end_matches('\r').to_string();
+            match &observer {
+                Some(observer) => observer(&line),
+                None => log::warn!("bjig stderr: {}", line),
+            }
+            if tail.len() == STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+
+        Vec::from(tail).join("\n")
+    })
+}
+
+/// Run a command through `runner`, passing `stdin` if given
+async fn run_with(runner: &dyn CommandRunner, argv: &[String], stdin: Option<&[u8]>) -> Result<String> {
+    match stdin {
+        Some(stdin) => runner.run_with_stdin(argv, stdin).await,
+        None => runner.run(argv).await,
+    }
+}
+
+/// Create a command for the bjig binary, running in `working_dir` if given
+fn bjig_command(bjig_path: &Path, working_dir: Option<&Path>) -> Command {
+    let mut command = Command::new(bjig_path);
+    if let Some(dir) = working_dir {
+        command.current_dir(dir);
+    }
+    command
+}
+
+/// Spawn the bjig binary with given arguments and return its stdout
+pub(crate) async fn run_process(
+    bjig_path: &Path,
+    working_dir: Option<&Path>,
+    args: &[String],
+    redact_logs: bool,
+) -> Result<String> {
+    let output = run_process_output(bjig_path, working_dir, args, redact_logs).await?;
+    process_output(args, output, redact_logs)
+}
+
+/// Spawn the bjig binary with given arguments and return the finished process
+///
+/// Only fails if bjig cannot be spawned; the exit status is left to the caller.
+pub(crate) async fn run_process_output(
+    bjig_path: &Path,
+    working_dir: Option<&Path>,
+    args: &[String],
+    redact_logs: bool,
+) -> Result<std::process::Output> {
+    log::debug!("Executing: {:?} {:?}", bjig_path, LogArgs { args, redact: redact_logs });
+    let started = Instant::now();
+
+    let output = bjig_command(bjig_path, working_dir)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| {
+            log::error!("Failed to execute bjig command: {}", e);
+            e
+        })?;
+
+    trace_completion(started, output.status.code());
+    Ok(output)
+}
+
+/// Spawn the bjig binary, write `stdin` to its stdin, and return its stdout
+///
+/// Stdin is closed after the payload is written. If the child exits without
+/// reading all of it, the resulting broken pipe is ignored and the exit
+/// status decides the outcome.
+pub(crate) async fn run_process_with_stdin(
+    bjig_path: &Path,
+    working_dir: Option<&Path>,
+    args: &[String],
+    stdin: &[u8],
+    redact_logs: bool,
+) -> Result<String> {
+    use tokio::io::AsyncWriteExt;
+
+    log::debug!(
+        "Executing (stdin {} bytes): {:?} {:?}",
+        stdin.len(),
+        bjig_path,
+        LogArgs { args, redact: redact_logs }
+    );
+    let started = Instant::now();
+
+    let mut child = bjig_command(bjig_path, working_dir)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            log::error!("Failed to spawn bjig command: {}", e);
+            e
+        })?;
+
+    let mut child_stdin = child.stdin.take();
+    let write = async {
+        if let Some(pipe) = child_stdin.as_mut() {
+            pipe.write_all(stdin).await?;
+            pipe.shutdown().await?;
         }
+        // Close stdin so the child sees EOF
+        drop(child_stdin);
+        Ok::<_, std::io::Error>(())
+    };
 
-        let stdout = String::from_utf8(output.stdout)?;
-        log::debug!("Command output: {}", stdout);
+    // Write and collect output concurrently so a chatty child cannot deadlock
+    let (written, output) = tokio::join!(write, child.wait_with_output());
+    match written {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+            log::debug!("bjig exited before reading all of stdin");
+        }
+        result => result?,
+    }
+
+    let output = output?;
+    trace_completion(started, output.status.code());
+    process_output(args, output, redact_logs)
+}
+
+/// Turn a finished bjig process into its stdout or a classified error
+fn process_output(args: &[String], output: std::process::Output, redact_logs: bool) -> Result<String> {
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        log::error!(
+            "Command failed - stdout: {}, stderr: {}",
+            LogText { text: &stdout, redact: redact_logs },
+            LogText { text: &stderr, redact: redact_logs }
+        );
 
-        Ok(stdout)
+        return Err(classify_failure(args, output.status.code(), &stderr));
     }
+
+    let stdout = String::from_utf8(output.stdout)?;
+    log::debug!("Command output: {}", LogText { text: &stdout, redact: redact_logs });
+
+    Ok(stdout)
+}
+
+/// Apply a pause (`true`) or resume (`false`) control message to `paused`
+///
+/// Pausing a paused monitor or resuming a running one is a no-op.
+pub(crate) fn set_paused(paused: &AtomicBool, pause: bool) {
+    if paused.swap(pause, Ordering::SeqCst) == pause {
+        log::debug!("Monitor already {}, ignoring", if pause { "paused" } else { "resumed" });
+    } else if pause {
+        log::info!("Pause signal received");
+    } else {
+        log::info!("Resume signal received");
+    }
+}
+
+/// Parse stdout as exactly one JSON value
+fn parse_json_strict(output: &str, redact_logs: bool) -> Result<serde_json::Value> {
+    let json = serde_json::from_str(output).inspect_err(|_| {
+        log::error!("Failed to parse JSON output: {}", LogText { text: output, redact: redact_logs });
+    })?;
+    Ok(json)
+}
+
+/// Parse the first JSON value found in stdout (see `extract_json`)
+fn parse_json_lenient(output: &str, redact_logs: bool) -> Result<serde_json::Value> {
+    let json = serde_json::from_str(extract_json(output).unwrap_or(output)).inspect_err(|_| {
+        log::error!("Failed to parse JSON output: {}", LogText { text: output, redact: redact_logs });
+    })?;
+    Ok(json)
+}
+
+/// Check for empty or whitespace-only stdout of a successful command
+fn is_empty_output(output: &str) -> bool {
+    let empty = output.trim().is_empty();
+    if empty {
+        log::debug!("Command succeeded with empty output");
+    }
+    empty
+}
+
+/// Subcommands without side effects on the router or modules
+///
+/// These are the only commands retried under a `RetryPolicy`. Writes,
+/// module control, DFU, restarts and start/stop are never repeated, since
+/// a failed attempt may already have reached the device.
+const READ_ONLY_SUBCOMMANDS: [[&str; 2]; 7] = [
+    ["router", "get-version"],
+    ["router", "get-module-id"],
+    ["router", "get-scan-mode"],
+    ["router", "keep-alive"],
+    ["router", "debug-dump"],
+    ["module", "get-parameter"],
+    ["module", "instant-uplink"],
+];
+
+/// Check whether `subcommand` starts with a read-only subcommand
+fn is_read_only(subcommand: &[&str]) -> bool {
+    READ_ONLY_SUBCOMMANDS
+        .iter()
+        .any(|read_only| subcommand.starts_with(read_only))
+}
+
+/// Normalize a JSON value to an array, wrapping non-array values
+pub(crate) fn into_json_array(value: serde_json::Value) -> Vec<serde_json::Value> {
+    match value {
+        serde_json::Value::Array(values) => values,
+        other => vec![other],
+    }
+}
+
+/// Rewrite every object key in `value` to snake_case, recursively
+///
+/// `moduleId`, `ModuleId` and `moduleID` all become `module_id`; keys
+/// already in snake_case are unchanged. If a converted key collides with
+/// one already in snake_case, the latter is kept.
+pub(crate) fn normalize_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut normalized = serde_json::Map::new();
+            let mut converted = Vec::new();
+
+            for (key, value) in object {
+                let snake = to_snake_case(&key);
+                if snake == key {
+                    normalized.insert(key, normalize_keys(value));
+                } else {
+                    converted.push((key, snake, value));
+                }
+            }
+            for (key, snake, value) in converted {
+                if normalized.contains_key(&snake) {
+                    log::debug!("Dropping `{}`, `{}` is already present", key, snake);
+                } else {
+                    normalized.insert(snake, normalize_keys(value));
+                }
+            }
+
+            serde_json::Value::Object(normalized)
+        }
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(normalize_keys).collect())
+        }
+        other => other,
+    }
+}
+
+/// Convert a camelCase or PascalCase identifier to snake_case
+fn to_snake_case(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let mut snake = String::with_capacity(key.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = i.checked_sub(1).map(|j| chars[j]);
+            let next = chars.get(i + 1);
+            // Word boundary: "moduleId" or the last capital of "RSSIValue"
+            let boundary = match prev {
+                None | Some('_') => false,
+                Some(p) => p.is_lowercase() || p.is_ascii_digit() || next.is_some_and(|n| n.is_lowercase()),
+            };
+            if boundary {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+
+    snake
+}
+
+/// Extract the first balanced JSON object or array from command output
+///
+/// Heuristic: scan for the first `{` or `[`, then track nesting depth
+/// (ignoring brackets inside JSON strings) until the matching closing
+/// bracket. If that candidate is valid JSON it is returned; otherwise the
+/// scan continues from the next opening bracket. Text before and after the
+/// JSON value (log lines, whitespace) is ignored.
+///
+/// Returns `None` if no valid JSON object or array is found.
+pub(crate) fn extract_json(output: &str) -> Option<&str> {
+    let bytes = output.as_bytes();
+    let mut start = 0;
+
+    while let Some(offset) = output[start..].find(['{', '[']) {
+        let begin = start + offset;
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+
+        for (i, &b) in bytes.iter().enumerate().skip(begin) {
+            if in_string {
+                match b {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match b {
+                b'"' => in_string = true,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let candidate = &output[begin..=i];
+                        if serde_json::from_str::<serde_json::Value>(candidate).is_ok() {
+                            return Some(candidate);
+                        }
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        start = begin + 1;
+    }
+
+    None
+}
+
+/// Map a failed command to an error
+///
+/// bjig reports an expired `--response-timeout` with a non-zero exit and a
+/// "timeout" / "timed out" message on stderr. Such failures become
+/// `BjigError::Timeout` carrying the requested timeout; everything else is
+/// `BjigError::CommandFailed`.
+fn classify_failure(args: &[String], code: Option<i32>, stderr: &str) -> BjigError {
+    let stderr_lower = stderr.to_lowercase();
+    let requested_timeout = args
+        .iter()
+        .position(|a| a == "--response-timeout")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse().ok());
+
+    if let Some(secs) = requested_timeout {
+        if stderr_lower.contains("timeout") || stderr_lower.contains("timed out") {
+            return BjigError::Timeout { secs };
+        }
+    }
+
+    BjigError::CommandFailed(format!("Exit code: {:?}, stderr: {}", code, stderr))
+}
+
+/// Wrap a command future in a tracing span (no-op without the `tracing` feature)
+///
+/// The span carries the `subcommand` and `port` taken from the full argv and
+/// a `duration_ms` field recorded by `trace_completion`.
+#[cfg(feature = "tracing")]
+fn instrument<F: Future>(future: F, full_args: &[String]) -> impl Future<Output = F::Output> {
+    use tracing::Instrument;
+
+    let (subcommand, port) = command_fields(full_args);
+    let span = tracing::info_span!(
+        "bjig_command",
+        subcommand = %subcommand,
+        port = port.unwrap_or_default(),
+        duration_ms = tracing::field::Empty,
+    );
+    future.instrument(span)
+}
+
+#[cfg(not(feature = "tracing"))]
+fn instrument<F: Future>(future: F, _full_args: &[String]) -> F {
+    future
+}
+
+/// Record the duration and exit code of a finished command on the current span
+#[cfg(feature = "tracing")]
+fn trace_completion(started: Instant, exit_code: Option<i32>) {
+    let duration_ms = started.elapsed().as_millis() as u64;
+    tracing::Span::current().record("duration_ms", duration_ms);
+    tracing::info!(exit_code = ?exit_code, duration_ms, "bjig command completed");
+}
+
+#[cfg(not(feature = "tracing"))]
+fn trace_completion(_started: Instant, _exit_code: Option<i32>) {}
+
+/// Argv formatted for logs, with `--module-id` values masked if `redact` is set
+///
+/// Only affects log output; the command itself always receives the real ID.
+pub(crate) struct LogArgs<'a, S> {
+    pub args: &'a [S],
+    pub redact: bool,
+}
+
+impl<S: AsRef<str>> std::fmt::Debug for LogArgs<'_, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut list = f.debug_list();
+        let mut mask_next = false;
+        for arg in self.args {
+            let arg = arg.as_ref();
+            if mask_next {
+                list.entry(&mask_module_id(arg));
+            } else {
+                list.entry(&arg);
+            }
+            mask_next = self.redact && arg == "--module-id";
+        }
+        list.finish()
+    }
+}
+
+/// Module ID formatted for logs, masked if `redact` is set (see `mask_module_id`)
+pub(crate) struct LogId<'a> {
+    pub id: &'a str,
+    pub redact: bool,
+}
+
+impl std::fmt::Display for LogId<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.redact {
+            f.write_str(&mask_module_id(self.id))
+        } else {
+            f.write_str(self.id)
+        }
+    }
+}
+
+/// Command output formatted for logs, with module IDs masked if `redact` is set
+///
+/// Every run of exactly 16 hex digits is treated as a module ID.
+pub(crate) struct LogText<'a> {
+    pub text: &'a str,
+    pub redact: bool,
+}
+
+impl std::fmt::Display for LogText<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.redact {
+            return f.write_str(self.text);
+        }
+
+        let mut rest = self.text;
+        while let Some(start) = rest.find(|c: char| c.is_ascii_hexdigit()) {
+            let (before, from_run) = rest.split_at(start);
+            let len = from_run
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(from_run.len());
+            let (run, after) = from_run.split_at(len);
+            f.write_str(before)?;
+            if run.len() == 16 {
+                f.write_str(&mask_module_id(run))?;
+            } else {
+                f.write_str(run)?;
+            }
+            rest = after;
+        }
+        f.write_str(rest)
+    }
+}
+
+/// Copy `args` with every `--module-id` value masked (see `mask_module_id`)
+pub(crate) fn redact_argv(args: &[String]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut mask_next = false;
+    for arg in args {
+        redacted.push(if mask_next { mask_module_id(arg) } else { arg.clone() });
+        mask_next = arg == "--module-id";
+    }
+    redacted
+}
+
+/// Mask a module ID, keeping the first 4 and last 2 characters
+///
+/// IDs of 6 characters or fewer are masked completely.
+pub(crate) fn mask_module_id(module_id: &str) -> String {
+    let chars: Vec<char> = module_id.chars().collect();
+    if chars.len() <= 6 {
+        return "*".repeat(chars.len());
+    }
+
+    let mut masked: String = chars[..4].iter().collect();
+    masked.push_str(&"*".repeat(chars.len() - 6));
+    masked.extend(&chars[chars.len() - 2..]);
+    masked
+}
+
+/// Flags of the bjig CLI that are followed by a value
+const VALUE_FLAGS: [&str; 8] = [
+    "--port",
+    "--baud",
+    "--sensor-id",
+    "--module-id",
+    "--data",
+    "--file",
+    "--response-timeout",
+    "--ttl",
+];
+
+/// Split a full argv into the subcommand (flags stripped) and the port
+///
+/// Values are skipped only for flags in `VALUE_FLAGS`; any other flag is
+/// treated as a switch, so a following word stays part of the subcommand.
+pub(crate) fn command_fields(full_args: &[String]) -> (String, Option<&str>) {
+    let mut port = None;
+    let mut words = Vec::new();
+    let mut iter = full_args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--port" {
+            port = iter.next().map(|s| s.as_str());
+        } else if VALUE_FLAGS.contains(&arg.as_str()) {
+            // Skip flag value
+            iter.next();
+        } else if arg.starts_with("--") {
+            // Switch without a value
+        } else {
+            words.push(arg.as_str());
+        }
+    }
+
+    (words.join(" "), port)
+}
+
+/// Flags whose values are IDs that may come from untrusted input
+const ID_FLAGS: [&str; 2] = ["--sensor-id", "--module-id"];
+
+/// Reject ID values that bjig would parse as a flag (e.g. `--port`)
+fn validate_id_args(subcommand: &[&str]) -> Result<()> {
+    for pair in subcommand.windows(2) {
+        if ID_FLAGS.contains(&pair[0]) && pair[1].starts_with('-') {
+            return Err(BjigError::InvalidParameter(format!(
+                "{} must not start with '-': {}",
+                pair[0].trim_start_matches('-'),
+                pair[1]
+            )));
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_args_rejects_flag_like_ids() {
+        let executor = CommandExecutor::new(Path::new("/bin/bjig"), Some("/dev/ttyACM0"), Some(38400));
+
+        for args in [
+            ["module", "get-parameter", "--sensor-id", "0121", "--module-id", "--port"],
+            ["module", "get-parameter", "--sensor-id", "-1", "--module-id", "2468800203400004"],
+        ] {
+            assert!(matches!(
+                executor.build_args(&args, None, None),
+                Err(BjigError::InvalidParameter(_))
+            ));
+        }
+
+        // Other flag values, such as negative numbers in --data, are untouched
+        assert!(executor
+            .build_args(&["module", "control", "--module-id", "2468800203400004", "--data", "-1"], None, None)
+            .is_ok());
+    }
 
     #[test]
     fn test_build_args_with_overrides() {
@@ -443,6 +2024,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_args_require_explicit_baud() {
+        let lenient = CommandExecutor::new(Path::new("/bin/bjig"), Some("/dev/ttyACM0"), None);
+        let strict = CommandExecutor::new(Path::new("/bin/bjig"), Some("/dev/ttyACM0"), None)
+            .with_require_explicit_baud(true);
+        let args = ["router", "start"];
+
+        // No BJIG_CLI_BAUD
+        let built = lenient.build_args_with_env(&args, None, None, None).unwrap();
+        assert_eq!(built[3], crate::env::DEFAULT_BAUD.to_string());
+        let result = strict.build_args_with_env(&args, None, None, None);
+        assert!(matches!(result, Err(BjigError::BaudNotConfigured)));
+
+        // BJIG_CLI_BAUD counts as explicit
+        let built = strict.build_args_with_env(&args, None, None, Some(9600)).unwrap();
+        assert_eq!(built[3], "9600");
+
+        let built = strict.build_args_with_env(&args, None, Some(115200), Some(9600)).unwrap();
+        assert_eq!(built[3], "115200");
+    }
+
     #[test]
     fn test_build_args_with_defaults() {
         let executor = CommandExecutor::new(
@@ -460,4 +2062,217 @@ mod tests {
             vec!["--port", "/dev/ttyACM0", "--baud", "38400", "router", "start"]
         );
     }
+
+    #[test]
+    fn test_extract_json_with_leading_logs() {
+        let output = "[INFO] Connecting to /dev/ttyACM0\n[INFO] Connected\n{\"major\":1,\"minor\":2}\n\n  ";
+        assert_eq!(extract_json(output), Some("{\"major\":1,\"minor\":2}"));
+    }
+
+    #[test]
+    fn test_extract_json_array_and_brackets_in_strings() {
+        let output = "log [debug] start\n[{\"name\":\"a}]\\\"\"}]\ntrailer";
+        assert_eq!(extract_json(output), Some("[{\"name\":\"a}]\\\"\"}]"));
+    }
+
+    #[test]
+    fn test_extract_json_none() {
+        assert_eq!(extract_json("no json here"), None);
+        assert_eq!(extract_json("{ not json }"), None);
+    }
+
+    #[tokio::test]
+    async fn test_execute_static_array() {
+        let bin = crate::test_support::fake_bjig(
+            r#"case "$1" in
+object) echo '{"sensor_id":"0121"}' ;;
+array) echo '[{"sensor_id":"0121"},{"sensor_id":"0122"}]' ;;
+ndjson) echo '{"sensor_id":"0121"}'; echo '{"sensor_id":"0122"}' ;;
+esac"#,
+        );
+        let executor = CommandExecutor::new(&bin, None, None);
+
+        let wrapped = executor.execute_static_array(&["object"]).await.unwrap();
+        assert_eq!(wrapped, vec![json!({"sensor_id": "0121"})]);
+
+        let expected = vec![json!({"sensor_id": "0121"}), json!({"sensor_id": "0122"})];
+        assert_eq!(executor.execute_static_array(&["array"]).await.unwrap(), expected);
+
+        // Several JSON values are rejected, as by `execute_json`
+        assert!(matches!(
+            executor.execute_static_array(&["ndjson"]).await,
+            Err(BjigError::JsonParseError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_slow_callback_loses_no_lines() {
+        let bin = crate::test_support::fake_bjig(
+            "i=0; while [ $i -lt 300 ]; do echo \"line-$i-padding-padding-padding\"; i=$((i + 1)); done",
+        );
+        let executor = CommandExecutor::new(&bin, Some("/dev/null"), None).with_stream_buffer(
+            StreamBufferConfig {
+                read_capacity: 64,
+                max_pending_lines: 2,
+            },
+        );
+
+        let mut received = Vec::new();
+        executor
+            .execute_streaming_with_callback(&["monitor"], None, None, |line| {
+                if received.len() % 50 == 0 {
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                received.push(line.to_string());
+                Ok(true)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(received.len(), 300);
+        assert!(received
+            .iter()
+            .enumerate()
+            .all(|(i, line)| line.starts_with(&format!("line-{}-", i))));
+    }
+
+    #[tokio::test]
+    async fn test_zero_stream_buffer_reads_lines() {
+        let bin = crate::test_support::fake_bjig("echo first; echo second");
+        let executor = CommandExecutor::new(&bin, Some("/dev/null"), None).with_stream_buffer(
+            StreamBufferConfig {
+                read_capacity: 0,
+                max_pending_lines: 0,
+            },
+        );
+
+        let mut received = Vec::new();
+        executor
+            .execute_streaming_with_callback(&["monitor"], None, None, |line| {
+                received.push(line.to_string());
+                Ok(true)
+            })
+            .await
+            .unwrap();
+        assert_eq!(received, ["first", "second"]);
+
+        let mut lines = executor.spawn_line_stream(&["monitor"], None, None).unwrap();
+        assert_eq!(lines.recv().await.unwrap().unwrap(), "first");
+        assert_eq!(lines.recv().await.unwrap().unwrap(), "second");
+        assert!(lines.recv().await.is_none());
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        for (key, expected) in [
+            ("moduleId", "module_id"),
+            ("ModuleId", "module_id"),
+            ("sensorID", "sensor_id"),
+            ("RSSIValue", "rssi_value"),
+            ("battery2Level", "battery2_level"),
+            ("module_id", "module_id"),
+            ("lux", "lux"),
+        ] {
+            assert_eq!(to_snake_case(key), expected, "{}", key);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_json_normalizes_mixed_casing() {
+        let mock = crate::runner::MockRunner::new().on(
+            &["module", "instant-uplink"],
+            r#"{"sensorId":"0121","module_id":"2468800203400004","RSSI":-70,
+                "data":{"luxValue":120,"readings":[{"sampledAt":1}]},
+                "batteryMv":3000,"battery_mv":3100}"#,
+        );
+        let executor = CommandExecutor::new(Path::new("/bin/bjig"), Some("/dev/ttyACM0"), None)
+            .with_runner(Some(Arc::new(mock)))
+            .with_field_normalization(true);
+
+        let json = executor
+            .execute_json(&["module", "instant-uplink"], None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            json,
+            json!({
+                "sensor_id": "0121",
+                "module_id": "2468800203400004",
+                "rssi": -70,
+                "data": {"lux_value": 120, "readings": [{"sampled_at": 1}]},
+                "battery_mv": 3100
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_json_lenient() {
+        let bin = crate::test_support::fake_bjig(
+            r#"echo 'Opening port...'
+echo '{"result":"success","message":"ok"}'
+echo ''"#,
+        );
+        let executor = CommandExecutor::new(&bin, Some("/dev/null"), None);
+
+        assert!(executor.execute_json(&["router", "start"], None, None).await.is_err());
+
+        let json = executor
+            .with_lenient_json(true)
+            .execute_json(&["router", "start"], None, None)
+            .await
+            .unwrap();
+        assert_eq!(json["result"], "success");
+    }
+
+    #[test]
+    fn test_classify_timeout_failure() {
+        let args: Vec<String> = ["module", "instant-uplink", "--response-timeout", "30"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let err = classify_failure(&args, Some(1), "Error: Response timeout (no response within 30s)\n");
+        assert!(matches!(err, BjigError::Timeout { secs: 30 }));
+
+        let err = classify_failure(&args, Some(1), "Error: Serial port busy\n");
+        assert!(matches!(err, BjigError::CommandFailed(_)));
+
+        // Without a requested timeout there is nothing to report
+        let err = classify_failure(&args[..2], Some(1), "operation timed out");
+        assert!(matches!(err, BjigError::CommandFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_module_timeout_surfaces_as_timeout_error() {
+        let bin = crate::test_support::fake_bjig("echo 'Error: Response timed out' >&2\nexit 2");
+        let bjig = crate::BjigController::new(bin).unwrap().with_port("/dev/null");
+
+        let result = bjig
+            .module("0121", "2468800203400004")
+            .instant_uplink_with_timeout(7)
+            .await;
+        assert!(matches!(result, Err(BjigError::Timeout { secs: 7 })));
+    }
+
+    #[test]
+    fn test_command_fields() {
+        let args: Vec<String> = [
+            "--port", "/dev/ttyACM0", "--baud", "38400",
+            "module", "get-parameter", "--sensor-id", "0121",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let (subcommand, port) = command_fields(&args);
+        assert_eq!(subcommand, "module get-parameter");
+        assert_eq!(port, Some("/dev/ttyACM0"));
+
+        let args: Vec<String> = ["--verbose", "router", "debug-dump", "--ttl", "30"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(command_fields(&args), ("router debug-dump".to_string(), None));
+    }
 }
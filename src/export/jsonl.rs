@@ -0,0 +1,163 @@
+//! JSON-lines export of monitor uplinks
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::MonitorCommand;
+use crate::types::{MonitorEvent, Result};
+
+/// Counts reported by `MonitorCollector` on completion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectorStats {
+    /// Uplinks written to the output
+    pub written: usize,
+    /// Lines skipped because they were not uplink JSON
+    pub skipped: usize,
+}
+
+/// Collects monitor uplinks and writes them as JSON lines
+///
+/// Each uplink is written as a single compact JSON object followed by a
+/// newline. Lines that are not uplink JSON are skipped and counted. The
+/// collector stops after `max_items` uplinks or when the TTL expires,
+/// whichever comes first.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::BjigController;
+/// use bjig_controller::export::MonitorCollector;
+/// use std::fs::File;
+///
+/// let bjig = BjigController::from_env()?;
+/// let file = File::create("uplinks.jsonl")?;
+///
+/// let stats = MonitorCollector::new()
+///     .with_ttl(120)
+///     .start(&bjig.monitor(), 100, file)
+///     .await?;
+/// println!("Wrote {} uplinks, skipped {} lines", stats.written, stats.skipped);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MonitorCollector {
+    ttl_secs: Option<u64>,
+    flush_every: usize,
+}
+
+impl Default for MonitorCollector {
+    fn default() -> Self {
+        Self {
+            ttl_secs: None,
+            flush_every: 10,
+        }
+    }
+}
+
+impl MonitorCollector {
+    /// Create collector without TTL, flushing every 10 items
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop collecting after the given number of seconds
+    pub fn with_ttl(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = Some(ttl_secs);
+        self
+    }
+
+    /// Flush the output every `items` written uplinks (minimum 1)
+    pub fn with_flush_interval(mut self, items: usize) -> Self {
+        self.flush_every = items.max(1);
+        self
+    }
+
+    /// Run the monitor and write uplinks to `output`
+    ///
+    /// # Arguments
+    /// * `monitor` - Monitor command interface to collect from
+    /// * `max_items` - Stop after this many uplinks have been written
+    /// * `output` - Destination for the JSON lines
+    pub async fn start<W: Write>(
+        &self,
+        monitor: &MonitorCommand<'_>,
+        max_items: usize,
+        mut output: W,
+    ) -> Result<CollectorStats> {
+        let mut stats = CollectorStats::default();
+        if max_items == 0 {
+            return Ok(stats);
+        }
+
+        let callback = |line: &str| {
+            let uplink = match MonitorEvent::parse(line) {
+                MonitorEvent::Uplink(uplink) => uplink,
+                _ => {
+                    log::debug!("Skipping non-uplink monitor line: {}", line);
+                    stats.skipped += 1;
+                    return Ok(true);
+                }
+            };
+
+            serde_json::to_writer(&mut output, &uplink)?;
+            output.write_all(b"\n")?;
+            stats.written += 1;
+
+            if stats.written % self.flush_every == 0 {
+                output.flush()?;
+            }
+
+            Ok(stats.written < max_items)
+        };
+
+        let monitored = match self.ttl_secs {
+            Some(ttl) => monitor.start_with_ttl_and_callback(ttl, callback).await,
+            None => monitor.start_with_callback(callback).await,
+        };
+        if stats.skipped > 0 {
+            log::warn!("Skipped {} non-uplink monitor lines", stats.skipped);
+        }
+        monitored?;
+
+        output.flush()?;
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_bjig;
+    use crate::BjigController;
+
+    #[tokio::test]
+    async fn test_collect_jsonl() {
+        let bin = fake_bjig(
+            r#"echo '{"sensor_id":"0121","module_id":"A","lux":1}'
+echo 'starting monitor'
+echo '{"sensor_id":"0121","module_id":"B","lux":2}'
+echo '{"sensor_id":"0121","module_id":"C","lux":3}'"#,
+        );
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+
+        let mut output = Vec::new();
+        let stats = MonitorCollector::new()
+            .start(&bjig.monitor(), 2, &mut output)
+            .await
+            .unwrap();
+
+        assert_eq!(stats, CollectorStats { written: 2, skipped: 1 });
+
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["module_id"], "A");
+        assert_eq!(lines[1]["lux"], 2);
+    }
+}
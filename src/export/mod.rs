@@ -0,0 +1,13 @@
+//! Export helpers for collected monitor data
+
+pub mod csv_export;
+pub mod jsonl;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
+pub mod tee;
+
+pub use csv_export::CsvExporter;
+pub use jsonl::{CollectorStats, MonitorCollector};
+#[cfg(feature = "parquet")]
+pub use parquet_export::{ParquetColumn, ParquetColumnType, ParquetSink};
+pub use tee::{TeeCompression, TeeLog};
@@ -0,0 +1,332 @@
+//! Parquet export of monitor uplinks
+
+use std::io::Write;
+use std::sync::Arc;
+
+use parquet::basic::{ConvertedType, Repetition, Type as PhysicalType};
+use parquet::column::writer::ColumnWriter;
+use parquet::data_type::ByteArray;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type;
+use serde_json::Value;
+
+use crate::types::{MonitorEvent, Result};
+
+/// Value type of a Parquet column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParquetColumnType {
+    /// 64-bit integer
+    Int64,
+    /// 64-bit float
+    Double,
+    /// UTF-8 string
+    Utf8,
+    /// Boolean
+    Boolean,
+}
+
+/// Parquet column mapped from a JSON pointer into the uplink
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParquetColumn {
+    /// Column name in the Parquet schema
+    pub name: String,
+    /// JSON pointer (RFC 6901) evaluated against the uplink JSON object
+    pub pointer: String,
+    pub kind: ParquetColumnType,
+}
+
+impl ParquetColumn {
+    /// Create column `name` holding the value at `pointer`
+    pub fn new(name: impl Into<String>, pointer: impl Into<String>, kind: ParquetColumnType) -> Self {
+        Self {
+            name: name.into(),
+            pointer: pointer.into(),
+            kind,
+        }
+    }
+}
+
+/// Buffered values of one column (`None` is written as null)
+#[derive(Debug, Clone)]
+enum ColumnBuffer {
+    Int64(Vec<Option<i64>>),
+    Double(Vec<Option<f64>>),
+    Utf8(Vec<Option<String>>),
+    Boolean(Vec<Option<bool>>),
+}
+
+impl ColumnBuffer {
+    fn new(kind: ParquetColumnType) -> Self {
+        match kind {
+            ParquetColumnType::Int64 => Self::Int64(Vec::new()),
+            ParquetColumnType::Double => Self::Double(Vec::new()),
+            ParquetColumnType::Utf8 => Self::Utf8(Vec::new()),
+            ParquetColumnType::Boolean => Self::Boolean(Vec::new()),
+        }
+    }
+
+    /// Append `value` coerced to the column type
+    fn push(&mut self, value: Option<&Value>) {
+        let value = value.filter(|v| !v.is_null());
+        match self {
+            Self::Int64(values) => values.push(value.and_then(|v| match v {
+                Value::String(s) => s.trim().parse().ok(),
+                v => v.as_i64(),
+            })),
+            Self::Double(values) => values.push(value.and_then(|v| match v {
+                Value::String(s) => s.trim().parse().ok(),
+                v => v.as_f64(),
+            })),
+            Self::Utf8(values) => values.push(value.map(|v| match v {
+                Value::String(s) => s.clone(),
+                v => v.to_string(),
+            })),
+            Self::Boolean(values) => values.push(value.and_then(|v| match v {
+                Value::String(s) => s.trim().parse().ok(),
+                v => v.as_bool(),
+            })),
+        }
+    }
+
+    fn clear(&mut self) {
+        match self {
+            Self::Int64(values) => values.clear(),
+            Self::Double(values) => values.clear(),
+            Self::Utf8(values) => values.clear(),
+            Self::Boolean(values) => values.clear(),
+        }
+    }
+
+    /// Write the buffered values to a column chunk
+    fn write(&self, writer: &mut ColumnWriter<'_>) -> Result<()> {
+        match (self, writer) {
+            (Self::Int64(values), ColumnWriter::Int64ColumnWriter(w)) => {
+                let (data, defs) = split_nulls(values, |v| *v);
+                w.write_batch(&data, Some(&defs), None)?;
+            }
+            (Self::Double(values), ColumnWriter::DoubleColumnWriter(w)) => {
+                let (data, defs) = split_nulls(values, |v| *v);
+                w.write_batch(&data, Some(&defs), None)?;
+            }
+            (Self::Utf8(values), ColumnWriter::ByteArrayColumnWriter(w)) => {
+                let (data, defs) = split_nulls(values, |v| ByteArray::from(v.as_str()));
+                w.write_batch(&data, Some(&defs), None)?;
+            }
+            (Self::Boolean(values), ColumnWriter::BoolColumnWriter(w)) => {
+                let (data, defs) = split_nulls(values, |v| *v);
+                w.write_batch(&data, Some(&defs), None)?;
+            }
+            _ => unreachable!("column writer type follows the schema"),
+        }
+        Ok(())
+    }
+}
+
+/// Split optional values into non-null data and definition levels
+fn split_nulls<T, U>(values: &[Option<T>], convert: impl Fn(&T) -> U) -> (Vec<U>, Vec<i16>) {
+    let data = values.iter().flatten().map(convert).collect();
+    let defs = values.iter().map(|v| i16::from(v.is_some())).collect();
+    (data, defs)
+}
+
+/// Buffers monitor uplinks and writes them as a Parquet file
+///
+/// The schema is fixed by the column spec given up front. All columns are
+/// optional: fields missing from an uplink, or that cannot be coerced to the
+/// column type, are written as null. Numeric strings are accepted for
+/// numeric columns, and non-string values are written as compact JSON to
+/// `Utf8` columns. All rows are written as a single row group.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::BjigController;
+/// use bjig_controller::export::{ParquetColumn, ParquetColumnType, ParquetSink};
+///
+/// let bjig = BjigController::from_env()?;
+/// let mut sink = ParquetSink::new([
+///     ParquetColumn::new("module_id", "/module_id", ParquetColumnType::Utf8),
+///     ParquetColumn::new("temperature", "/data/temperature", ParquetColumnType::Double),
+///     ParquetColumn::new("rssi", "/rssi", ParquetColumnType::Int64),
+/// ]);
+/// let file = std::fs::File::create("uplinks.parquet")?;
+///
+/// let rows = bjig.monitor()
+///     .start_with_parquet_export(&mut sink, file, 100)
+///     .await?;
+/// println!("Wrote {} rows", rows);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParquetSink {
+    columns: Vec<ParquetColumn>,
+    buffers: Vec<ColumnBuffer>,
+    rows: usize,
+}
+
+impl ParquetSink {
+    /// Create sink with the given column spec
+    pub fn new(columns: impl IntoIterator<Item = ParquetColumn>) -> Self {
+        let columns: Vec<ParquetColumn> = columns.into_iter().collect();
+        let buffers = columns.iter().map(|c| ColumnBuffer::new(c.kind)).collect();
+        Self {
+            columns,
+            buffers,
+            rows: 0,
+        }
+    }
+
+    /// Column spec
+    pub fn columns(&self) -> &[ParquetColumn] {
+        &self.columns
+    }
+
+    /// Number of buffered rows
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns `true` if no rows are buffered
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Buffer a row for the event
+    ///
+    /// Returns `false` without buffering if the event is not an uplink.
+    pub fn record(&mut self, event: &MonitorEvent) -> Result<bool> {
+        let uplink = match event.as_uplink() {
+            Some(uplink) => serde_json::to_value(uplink)?,
+            None => return Ok(false),
+        };
+
+        for (column, buffer) in self.columns.iter().zip(&mut self.buffers) {
+            buffer.push(uplink.pointer(&column.pointer));
+        }
+        self.rows += 1;
+        Ok(true)
+    }
+
+    /// Write the buffered rows as a Parquet file and clear the buffer
+    pub fn write<W: Write + Send>(&mut self, writer: W) -> Result<()> {
+        let mut file = SerializedFileWriter::new(
+            writer,
+            Arc::new(self.schema()?),
+            Arc::new(WriterProperties::builder().build()),
+        )?;
+
+        let mut row_group = file.next_row_group()?;
+        for buffer in &self.buffers {
+            if let Some(mut column) = row_group.next_column()? {
+                buffer.write(column.untyped())?;
+                column.close()?;
+            }
+        }
+        row_group.close()?;
+        file.close()?;
+
+        self.buffers.iter_mut().for_each(ColumnBuffer::clear);
+        self.rows = 0;
+        Ok(())
+    }
+
+    /// Build the Parquet schema from the column spec
+    fn schema(&self) -> Result<Type> {
+        let fields = self
+            .columns
+            .iter()
+            .map(|column| {
+                let (physical, converted) = match column.kind {
+                    ParquetColumnType::Int64 => (PhysicalType::INT64, ConvertedType::NONE),
+                    ParquetColumnType::Double => (PhysicalType::DOUBLE, ConvertedType::NONE),
+                    ParquetColumnType::Utf8 => (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8),
+                    ParquetColumnType::Boolean => (PhysicalType::BOOLEAN, ConvertedType::NONE),
+                };
+                Type::primitive_type_builder(&column.name, physical)
+                    .with_repetition(Repetition::OPTIONAL)
+                    .with_converted_type(converted)
+                    .build()
+                    .map(Arc::new)
+            })
+            .collect::<parquet::errors::Result<Vec<_>>>()?;
+
+        Ok(Type::group_type_builder("uplink")
+            .with_fields(fields)
+            .build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fake_bjig, temp_dir};
+    use crate::{BjigController, BjigError};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    #[tokio::test]
+    async fn test_parquet_export_round_trip() {
+        let bin = fake_bjig(
+            r#"echo '{"sensor_id":"0122","module_id":"A","rssi":-70,"data":{"temperature":21.5}}'
+echo 'router ready'
+echo '{"sensor_id":"0122","module_id":"B","rssi":"-65","data":{"humidity":40}}'"#,
+        );
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        let mut sink = ParquetSink::new([
+            ParquetColumn::new("module_id", "/module_id", ParquetColumnType::Utf8),
+            ParquetColumn::new("temperature", "/data/temperature", ParquetColumnType::Double),
+            ParquetColumn::new("rssi", "/rssi", ParquetColumnType::Int64),
+        ]);
+        let path = temp_dir().join("uplinks.parquet");
+
+        let rows = bjig
+            .monitor()
+            .start_with_parquet_export(&mut sink, std::fs::File::create(&path).unwrap(), 10)
+            .await
+            .unwrap();
+        assert_eq!(rows, 2);
+        assert!(sink.is_empty());
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let rows: Vec<Vec<Field>> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().get_column_iter().map(|(_, f)| f.clone()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec![Field::Str("A".to_string()), Field::Double(21.5), Field::Long(-70)],
+                vec![Field::Str("B".to_string()), Field::Null, Field::Long(-65)],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parquet_export_keeps_rows_on_monitor_failure() {
+        let bin = fake_bjig(
+            r#"echo '{"sensor_id":"0122","module_id":"A","rssi":-70}'
+echo 'Error: device disconnected' >&2
+exit 1"#,
+        );
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        let mut sink = ParquetSink::new([ParquetColumn::new(
+            "module_id",
+            "/module_id",
+            ParquetColumnType::Utf8,
+        )]);
+        let path = temp_dir().join("uplinks.parquet");
+
+        let result = bjig
+            .monitor()
+            .start_with_parquet_export(&mut sink, std::fs::File::create(&path).unwrap(), 10)
+            .await;
+        assert!(matches!(result, Err(BjigError::CommandFailed(_))), "{:?}", result);
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 1);
+    }
+}
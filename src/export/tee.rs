@@ -0,0 +1,149 @@
+//! Raw monitor output logs ("tee" files)
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+
+use crate::types::Result;
+
+/// Compression applied to a tee log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TeeCompression {
+    /// Plain text
+    #[default]
+    None,
+    /// gzip
+    Gzip,
+}
+
+impl TeeCompression {
+    /// Pick compression from the file extension (`.gz` is gzip)
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => TeeCompression::Gzip,
+            _ => TeeCompression::None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum TeeOutput {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<File>),
+}
+
+/// File receiving every raw monitor line
+///
+/// Each line is flushed to the file as soon as it is written. For gzip logs
+/// this is a sync flush, so if the process is killed everything written so
+/// far can still be decompressed (`zcat` reports a missing trailer, but
+/// outputs all lines). `finish` completes the gzip stream.
+#[derive(Debug)]
+pub struct TeeLog {
+    output: TeeOutput,
+}
+
+impl TeeLog {
+    /// Create (or truncate) the log at `path`, compressing if it ends in `.gz`
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let compression = TeeCompression::from_path(&path);
+        Self::create_with(path, compression)
+    }
+
+    /// Create (or truncate) the log at `path` with the given compression
+    pub fn create_with<P: AsRef<Path>>(path: P, compression: TeeCompression) -> Result<Self> {
+        let file = File::create(path)?;
+        let output = match compression {
+            TeeCompression::None => TeeOutput::Plain(BufWriter::new(file)),
+            TeeCompression::Gzip => TeeOutput::Gzip(GzEncoder::new(file, flate2::Compression::default())),
+        };
+        Ok(Self { output })
+    }
+
+    /// Append `line` and a newline, then flush
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        let writer: &mut dyn Write = match &mut self.output {
+            TeeOutput::Plain(w) => w,
+            TeeOutput::Gzip(w) => w,
+        };
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Flush remaining data and complete the gzip stream
+    pub fn finish(self) -> Result<()> {
+        match self.output {
+            TeeOutput::Plain(mut w) => w.flush()?,
+            TeeOutput::Gzip(w) => {
+                w.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fake_bjig, temp_dir};
+    use crate::BjigController;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    const LINES: &str = r#"echo '{"sensor_id":"0121","module_id":"A","lux":1}'
+echo 'router ready'
+echo '{"sensor_id":"0121","module_id":"B","lux":2}'"#;
+
+    #[test]
+    fn test_compression_from_path() {
+        assert_eq!(TeeCompression::from_path("monitor.log.gz"), TeeCompression::Gzip);
+        assert_eq!(TeeCompression::from_path("monitor.GZ"), TeeCompression::Gzip);
+        assert_eq!(TeeCompression::from_path("monitor.log"), TeeCompression::None);
+        assert_eq!(TeeCompression::from_path("gz"), TeeCompression::None);
+    }
+
+    #[tokio::test]
+    async fn test_tee_gzip_round_trip() {
+        let bjig = BjigController::new(fake_bjig(LINES)).unwrap().with_port("/dev/null");
+        let path = temp_dir().join("monitor.log.gz");
+
+        let mut seen = 0;
+        bjig.monitor()
+            .start_with_tee(&path, |_| {
+                seen += 1;
+                Ok(true)
+            })
+            .await
+            .unwrap();
+        assert_eq!(seen, 3);
+
+        let mut text = String::new();
+        GzDecoder::new(File::open(&path).unwrap())
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(
+            text,
+            "{\"sensor_id\":\"0121\",\"module_id\":\"A\",\"lux\":1}\nrouter ready\n\
+             {\"sensor_id\":\"0121\",\"module_id\":\"B\",\"lux\":2}\n"
+        );
+    }
+
+    #[test]
+    fn test_gzip_lines_readable_before_finish() {
+        let path = temp_dir().join("monitor.log.gz");
+        let mut log = TeeLog::create(&path).unwrap();
+        log.write_line("first").unwrap();
+        log.write_line("second").unwrap();
+
+        // Simulate a killed process: the stream has no trailer yet
+        let mut text = Vec::new();
+        let _ = GzDecoder::new(File::open(&path).unwrap()).read_to_end(&mut text);
+        assert_eq!(text, b"first\nsecond\n");
+
+        log.finish().unwrap();
+    }
+}
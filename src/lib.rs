@@ -48,20 +48,46 @@
 //!
 //! See the `examples/` directory for more usage examples.
 
+pub mod aggregate;
+pub mod api;
 pub mod controller;
 pub mod commands;
 pub mod env;
 pub mod executor;
+pub mod export;
+pub mod metrics;
+pub mod multi_monitor;
+pub mod ports;
+pub mod registry;
+pub mod resilient;
+pub mod routers;
+pub mod runner;
 pub mod types;
+#[cfg(feature = "base64")]
+pub mod util;
+
+#[cfg(test)]
+pub(crate) mod test_support;
 
 // Re-export main types
-pub use controller::BjigController;
-pub use commands::monitor::MonitorHandle;
+pub use aggregate::{IntervalEstimator, UplinkAggregator, UplinkStats};
+pub use api::{ApiFuture, ModuleApi, RouterApi};
+pub use controller::{BjigController, BjigControllerBuilder, RetryPolicy, StreamBufferConfig};
+pub use commands::batch::BatchResult;
+pub use commands::module::DfuTask;
+pub use commands::router::{KeepAliveTask, WatchdogPolicy, WatchdogTask};
+pub use commands::monitor::{MonitorDedup, MonitorFilter, MonitorHandle, ReconnectPolicy};
+pub use multi_monitor::{MultiMonitor, TaggedMonitorEvent};
+pub use metrics::{CommandObserver, CommandRecord, MetricsSink, NoopMetrics, StderrObserver};
+pub use registry::{ModuleRegistry, ModuleState, ModuleStatus};
+pub use resilient::{PortDetector, ResilientController};
+pub use routers::BjigRouters;
+pub use runner::{CommandRunner, MockRunner, ProcessRunner};
 pub use types::*;
 
 // Re-export environment constants for user reference
 pub use env::{
     ENV_BJIG_CLI_BIN_PATH, ENV_BJIG_CLI_PORT, ENV_BJIG_CLI_BAUD,
-    ENV_BJIG_CLI_MODULE_CONFIG, DEFAULT_BAUD, DEFAULT_MODULE_CONFIG,
-    DEFAULT_BJIG_BINARY,
+    ENV_BJIG_CLI_MODULE_CONFIG, ENV_BJIG_CLI_USB_ID, DEFAULT_BAUD, DEFAULT_MODULE_CONFIG,
+    DEFAULT_BJIG_BINARY, DEFAULT_RESPONSE_TIMEOUT_SECS, STANDARD_BAUD_RATES,
 };
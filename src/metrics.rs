@@ -0,0 +1,246 @@
+//! Metrics hooks for command execution
+//!
+//! Implement `MetricsSink` to forward per-command counts and latency to a
+//! metrics library of your choice and register it with
+//! `BjigController::with_metrics`. For the full argv of every command (e.g.
+//! for an audit log), register a `CommandObserver` with
+//! `BjigController::on_command`. Diagnostics written to stderr by streaming
+//! commands go to a `StderrObserver` registered with
+//! `BjigController::on_stderr`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::executor::{command_fields, redact_argv};
+use crate::runner::{CommandRunner, OutputFuture, RunFuture};
+use crate::types::{CommandOutput, Result};
+
+/// Receives one measurement per executed non-streaming command
+pub trait MetricsSink: Send + Sync {
+    /// Record a finished command
+    ///
+    /// # Arguments
+    /// * `subcommand` - bjig subcommand without flags (e.g. `"router keep-alive"`)
+    /// * `duration` - Execution time, excluding time spent waiting in the command queue
+    /// * `success` - Whether the command exited successfully
+    fn record(&self, subcommand: &str, duration: Duration, success: bool);
+}
+
+impl<S: MetricsSink + ?Sized> MetricsSink for Arc<S> {
+    fn record(&self, subcommand: &str, duration: Duration, success: bool) {
+        (**self).record(subcommand, duration, success)
+    }
+}
+
+/// Sink that discards all measurements (the behavior when no sink is set)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl MetricsSink for NoopMetrics {
+    fn record(&self, _subcommand: &str, _duration: Duration, _success: bool) {}
+}
+
+/// Completed non-streaming command, passed to a `CommandObserver`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRecord {
+    /// bjig subcommand without flags (e.g. `"module get-parameter"`)
+    pub subcommand: String,
+    /// Full argv passed to bjig, with module IDs masked if log redaction is enabled
+    pub argv: Vec<String>,
+    /// Execution time, excluding time spent waiting in the command queue
+    pub duration: Duration,
+    /// Whether the command exited successfully
+    pub success: bool,
+}
+
+/// Callback invoked with a `CommandRecord` after each non-streaming command
+pub type CommandObserver = Arc<dyn Fn(&CommandRecord) + Send + Sync>;
+
+/// Callback invoked with each stderr line of a streaming command
+pub type StderrObserver = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Runner that passes a record of every invocation of `inner` to `observer`
+pub(crate) struct ObservedRunner {
+    pub inner: Arc<dyn CommandRunner>,
+    pub observer: CommandObserver,
+    pub redact: bool,
+}
+
+impl std::fmt::Debug for ObservedRunner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObservedRunner")
+            .field("inner", &self.inner)
+            .field("redact", &self.redact)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ObservedRunner {
+    fn observe<'a, T: Send + 'a>(
+        &'a self,
+        argv: &'a [String],
+        run: Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>,
+        succeeded: fn(&Result<T>) -> bool,
+    ) -> Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>> {
+        Box::pin(async move {
+            let started = Instant::now();
+            let result = run.await;
+            let record = CommandRecord {
+                subcommand: command_fields(argv).0,
+                argv: if self.redact { redact_argv(argv) } else { argv.to_vec() },
+                duration: started.elapsed(),
+                success: succeeded(&result),
+            };
+            (self.observer)(&record);
+            result
+        })
+    }
+}
+
+impl CommandRunner for ObservedRunner {
+    fn run<'a>(&'a self, argv: &'a [String]) -> RunFuture<'a> {
+        self.observe(argv, self.inner.run(argv), Result::is_ok)
+    }
+
+    fn run_with_stdin<'a>(&'a self, argv: &'a [String], stdin: &'a [u8]) -> RunFuture<'a> {
+        self.observe(argv, self.inner.run_with_stdin(argv, stdin), Result::is_ok)
+    }
+
+    fn run_output<'a>(&'a self, argv: &'a [String]) -> OutputFuture<'a> {
+        self.observe(argv, self.inner.run_output(argv), exited_successfully)
+    }
+}
+
+/// Runner that reports every invocation of `inner` to `sink`
+pub(crate) struct MeteredRunner {
+    pub inner: Arc<dyn CommandRunner>,
+    pub sink: Arc<dyn MetricsSink>,
+}
+
+impl std::fmt::Debug for MeteredRunner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeteredRunner")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MeteredRunner {
+    fn measure<'a, T: Send + 'a>(
+        &'a self,
+        argv: &'a [String],
+        run: Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>,
+        succeeded: fn(&Result<T>) -> bool,
+    ) -> Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>> {
+        Box::pin(async move {
+            let started = Instant::now();
+            let result = run.await;
+            let (subcommand, _) = command_fields(argv);
+            self.sink.record(&subcommand, started.elapsed(), succeeded(&result));
+            result
+        })
+    }
+}
+
+impl CommandRunner for MeteredRunner {
+    fn run<'a>(&'a self, argv: &'a [String]) -> RunFuture<'a> {
+        self.measure(argv, self.inner.run(argv), Result::is_ok)
+    }
+
+    fn run_with_stdin<'a>(&'a self, argv: &'a [String], stdin: &'a [u8]) -> RunFuture<'a> {
+        self.measure(argv, self.inner.run_with_stdin(argv, stdin), Result::is_ok)
+    }
+
+    fn run_output<'a>(&'a self, argv: &'a [String]) -> OutputFuture<'a> {
+        self.measure(argv, self.inner.run_output(argv), exited_successfully)
+    }
+}
+
+/// Success of a raw command, which reports a non-zero exit in its output
+fn exited_successfully(result: &Result<CommandOutput>) -> bool {
+    result.as_ref().is_ok_and(CommandOutput::is_success)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+    use crate::BjigController;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct CapturingSink {
+        records: Mutex<Vec<(String, bool)>>,
+    }
+
+    impl MetricsSink for CapturingSink {
+        fn record(&self, subcommand: &str, _duration: Duration, success: bool) {
+            self.records
+                .lock()
+                .unwrap()
+                .push((subcommand.to_string(), success));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_sink_records_each_command() {
+        let sink = Arc::new(CapturingSink::default());
+        let mock = MockRunner::new()
+            .on(&["router", "keep-alive"], r#"{"result":"success","message":"ok"}"#)
+            .on_failure(&["router", "stop"], "Serial port busy");
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock)
+            .build()
+            .unwrap()
+            .with_metrics(sink.clone());
+
+        bjig.router().keep_alive().await.unwrap();
+        assert!(bjig.router().stop().await.is_err());
+        bjig.module("0121", "2468800203400004")
+            .get_parameter()
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            *sink.records.lock().unwrap(),
+            vec![
+                ("router keep-alive".to_string(), true),
+                ("router stop".to_string(), false),
+                ("module get-parameter".to_string(), false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_observer_receives_record() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&records);
+        let mock = MockRunner::new().on(&["module", "get-parameter"], r#"{"interval":60}"#);
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .baud(38400)
+            .runner(mock)
+            .build()
+            .unwrap()
+            .with_log_redaction(true)
+            .on_command(Arc::new(move |record: &CommandRecord| {
+                sink.lock().unwrap().push(record.clone());
+            }));
+
+        bjig.module("0121", "2468800203400004")
+            .get_parameter()
+            .await
+            .unwrap();
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].subcommand, "module get-parameter");
+        assert!(records[0].success);
+        assert!(records[0].argv.contains(&"2468**********04".to_string()));
+        assert!(!records[0].argv.contains(&"2468800203400004".to_string()));
+        assert_eq!(&records[0].argv[..4], ["--port", "/dev/null", "--baud", "38400"]);
+    }
+}
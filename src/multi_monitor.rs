@@ -0,0 +1,177 @@
+//! Monitoring several routers at once into one merged event stream
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::commands::monitor::{MonitorCommand, MonitorHandle};
+use crate::types::{BjigError, MonitorEvent, Result};
+
+/// Monitor event tagged with the port it was received on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaggedMonitorEvent {
+    pub port: String,
+    pub event: MonitorEvent,
+}
+
+/// `bjig monitor` running on several ports, merged into one channel
+///
+/// One monitor process is started per port; every parsed event is sent to
+/// the shared channel tagged with its origin port. Events of one port keep
+/// their order, events of different ports are interleaved as they arrive.
+/// A monitor stops when its process exits or the receiver is dropped;
+/// `stop` stops all of them together.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::{BjigController, MultiMonitor};
+/// use tokio::sync::mpsc;
+///
+/// let bjig = BjigController::from_env()?;
+/// let (tx, mut rx) = mpsc::channel(64);
+///
+/// let monitors = MultiMonitor::start(
+///     &bjig.monitor(),
+///     [("/dev/ttyACM0", 38400), ("/dev/ttyACM1", 38400)],
+///     tx,
+/// )
+/// .await?;
+/// while let Some(tagged) = rx.recv().await {
+///     println!("{}: {:?}", tagged.port, tagged.event);
+/// }
+/// monitors.stop().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiMonitor {
+    monitors: Vec<(String, MonitorHandle)>,
+}
+
+impl MultiMonitor {
+    /// Start monitoring on every `(port, baud)` and send tagged events to `tx`
+    ///
+    /// # Errors
+    /// If a monitor fails to start, the monitors started so far are stopped
+    /// and the error is returned.
+    pub async fn start<P>(
+        monitor: &MonitorCommand<'_>,
+        ports: impl IntoIterator<Item = (P, u32)>,
+        tx: mpsc::Sender<TaggedMonitorEvent>,
+    ) -> Result<Self>
+    where
+        P: Into<String>,
+    {
+        let mut monitors = Vec::new();
+
+        for (port, baud) in ports {
+            let port = port.into();
+            let tag = port.clone();
+            let sink = futures_util::sink::unfold(tx.clone(), move |tx, event| {
+                let port = tag.clone();
+                async move {
+                    tx.send(TaggedMonitorEvent { port, event }).await?;
+                    Ok::<_, mpsc::error::SendError<TaggedMonitorEvent>>(tx)
+                }
+            });
+
+            match monitor.start_to_sink_on(&port, baud, sink).await {
+                Ok(handle) => monitors.push((port, handle)),
+                Err(e) => {
+                    log::error!("Failed to start monitor on {}: {}", port, e);
+                    Self { monitors }.stop().await.ok();
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(Self { monitors })
+    }
+
+    /// Get the monitored ports in start order
+    pub fn ports(&self) -> Vec<&str> {
+        self.monitors.iter().map(|(port, _)| port.as_str()).collect()
+    }
+
+    /// Check if any monitor is still running
+    pub fn is_running(&self) -> bool {
+        self.monitors.iter().any(|(_, handle)| handle.is_running())
+    }
+
+    /// Stop all monitors concurrently
+    ///
+    /// # Errors
+    /// Every monitor is stopped; the first failure is returned.
+    pub async fn stop(self) -> Result<()> {
+        let mut tasks = tokio::task::JoinSet::new();
+        for (port, handle) in self.monitors {
+            tasks.spawn(async move {
+                handle
+                    .stop()
+                    .await
+                    .inspect_err(|e| log::error!("Monitor on {} failed: {}", port, e))
+            });
+        }
+
+        let mut result = Ok(());
+        while let Some(joined) = tasks.join_next().await {
+            let stopped = joined.unwrap_or_else(|e| {
+                Err(BjigError::CommandFailed(format!("Monitor task panicked: {}", e)))
+            });
+            result = result.and(stopped);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_bjig;
+    use crate::BjigController;
+    use std::time::Duration;
+
+    fn uplink(module_id: &str) -> String {
+        format!(r#"{{"sensor_id":"0121","module_id":"{}","lux":1}}"#, module_id)
+    }
+
+    #[tokio::test]
+    async fn test_multi_monitor_merges_tagged_events() {
+        let bin = fake_bjig(&format!(
+            r#"case "$2" in
+  /dev/north) echo '{a1}'; sleep 0.2; echo '{a2}' ;;
+  /dev/south) sleep 0.1; echo '{b1}'; sleep 0.2; echo '{b2}' ;;
+esac"#,
+            a1 = uplink("A1"),
+            a2 = uplink("A2"),
+            b1 = uplink("B1"),
+            b2 = uplink("B2"),
+        ));
+        let bjig = BjigController::new(&bin).unwrap();
+        let (tx, mut rx) = mpsc::channel(8);
+
+        let monitors = MultiMonitor::start(
+            &bjig.monitor(),
+            [("/dev/north", 38400), ("/dev/south", 38400)],
+            tx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(monitors.ports(), vec!["/dev/north", "/dev/south"]);
+
+        let mut received = Vec::new();
+        while let Ok(Some(tagged)) = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await {
+            received.push((tagged.port, tagged.event.module_id().unwrap().to_string()));
+            if received.len() == 4 {
+                break;
+            }
+        }
+        monitors.stop().await.unwrap();
+
+        let expected = [("/dev/north", "A1"), ("/dev/south", "B1"), ("/dev/north", "A2"), ("/dev/south", "B2")];
+        assert_eq!(
+            received,
+            expected.map(|(port, id)| (port.to_string(), id.to_string()))
+        );
+    }
+}
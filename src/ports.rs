@@ -0,0 +1,164 @@
+//! Serial port auto-detection by USB vendor/product ID
+
+use std::fs;
+use std::path::Path;
+
+use crate::types::{BjigError, Result};
+
+/// Root of the tty class in sysfs
+const SYSFS_TTY_CLASS: &str = "/sys/class/tty";
+
+/// USB serial port found on the system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsbPortInfo {
+    /// Device path (e.g. `/dev/ttyACM0`)
+    pub path: String,
+    pub vid: u16,
+    pub pid: u16,
+}
+
+/// Enumerate USB serial ports
+///
+/// Ports are discovered through sysfs, so this returns an empty list on
+/// platforms other than Linux.
+pub fn available_usb_ports() -> Result<Vec<UsbPortInfo>> {
+    let root = Path::new(SYSFS_TTY_CLASS);
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+    scan_tty_class(root, Path::new("/dev"))
+}
+
+/// Select the single port matching `vid`/`pid`
+///
+/// # Errors
+/// Returns `BjigError::NoMatchingPort` if no port matches and
+/// `BjigError::AmbiguousPort` if more than one does.
+pub fn select_port(ports: &[UsbPortInfo], vid: u16, pid: u16) -> Result<String> {
+    let mut candidates: Vec<String> = ports
+        .iter()
+        .filter(|port| port.vid == vid && port.pid == pid)
+        .map(|port| port.path.clone())
+        .collect();
+
+    match candidates.len() {
+        0 => Err(BjigError::NoMatchingPort { vid, pid }),
+        1 => Ok(candidates.remove(0)),
+        _ => {
+            candidates.sort();
+            Err(BjigError::AmbiguousPort { vid, pid, candidates })
+        }
+    }
+}
+
+/// Find the port of the device with `vid`/`pid`
+pub fn detect_port(vid: u16, pid: u16) -> Result<String> {
+    select_port(&available_usb_ports()?, vid, pid)
+}
+
+/// Scan a sysfs tty class directory for USB devices
+///
+/// For each tty, `device` points at the USB interface; the vendor and
+/// product IDs live in its parent (the USB device).
+fn scan_tty_class(root: &Path, dev_dir: &Path) -> Result<Vec<UsbPortInfo>> {
+    let mut ports = Vec::new();
+
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let interface = match fs::canonicalize(entry.path().join("device")) {
+            Ok(path) => path,
+            Err(_) => continue,
+        };
+        let usb_device = match interface.parent() {
+            Some(parent) => parent,
+            None => continue,
+        };
+
+        if let (Some(vid), Some(pid)) = (
+            read_hex_id(&usb_device.join("idVendor")),
+            read_hex_id(&usb_device.join("idProduct")),
+        ) {
+            ports.push(UsbPortInfo {
+                path: dev_dir.join(entry.file_name()).to_string_lossy().into_owned(),
+                vid,
+                pid,
+            });
+        }
+    }
+
+    ports.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(ports)
+}
+
+fn read_hex_id(path: &Path) -> Option<u16> {
+    let contents = fs::read_to_string(path).ok()?;
+    u16::from_str_radix(contents.trim(), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_dir;
+
+    const ROUTER_VID: u16 = 0x1915;
+    const ROUTER_PID: u16 = 0x521A;
+
+    fn port(path: &str, vid: u16, pid: u16) -> UsbPortInfo {
+        UsbPortInfo {
+            path: path.to_string(),
+            vid,
+            pid,
+        }
+    }
+
+    #[test]
+    fn test_select_port() {
+        let ports = vec![
+            port("/dev/ttyUSB0", 0x0403, 0x6001),
+            port("/dev/ttyACM1", ROUTER_VID, ROUTER_PID),
+            port("/dev/ttyACM0", 0x2341, 0x0043),
+        ];
+        assert_eq!(
+            select_port(&ports, ROUTER_VID, ROUTER_PID).unwrap(),
+            "/dev/ttyACM1"
+        );
+
+        assert!(matches!(
+            select_port(&ports, 0x1234, 0x5678),
+            Err(BjigError::NoMatchingPort { vid: 0x1234, pid: 0x5678 })
+        ));
+        assert!(matches!(
+            select_port(&[], ROUTER_VID, ROUTER_PID),
+            Err(BjigError::NoMatchingPort { .. })
+        ));
+
+        let ports = vec![
+            port("/dev/ttyACM3", 0x0403, 0x6001),
+            port("/dev/ttyACM2", 0x0403, 0x6001),
+        ];
+        match select_port(&ports, 0x0403, 0x6001) {
+            Err(BjigError::AmbiguousPort { candidates, .. }) => {
+                assert_eq!(candidates, vec!["/dev/ttyACM2", "/dev/ttyACM3"]);
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_scan_tty_class() {
+        let sys = temp_dir();
+        let usb_device = sys.join("devices/usb1/1-1");
+        std::fs::create_dir_all(usb_device.join("1-1:1.0")).unwrap();
+        std::fs::write(usb_device.join("idVendor"), "1915\n").unwrap();
+        std::fs::write(usb_device.join("idProduct"), "521a\n").unwrap();
+
+        let class = sys.join("class/tty");
+        std::fs::create_dir_all(class.join("ttyACM0")).unwrap();
+        std::os::unix::fs::symlink(usb_device.join("1-1:1.0"), class.join("ttyACM0/device")).unwrap();
+        // Virtual console without a device link
+        std::fs::create_dir_all(class.join("tty0")).unwrap();
+
+        let ports = scan_tty_class(&class, Path::new("/dev")).unwrap();
+        assert_eq!(ports, vec![port("/dev/ttyACM0", 0x1915, 0x521A)]);
+    }
+}
@@ -0,0 +1,235 @@
+//! Module registry maintained from monitor notifications
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::monitor::{MonitorCommand, MonitorHandle};
+use crate::commands::router::RouterCommands;
+use crate::types::{ModuleIdList, ModuleMembership, MonitorEvent, Result};
+
+/// Presence of a module as last reported to the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleStatus {
+    /// Registered on the router (seeded), but not heard from yet
+    Registered,
+    /// Joined the router or sent an uplink
+    Joined,
+    /// Left the router (reported by a leave notification)
+    Left,
+}
+
+/// Last known state of a module
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleState {
+    pub status: ModuleStatus,
+    /// Sensor ID, once reported by a notification or uplink
+    pub sensor_id: Option<String>,
+    /// Time of the last notification or uplink from the module
+    pub last_seen: Option<SystemTime>,
+    /// Uplinks received since the module was added to the registry
+    pub uplink_count: u64,
+    /// RSSI of the last uplink that carried one
+    pub rssi: Option<i16>,
+}
+
+impl ModuleState {
+    fn new(status: ModuleStatus) -> Self {
+        Self {
+            status,
+            sensor_id: None,
+            last_seen: None,
+            uplink_count: 0,
+            rssi: None,
+        }
+    }
+}
+
+/// Registry of modules kept up to date from monitor events
+///
+/// `module_joined` and `module_left` notifications update the module's
+/// status, and uplinks mark the module as joined and refresh its last-seen
+/// time and RSSI. Clones share the same registry, so one clone can be fed
+/// by a background monitor (`subscribe`) while others are queried.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::{BjigController, ModuleRegistry};
+///
+/// let bjig = BjigController::from_env()?;
+/// let registry = ModuleRegistry::new();
+/// registry.seed_from_router(&bjig.router()).await?;
+///
+/// let handle = registry.subscribe(&bjig.monitor()).await?;
+/// tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+/// for (module_id, state) in registry.snapshot() {
+///     println!("{}: {:?}", module_id, state.status);
+/// }
+/// handle.stop().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ModuleRegistry {
+    modules: Arc<Mutex<HashMap<String, ModuleState>>>,
+}
+
+impl ModuleRegistry {
+    /// Create empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add modules registered on the router, keeping the state of known ones
+    ///
+    /// Returns the number of modules added.
+    pub fn seed(&self, list: &ModuleIdList) -> usize {
+        let mut modules = self.modules.lock().unwrap();
+        let before = modules.len();
+        for module_id in &list.modules {
+            modules
+                .entry(module_id.clone())
+                .or_insert_with(|| ModuleState::new(ModuleStatus::Registered));
+        }
+        modules.len() - before
+    }
+
+    /// Seed the registry with the modules registered on the router
+    ///
+    /// Returns the number of modules added.
+    pub async fn seed_from_router(&self, router: &RouterCommands<'_>) -> Result<usize> {
+        let list = router.get_module_id(None).await?;
+        Ok(self.seed(&list))
+    }
+
+    /// Update the registry from an event received now
+    ///
+    /// Returns `true` if the event concerned a module.
+    pub fn record(&self, event: &MonitorEvent) -> bool {
+        self.record_at(event, SystemTime::now())
+    }
+
+    /// Update the registry from an event received at `at`
+    pub fn record_at(&self, event: &MonitorEvent, at: SystemTime) -> bool {
+        let (module_id, sensor_id, status) = match (event.as_uplink(), event.module_membership()) {
+            (Some(uplink), _) => (
+                uplink.module_id.clone(),
+                Some(uplink.sensor_id.clone()),
+                ModuleStatus::Joined,
+            ),
+            (None, Some(ModuleMembership::Joined { module_id, sensor_id })) => {
+                (module_id, sensor_id, ModuleStatus::Joined)
+            }
+            (None, Some(ModuleMembership::Left { module_id, sensor_id })) => {
+                (module_id, sensor_id, ModuleStatus::Left)
+            }
+            (None, None) => return false,
+        };
+
+        let mut modules = self.modules.lock().unwrap();
+        let state = modules
+            .entry(module_id)
+            .or_insert_with(|| ModuleState::new(status));
+        state.status = status;
+        state.last_seen = Some(at);
+        if sensor_id.is_some() {
+            state.sensor_id = sensor_id;
+        }
+        if event.as_uplink().is_some() {
+            state.uplink_count += 1;
+            state.rssi = event.rssi().or(state.rssi);
+        }
+        true
+    }
+
+    /// Start a background monitor that feeds this registry
+    pub async fn subscribe(&self, monitor: &MonitorCommand<'_>) -> Result<MonitorHandle> {
+        let registry = self.clone();
+        monitor
+            .start_with_callback_and_handle(move |line| {
+                registry.record(&MonitorEvent::parse(line));
+                Ok(true)
+            })
+            .await
+    }
+
+    /// Get the state of one module
+    pub fn get(&self, module_id: &str) -> Option<ModuleState> {
+        self.modules.lock().unwrap().get(module_id).cloned()
+    }
+
+    /// Get the state of all modules keyed by module ID
+    pub fn snapshot(&self) -> HashMap<String, ModuleState> {
+        self.modules.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_bjig;
+    use crate::BjigController;
+    use std::time::Duration;
+
+    const JOINED: &str = r#"{"notification":"module_joined","module_id":"AAAA000000000001","sensor_id":"0121"}"#;
+    const UPLINK: &str = r#"{"sensor_id":"0121","module_id":"AAAA000000000001","lux":300,"rssi":-70}"#;
+    const LEFT: &str = r#"{"notification":"module_left","module_id":"AAAA000000000001"}"#;
+
+    #[test]
+    fn test_join_then_leave() {
+        let registry = ModuleRegistry::new();
+        let list = ModuleIdList {
+            module_count: 2,
+            modules: vec!["AAAA000000000001".to_string(), "BBBB000000000002".to_string()],
+        };
+        assert_eq!(registry.seed(&list), 2);
+        assert_eq!(registry.get("AAAA000000000001").unwrap().status, ModuleStatus::Registered);
+
+        assert!(registry.record(&MonitorEvent::parse(JOINED)));
+        assert!(registry.record(&MonitorEvent::parse(UPLINK)));
+        let state = registry.get("AAAA000000000001").unwrap();
+        assert_eq!(state.status, ModuleStatus::Joined);
+        assert_eq!(state.sensor_id.as_deref(), Some("0121"));
+        assert_eq!((state.uplink_count, state.rssi), (1, Some(-70)));
+
+        assert!(registry.record(&MonitorEvent::parse(LEFT)));
+        assert!(!registry.record(&MonitorEvent::parse(r#"{"notification":"scan_started"}"#)));
+        assert!(!registry.record(&MonitorEvent::parse("router ready")));
+
+        let snapshot = registry.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot["AAAA000000000001"].status, ModuleStatus::Left);
+        assert_eq!(snapshot["AAAA000000000001"].uplink_count, 1);
+        assert_eq!(snapshot["BBBB000000000002"].status, ModuleStatus::Registered);
+
+        // Seeding again keeps known state
+        assert_eq!(registry.seed(&list), 0);
+        assert_eq!(registry.get("AAAA000000000001").unwrap().status, ModuleStatus::Left);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_feeds_registry() {
+        let bin = fake_bjig(&format!("echo '{}'\necho '{}'\necho '{}'", JOINED, UPLINK, LEFT));
+        let bjig = BjigController::new(bin).unwrap().with_port("/dev/null");
+        let registry = ModuleRegistry::new();
+
+        let handle = registry.subscribe(&bjig.monitor()).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while handle.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        handle.stop().await.unwrap();
+
+        let state = registry.get("AAAA000000000001").unwrap();
+        assert_eq!(state.status, ModuleStatus::Left);
+        assert_eq!(state.uplink_count, 1);
+    }
+}
@@ -0,0 +1,218 @@
+//! Controller wrapper that follows the router across USB re-enumeration
+
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::api::ApiFuture;
+use crate::controller::BjigController;
+use crate::ports;
+use crate::types::{BjigError, PortFault, Result};
+
+/// Function returning the current serial port of the router
+pub type PortDetector = Arc<dyn Fn() -> Result<String> + Send + Sync>;
+
+/// `BjigController` that re-detects its port when the device disappears
+///
+/// When the router re-enumerates, its port path may change (e.g.
+/// `/dev/ttyACM0` to `/dev/ttyACM1`). Commands run through `run` that fail
+/// because the port device is missing (`PortFault::DeviceNotFound`) trigger
+/// port autodetection by USB ID, and are retried once if the router was
+/// found on a different port. Later commands use the new port directly.
+///
+/// Since bjig could not open the old port, the failed attempt never reached
+/// the router and the retry does not repeat a write.
+///
+/// Only commands run through `run` are covered; monitors started from the
+/// wrapped controller keep the port they were started on.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::BjigController;
+///
+/// let bjig = BjigController::from_env_autodetect()?.resilient();
+///
+/// let version = bjig
+///     .run(|c| Box::pin(async move { c.router().get_version().await }))
+///     .await?;
+/// println!("Version {} on {:?}", version.version, bjig.port().await);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ResilientController {
+    controller: RwLock<BjigController>,
+    detector: PortDetector,
+}
+
+impl ResilientController {
+    /// Wrap `controller`, detecting ports by its USB IDs
+    pub(crate) fn new(controller: BjigController) -> Self {
+        let usb_ids = controller.usb_ids;
+        Self {
+            controller: RwLock::new(controller),
+            detector: Arc::new(move || {
+                let (vid, pid) = usb_ids.ok_or(BjigError::UsbIdsNotConfigured)?;
+                ports::detect_port(vid, pid)
+            }),
+        }
+    }
+
+    /// Use `detector` instead of USB ID autodetection to find the new port
+    pub fn with_detector(mut self, detector: PortDetector) -> Self {
+        self.detector = detector;
+        self
+    }
+
+    /// Get the port commands currently run on
+    pub async fn port(&self) -> Option<String> {
+        self.controller.read().await.config.default_port.clone()
+    }
+
+    /// Run `operation`, re-detecting the port and retrying once if the device went missing
+    ///
+    /// Other errors, including `PortFault::PortBusy`, are returned as-is.
+    ///
+    /// # Errors
+    /// Returns the original error if the port cannot be re-detected or the
+    /// router is still on the failed port, and the retry's error if the
+    /// command also fails on the new port.
+    pub async fn run<T, F>(&self, operation: F) -> Result<T>
+    where
+        F: for<'c> Fn(&'c BjigController) -> ApiFuture<'c, T>,
+    {
+        let (error, failed_port) = {
+            let controller = self.controller.read().await;
+            match operation(&controller).await {
+                Err(e) if e.port_fault() == Some(PortFault::DeviceNotFound) => {
+                    (e, controller.config.default_port.clone())
+                }
+                result => return result,
+            }
+        };
+
+        self.redetect(failed_port, error).await?;
+        let controller = self.controller.read().await;
+        operation(&controller).await
+    }
+
+    /// Switch to the detected port after the device on `failed_port` went missing
+    ///
+    /// Returns `error` unless the router is now on another port.
+    async fn redetect(&self, failed_port: Option<String>, error: BjigError) -> Result<()> {
+        let mut controller = self.controller.write().await;
+        if controller.config.default_port != failed_port {
+            // Another command re-detected the port in the meantime
+            return Ok(());
+        }
+
+        match (self.detector)() {
+            Ok(port) if failed_port.as_deref() == Some(port.as_str()) => {
+                log::warn!("Router still detected on {} after fault: {}", port, error);
+                Err(error)
+            }
+            Ok(port) => {
+                log::warn!(
+                    "Port fault on {:?} ({}), switching to {}",
+                    failed_port, error, port
+                );
+                controller.config.default_port = Some(port);
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("Port re-detection after fault failed: {}", e);
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_bjig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_port_change_between_commands() {
+        let bin = fake_bjig(
+            r#"echo "$2" >> "$0.ports"
+case "$2" in
+  /dev/ttyACM1) echo '{"major":1,"minor":2,"build":3,"version":"1.2.3"}' ;;
+  *) echo "Error: could not open port $2: No such file or directory" >&2; exit 1 ;;
+esac"#,
+        );
+        let detections = Arc::new(AtomicUsize::new(0));
+        let counter = detections.clone();
+        let bjig = BjigController::new(&bin)
+            .unwrap()
+            .with_port("/dev/ttyACM0")
+            .resilient()
+            .with_detector(Arc::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok("/dev/ttyACM1".to_string())
+            }));
+
+        for _ in 0..2 {
+            let version = bjig
+                .run(|c| Box::pin(async move { c.router().get_version().await }))
+                .await
+                .unwrap();
+            assert_eq!(version.version, "1.2.3");
+        }
+
+        assert_eq!(bjig.port().await.as_deref(), Some("/dev/ttyACM1"));
+        assert_eq!(detections.load(Ordering::SeqCst), 1);
+        let ports = std::fs::read_to_string(bin.with_extension("ports")).unwrap();
+        assert_eq!(
+            ports.lines().collect::<Vec<_>>(),
+            ["/dev/ttyACM0", "/dev/ttyACM1", "/dev/ttyACM1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_detection_returns_original_error() {
+        let bin = fake_bjig("echo 'Error: No such device' >&2; exit 1");
+        let bjig = BjigController::new(&bin)
+            .unwrap()
+            .with_port("/dev/ttyACM0")
+            .resilient()
+            .with_detector(Arc::new(|| {
+                Err(BjigError::NoMatchingPort { vid: 0x2fe3, pid: 0x0100 })
+            }));
+
+        let result = bjig
+            .run(|c| Box::pin(async move { c.router().get_version().await }))
+            .await;
+        assert!(matches!(&result, Err(e) if e.port_fault() == Some(PortFault::DeviceNotFound)));
+        assert_eq!(bjig.port().await.as_deref(), Some("/dev/ttyACM0"));
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_without_port_change() {
+        for stderr in ["No such file or directory", "Device or resource busy"] {
+            let bin = fake_bjig(&format!(
+                "echo x >> \"$0.calls\"; echo 'Error: {}' >&2; exit 1",
+                stderr
+            ));
+            let detections = Arc::new(AtomicUsize::new(0));
+            let counter = detections.clone();
+            let bjig = BjigController::new(&bin)
+                .unwrap()
+                .with_port("/dev/ttyACM0")
+                .resilient()
+                .with_detector(Arc::new(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok("/dev/ttyACM0".to_string())
+                }));
+
+            let result = bjig
+                .run(|c| Box::pin(async move { c.router().get_version().await }))
+                .await;
+            assert!(result.unwrap_err().port_fault().is_some(), "{}", stderr);
+            let calls = std::fs::read_to_string(bin.with_extension("calls")).unwrap();
+            assert_eq!(calls.lines().count(), 1, "{}", stderr);
+        }
+    }
+}
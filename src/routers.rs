@@ -0,0 +1,166 @@
+//! Registry of named controllers for several routers in one process
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::controller::BjigController;
+use crate::types::{BjigError, Result};
+
+/// Named `BjigController`s, one per USB router
+///
+/// Every controller keeps its own port, baud rate and command queue, so
+/// commands to different routers run concurrently while commands to the
+/// same router stay serialized. Controllers are held in an `Arc` and can be
+/// cloned out of the registry into spawned tasks.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use bjig_controller::{BjigController, BjigRouters};
+///
+/// let mut routers = BjigRouters::new();
+/// routers.add("north", BjigController::from_env()?.with_port("/dev/ttyACM0"));
+/// routers.add("south", BjigController::from_env()?.with_port("/dev/ttyACM1"));
+///
+/// let (north, south) = (routers.get("north")?.router(), routers.get("south")?.router());
+/// let (north, south) = tokio::join!(north.get_version(), south.get_version());
+/// println!("north: {}, south: {}", north?.version, south?.version);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct BjigRouters {
+    routers: HashMap<String, Arc<BjigController>>,
+}
+
+impl BjigRouters {
+    /// Create empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `controller` under `name`
+    ///
+    /// Returns the controller previously registered under `name`, if any.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        controller: BjigController,
+    ) -> Option<Arc<BjigController>> {
+        self.routers.insert(name.into(), Arc::new(controller))
+    }
+
+    /// Get the controller registered under `name`
+    ///
+    /// # Errors
+    /// Returns `BjigError::UnknownRouter` if no controller has that name.
+    pub fn get(&self, name: &str) -> Result<&Arc<BjigController>> {
+        self.routers
+            .get(name)
+            .ok_or_else(|| BjigError::UnknownRouter(name.to_string()))
+    }
+
+    /// Unregister and return the controller registered under `name`
+    pub fn remove(&mut self, name: &str) -> Option<Arc<BjigController>> {
+        self.routers.remove(name)
+    }
+
+    /// Get registered names in sorted order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.routers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Number of registered controllers
+    pub fn len(&self) -> usize {
+        self.routers.len()
+    }
+
+    /// Check if no controller is registered
+    pub fn is_empty(&self) -> bool {
+        self.routers.is_empty()
+    }
+
+    /// Shut down every registered controller concurrently (see `BjigController::shutdown`)
+    pub async fn shutdown_all(&self) {
+        let mut tasks = tokio::task::JoinSet::new();
+        for controller in self.routers.values() {
+            let controller = Arc::clone(controller);
+            tasks.spawn(async move { controller.shutdown().await });
+        }
+        while tasks.join_next().await.is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+    use crate::test_support::fake_bjig;
+    use std::time::{Duration, Instant};
+
+    const VERSION_JSON: &str = r#"{"major":1,"minor":2,"build":3,"version":"1.2.3"}"#;
+
+    fn mock_controller(port: &str, mock: &Arc<MockRunner>) -> BjigController {
+        BjigController::builder()
+            .port(port)
+            .runner(Arc::clone(mock))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_routers_address_by_name() {
+        let north = Arc::new(MockRunner::new().on(&["router", "get-version"], VERSION_JSON));
+        let south = Arc::new(MockRunner::new().on(&["router", "get-version"], VERSION_JSON));
+
+        let mut routers = BjigRouters::new();
+        assert!(routers.add("north", mock_controller("/dev/ttyACM0", &north)).is_none());
+        assert!(routers.add("south", mock_controller("/dev/ttyACM1", &south)).is_none());
+        assert_eq!(routers.names(), vec!["north", "south"]);
+
+        let (north_router, south_router) = (
+            routers.get("north").unwrap().router(),
+            routers.get("south").unwrap().router(),
+        );
+        let (a, b) = tokio::join!(north_router.get_version(), south_router.get_version());
+        assert_eq!(a.unwrap().version, "1.2.3");
+        assert_eq!(b.unwrap().version, "1.2.3");
+
+        assert_eq!(north.calls().len(), 1);
+        assert_eq!(north.calls()[0][1], "/dev/ttyACM0");
+        assert_eq!(south.calls().len(), 1);
+        assert_eq!(south.calls()[0][1], "/dev/ttyACM1");
+
+        assert!(matches!(
+            routers.get("east"),
+            Err(BjigError::UnknownRouter(name)) if name == "east"
+        ));
+
+        routers.shutdown_all().await;
+        assert!(matches!(
+            routers.get("north").unwrap().router().get_version().await,
+            Err(BjigError::Closed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_routers_run_concurrently() {
+        let bin = fake_bjig(&format!("sleep 0.3; echo '{}'", VERSION_JSON));
+        let mut routers = BjigRouters::new();
+        routers.add("north", BjigController::new(&bin).unwrap().with_port("/dev/ttyACM0"));
+        routers.add("south", BjigController::new(&bin).unwrap().with_port("/dev/ttyACM1"));
+
+        let started = Instant::now();
+        let (north_router, south_router) = (
+            routers.get("north").unwrap().router(),
+            routers.get("south").unwrap().router(),
+        );
+        let (a, b) = tokio::join!(north_router.get_version(), south_router.get_version());
+        a.unwrap();
+        b.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(550), "{:?}", started.elapsed());
+    }
+}
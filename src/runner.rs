@@ -0,0 +1,317 @@
+//! Pluggable runners for non-streaming bjig commands
+//!
+//! By default commands are executed by spawning the bjig binary
+//! (`ProcessRunner`). A custom `CommandRunner` can be injected with
+//! `BjigController::with_runner`, e.g. a `MockRunner` returning canned JSON
+//! in tests. Streaming commands (monitor) always spawn the binary.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use crate::executor::{run_process, run_process_output, run_process_with_stdin};
+use crate::types::{BjigError, CommandOutput, Result};
+
+/// Future returned by `CommandRunner::run`
+pub type RunFuture<'a> = Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+/// Future returned by `CommandRunner::run_output`
+pub type OutputFuture<'a> = Pin<Box<dyn Future<Output = Result<CommandOutput>> + Send + 'a>>;
+
+/// Executes a single bjig invocation and returns its stdout
+///
+/// `argv` is the full argument list passed to bjig, including `--port` and
+/// `--baud` for commands that use them. A non-zero exit should be reported
+/// as `BjigError::CommandFailed` (or `BjigError::Timeout`) so that retry
+/// policies apply.
+pub trait CommandRunner: std::fmt::Debug + Send + Sync {
+    /// Run bjig with the given arguments
+    fn run<'a>(&'a self, argv: &'a [String]) -> RunFuture<'a>;
+
+    /// Run bjig with the given arguments and `stdin` piped to it
+    ///
+    /// The default implementation ignores `stdin` and calls `run`.
+    fn run_with_stdin<'a>(&'a self, argv: &'a [String], stdin: &'a [u8]) -> RunFuture<'a> {
+        let _ = stdin;
+        self.run(argv)
+    }
+
+    /// Run bjig with the given arguments and return its exit status and output
+    ///
+    /// A non-zero exit is reported in `CommandOutput::status`, not as an
+    /// error. The default implementation calls `run` and reports its stdout
+    /// with exit code 0; errors from `run` are passed through.
+    fn run_output<'a>(&'a self, argv: &'a [String]) -> OutputFuture<'a> {
+        Box::pin(async move {
+            let stdout = self.run(argv).await?;
+            Ok(CommandOutput {
+                status: Some(0),
+                stdout,
+                stderr: String::new(),
+            })
+        })
+    }
+}
+
+impl<R: CommandRunner + ?Sized> CommandRunner for Arc<R> {
+    fn run<'a>(&'a self, argv: &'a [String]) -> RunFuture<'a> {
+        (**self).run(argv)
+    }
+
+    fn run_with_stdin<'a>(&'a self, argv: &'a [String], stdin: &'a [u8]) -> RunFuture<'a> {
+        (**self).run_with_stdin(argv, stdin)
+    }
+
+    fn run_output<'a>(&'a self, argv: &'a [String]) -> OutputFuture<'a> {
+        (**self).run_output(argv)
+    }
+}
+
+/// Runner that spawns the bjig binary
+#[derive(Debug, Clone)]
+pub struct ProcessRunner {
+    bjig_path: PathBuf,
+    working_dir: Option<PathBuf>,
+    redact_logs: bool,
+}
+
+impl ProcessRunner {
+    /// Create runner for the bjig binary at `bjig_path`
+    pub fn new<P: AsRef<Path>>(bjig_path: P) -> Self {
+        Self {
+            bjig_path: bjig_path.as_ref().to_path_buf(),
+            working_dir: None,
+            redact_logs: false,
+        }
+    }
+
+    /// Run bjig in `dir` instead of the current working directory
+    pub fn with_working_dir<P: AsRef<Path>>(mut self, dir: Option<P>) -> Self {
+        self.working_dir = dir.map(|d| d.as_ref().to_path_buf());
+        self
+    }
+
+    /// Mask module IDs in the logged argv
+    pub fn with_log_redaction(mut self, redact: bool) -> Self {
+        self.redact_logs = redact;
+        self
+    }
+}
+
+impl CommandRunner for ProcessRunner {
+    fn run<'a>(&'a self, argv: &'a [String]) -> RunFuture<'a> {
+        Box::pin(run_process(
+            &self.bjig_path,
+            self.working_dir.as_deref(),
+            argv,
+            self.redact_logs,
+        ))
+    }
+
+    fn run_with_stdin<'a>(&'a self, argv: &'a [String], stdin: &'a [u8]) -> RunFuture<'a> {
+        Box::pin(run_process_with_stdin(
+            &self.bjig_path,
+            self.working_dir.as_deref(),
+            argv,
+            stdin,
+            self.redact_logs,
+        ))
+    }
+
+    fn run_output<'a>(&'a self, argv: &'a [String]) -> OutputFuture<'a> {
+        Box::pin(async move {
+            let output = run_process_output(
+                &self.bjig_path,
+                self.working_dir.as_deref(),
+                argv,
+                self.redact_logs,
+            )
+            .await?;
+            Ok(CommandOutput {
+                status: output.status.code(),
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            })
+        })
+    }
+}
+
+/// Canned response of a `MockRunner`
+#[derive(Debug, Clone)]
+enum MockResponse {
+    Output(String),
+    Failure(String),
+    Timeout(u64),
+}
+
+/// Runner returning canned output per argv, for tests
+///
+/// A response matches if its arguments appear contiguously in the argv, so
+/// `["router", "get-version"]` matches regardless of `--port`/`--baud`.
+/// Responses are checked in registration order. Unmatched invocations fail
+/// with `BjigError::CommandFailed`. All invocations are recorded.
+///
+/// # Examples
+///
+/// ```
+/// # async fn example() -> bjig_controller::Result<()> {
+/// use std::sync::Arc;
+/// use bjig_controller::{BjigController, MockRunner};
+///
+/// let mock = Arc::new(MockRunner::new().on(
+///     &["router", "get-version"],
+///     r#"{"major":1,"minor":2,"build":3,"version":"1.2.3"}"#,
+/// ));
+/// let bjig = BjigController::builder()
+///     .port("/dev/ttyACM0")
+///     .runner(mock.clone())
+///     .build()?;
+///
+/// let version = bjig.router().get_version().await?;
+/// assert_eq!(version.version, "1.2.3");
+/// assert_eq!(mock.calls().len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct MockRunner {
+    responses: Vec<(Vec<String>, MockResponse)>,
+    calls: Mutex<Vec<Vec<String>>>,
+}
+
+impl MockRunner {
+    /// Create runner without any responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return `output` as stdout for invocations containing `args`
+    pub fn on(mut self, args: &[&str], output: impl Into<String>) -> Self {
+        self.responses
+            .push((to_strings(args), MockResponse::Output(output.into())));
+        self
+    }
+
+    /// Fail invocations containing `args` with `stderr` as the error message
+    pub fn on_failure(mut self, args: &[&str], stderr: impl Into<String>) -> Self {
+        self.responses
+            .push((to_strings(args), MockResponse::Failure(stderr.into())));
+        self
+    }
+
+    /// Fail invocations containing `args` with `BjigError::Timeout { secs }`
+    pub fn on_timeout(mut self, args: &[&str], secs: u64) -> Self {
+        self.responses.push((to_strings(args), MockResponse::Timeout(secs)));
+        self
+    }
+
+    /// Get the argv of every invocation so far
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Record an invocation and find its response
+    fn lookup(&self, argv: &[String]) -> Option<&MockResponse> {
+        self.calls.lock().unwrap().push(argv.to_vec());
+
+        self.responses
+            .iter()
+            .find(|(args, _)| {
+                args.is_empty() || argv.windows(args.len()).any(|w| w == args.as_slice())
+            })
+            .map(|(_, response)| response)
+    }
+
+    fn respond(&self, argv: &[String]) -> Result<String> {
+        Self::stdout_of(self.lookup(argv), argv)
+    }
+
+    fn stdout_of(response: Option<&MockResponse>, argv: &[String]) -> Result<String> {
+        match response {
+            Some(MockResponse::Output(output)) => Ok(output.clone()),
+            Some(MockResponse::Failure(stderr)) => Err(BjigError::CommandFailed(format!(
+                "Exit code: Some(1), stderr: {}",
+                stderr
+            ))),
+            Some(MockResponse::Timeout(secs)) => Err(BjigError::Timeout { secs: *secs }),
+            None => Err(BjigError::CommandFailed(format!(
+                "MockRunner: no response for {:?}",
+                argv
+            ))),
+        }
+    }
+}
+
+impl CommandRunner for MockRunner {
+    fn run<'a>(&'a self, argv: &'a [String]) -> RunFuture<'a> {
+        let result = self.respond(argv);
+        Box::pin(async move { result })
+    }
+
+    /// Report a failure response as exit code 1 with the message as stderr
+    fn run_output<'a>(&'a self, argv: &'a [String]) -> OutputFuture<'a> {
+        let result = match self.lookup(argv) {
+            Some(MockResponse::Failure(stderr)) => Ok(CommandOutput {
+                status: Some(1),
+                stdout: String::new(),
+                stderr: stderr.clone(),
+            }),
+            response => Self::stdout_of(response, argv).map(|stdout| CommandOutput {
+                status: Some(0),
+                stdout,
+                stderr: String::new(),
+            }),
+        };
+        Box::pin(async move { result })
+    }
+}
+
+fn to_strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BjigController;
+
+    #[tokio::test]
+    async fn test_mock_runner_matches_argv() {
+        let mock = Arc::new(
+            MockRunner::new()
+                .on(&["router", "start"], r#"{"result":"success","message":"started"}"#)
+                .on_failure(&["router", "stop"], "Serial port busy"),
+        );
+        let bjig = BjigController::builder()
+            .bin_path("/nonexistent/bjig")
+            .port("/dev/ttyACM0")
+            .baud(38400)
+            .runner(mock.clone())
+            .build()
+            .unwrap();
+
+        assert!(bjig.router().start().await.unwrap().is_success());
+        assert!(matches!(bjig.router().stop().await, Err(BjigError::CommandFailed(_))));
+        assert!(matches!(bjig.router().keep_alive().await, Err(BjigError::CommandFailed(_))));
+
+        let calls = mock.calls();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            calls[0],
+            vec!["--port", "/dev/ttyACM0", "--baud", "38400", "router", "start"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_runner_replaces_process() {
+        let bin = crate::test_support::fake_bjig("exit 1");
+        let mock = MockRunner::new().on(&["keep-alive"], r#"{"result":"success","message":"ok"}"#);
+        let bjig = BjigController::new(&bin)
+            .unwrap()
+            .with_port("/dev/null")
+            .with_runner(mock);
+
+        assert!(bjig.router().keep_alive().await.unwrap().is_success());
+    }
+}
@@ -0,0 +1,95 @@
+//! Helpers shared by unit tests
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Create a fresh, uniquely named temporary directory
+pub(crate) fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "bjig_controller_test_{}_{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write a file with the given contents into a fresh temporary directory
+pub(crate) fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = temp_dir().join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Write an executable shell script that stands in for the bjig binary
+///
+/// The script body receives the full bjig argv as `$@`.
+pub(crate) fn fake_bjig(body: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = temp_dir().join("bjig");
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    path
+}
+
+/// Deserialize JSON into `T`, serialize it back, and compare with the original
+pub(crate) fn assert_round_trip<T>(value: serde_json::Value)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let typed: T = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&typed).unwrap(), value);
+}
+
+/// Assert that the fake bjig process whose PID it wrote to `$0.pid` has exited
+///
+/// A killed but not yet reaped child (zombie) counts as exited.
+pub(crate) async fn assert_process_gone(bin: &std::path::Path) {
+    let pid = std::fs::read_to_string(bin.with_extension("pid")).unwrap();
+    let proc_path = format!("/proc/{}/stat", pid.trim());
+    for _ in 0..50 {
+        match std::fs::read_to_string(&proc_path) {
+            Err(_) => return,
+            Ok(stat) if stat.split(") ").nth(1).is_some_and(|s| s.starts_with('Z')) => return,
+            Ok(_) => tokio::time::sleep(std::time::Duration::from_millis(20)).await,
+        }
+    }
+    panic!("bjig process {} still running", pid.trim());
+}
+
+/// Log records captured by the test logger, formatted as `LEVEL message`
+static CAPTURED_LOGS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+struct CapturingLogger;
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        CAPTURED_LOGS
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", record.level(), record.args()));
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install a global logger capturing all records and return the records so far
+///
+/// Tests run in parallel, so callers should filter for values unique to
+/// their test.
+pub(crate) fn captured_logs() -> Vec<String> {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        log::set_logger(&CapturingLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+    });
+    CAPTURED_LOGS.lock().unwrap().clone()
+}
@@ -13,6 +13,41 @@ pub struct SensorInfo {
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+/// Uplink data fields carried by each measurement capability
+///
+/// Capabilities not listed here (e.g. `instant_uplink`, `dfu`) describe
+/// operations rather than data and add no expected fields.
+///
+/// This mapping is an assumption: bjig does not document which uplink
+/// fields a capability produces, so the names follow the capability names
+/// (and `lux` for illuminance). A capability whose uplink uses other field
+/// names will be reported as incomplete.
+const CAPABILITY_FIELDS: &[(&str, &[&str])] = &[
+    ("illuminance", &["lux"]),
+    ("temperature", &["temperature"]),
+    ("humidity", &["humidity"]),
+    ("pressure", &["pressure"]),
+    ("co2", &["co2"]),
+];
+
+impl SensorInfo {
+    /// Check whether the sensor lists `capability` (case-insensitive)
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(capability))
+    }
+
+    /// Get the uplink data fields implied by the sensor's capabilities
+    pub fn expected_fields(&self) -> Vec<&'static str> {
+        CAPABILITY_FIELDS
+            .iter()
+            .filter(|(capability, _)| self.supports(capability))
+            .flat_map(|(_, fields)| fields.iter().copied())
+            .collect()
+    }
+}
+
 /// Module configuration entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleConfig {
@@ -20,10 +55,21 @@ pub struct ModuleConfig {
     pub sensor_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Custom per-module metadata from the config file (e.g. location, owner)
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ModuleConfig {
+    /// Get a custom metadata field by key
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.extra.get(key)
+    }
 }
 
 /// Scan mode enum for type-safe API
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ScanModeType {
     LongRange,
     Legacy,
@@ -55,3 +101,160 @@ impl ScanModeType {
         }
     }
 }
+
+impl std::str::FromStr for ScanModeType {
+    type Err = super::error::BjigError;
+
+    /// Parse a mode name or its numeric value
+    ///
+    /// Names are case-insensitive and may contain `_` or `-`, so
+    /// `"LongRange"`, `"long_range"` and `"0"` all parse to `LongRange`.
+    fn from_str(s: &str) -> super::error::Result<Self> {
+        let normalized: String = s
+            .trim()
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-'))
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match normalized.as_str() {
+            "longrange" => Ok(ScanModeType::LongRange),
+            "legacy" => Ok(ScanModeType::Legacy),
+            other => other.parse().ok().and_then(Self::from_u8).ok_or_else(|| {
+                super::error::BjigError::InvalidParameter(format!("Invalid scan mode: {}", s))
+            }),
+        }
+    }
+}
+
+/// Desired router configuration for `RouterCommands::provision`
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use bjig_controller::{ProvisionSpec, ScanModeType};
+///
+/// let spec = ProvisionSpec::new()
+///     .with_scan_mode(ScanModeType::LongRange)
+///     .with_clear_modules(true)
+///     .with_settle_delay(Duration::from_secs(2));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProvisionSpec {
+    /// Scan mode to set, or `None` to leave it unchanged
+    pub scan_mode: Option<ScanModeType>,
+    /// Remove all registered module IDs
+    pub clear_modules: bool,
+    /// Delay between applying changes and verifying them
+    pub settle_delay: std::time::Duration,
+}
+
+impl ProvisionSpec {
+    /// Create a spec that changes nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the desired scan mode
+    pub fn with_scan_mode(mut self, mode: ScanModeType) -> Self {
+        self.scan_mode = Some(mode);
+        self
+    }
+
+    /// Remove all registered module IDs
+    pub fn with_clear_modules(mut self, clear: bool) -> Self {
+        self.clear_modules = clear;
+        self
+    }
+
+    /// Set delay between applying changes and verifying them
+    pub fn with_settle_delay(mut self, delay: std::time::Duration) -> Self {
+        self.settle_delay = delay;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::assert_round_trip;
+    use serde_json::json;
+
+    #[test]
+    fn test_scan_mode_from_str() {
+        assert_eq!("LongRange".parse::<ScanModeType>().unwrap(), ScanModeType::LongRange);
+        assert_eq!("long_range".parse::<ScanModeType>().unwrap(), ScanModeType::LongRange);
+        assert_eq!("legacy".parse::<ScanModeType>().unwrap(), ScanModeType::Legacy);
+        assert_eq!("0".parse::<ScanModeType>().unwrap(), ScanModeType::LongRange);
+        assert_eq!(" 1 ".parse::<ScanModeType>().unwrap(), ScanModeType::Legacy);
+        assert!(matches!(
+            "short_range".parse::<ScanModeType>(),
+            Err(crate::types::BjigError::InvalidParameter(_))
+        ));
+        assert!("2".parse::<ScanModeType>().is_err());
+    }
+
+    #[test]
+    fn test_common_round_trips() {
+        // Unknown fields are kept in `extra`
+        assert_round_trip::<SensorInfo>(json!({
+            "sensor_id": "0121",
+            "sensor_name": "Illuminance",
+            "capabilities": ["instant_uplink"],
+            "vendor": "MONO-ON",
+            "limits": {"max_lux": 100000}
+        }));
+        assert_round_trip::<ModuleConfig>(json!({"module_id": "2468800203400004", "sensor_id": "0121"}));
+        assert_round_trip::<ModuleConfig>(json!({
+            "module_id": "2468800203400004",
+            "sensor_id": "0121",
+            "description": "Warehouse light",
+            "location": "Dock 3",
+            "owner": {"team": "facilities"}
+        }));
+        assert_round_trip::<ScanModeType>(json!("long_range"));
+        assert_round_trip::<ScanModeType>(json!("legacy"));
+    }
+
+    #[test]
+    fn test_module_config_extra_fields() {
+        let config: ModuleConfig = serde_json::from_value(json!({
+            "module_id": "2468800203400004",
+            "sensor_id": "0121",
+            "location": "Dock 3"
+        }))
+        .unwrap();
+
+        assert_eq!(config.get("location"), Some(&json!("Dock 3")));
+        assert_eq!(config.get("module_id"), None);
+        assert_eq!(config.get("owner"), None);
+        assert_eq!(config.extra.len(), 1);
+    }
+
+    #[test]
+    fn test_sensor_info_expected_fields() {
+        let sensor: SensorInfo = serde_json::from_value(json!({
+            "sensor_id": "0121",
+            "sensor_name": "Illuminance",
+            "capabilities": ["instant_uplink", "Illuminance", "temperature"]
+        }))
+        .unwrap();
+        assert_eq!(sensor.expected_fields(), vec!["lux", "temperature"]);
+    }
+
+    #[test]
+    fn test_sensor_info_supports() {
+        let sensor: SensorInfo = serde_json::from_value(json!({
+            "sensor_id": "0121",
+            "sensor_name": "Illuminance",
+            "capabilities": ["instant_uplink", "DFU"]
+        }))
+        .unwrap();
+        assert!(sensor.supports("instant_uplink"));
+        assert!(sensor.supports("Instant_Uplink"));
+        assert!(sensor.supports("dfu"));
+        assert!(!sensor.supports("restart"));
+        assert!(!sensor.supports("instant"));
+    }
+}
@@ -1,13 +1,21 @@
 //! Error types for bjig_controller
 
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
+use super::results::ProvisionReport;
+
 /// Result type alias for bjig_controller operations
 pub type Result<T> = std::result::Result<T, BjigError>;
 
 /// Error types for bjig_controller operations
+///
+/// Some variants only exist with a cargo feature enabled (`ParquetError`
+/// with `parquet`, `Base64Decode` with `base64`), so matches outside this
+/// crate need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum BjigError {
     /// Bjig binary not found at the specified path
     #[error("Bjig binary not found: {0}")]
@@ -21,6 +29,27 @@ pub enum BjigError {
     #[error("Failed to parse JSON output: {0}")]
     JsonParseError(#[from] serde_json::Error),
 
+    /// Typed response of a command could not be deserialized
+    ///
+    /// `subcommand` is the bjig subcommand without flags (e.g.
+    /// `"router get-version"`) and `raw` the response JSON, truncated to 200
+    /// characters.
+    #[error("Failed to decode `{subcommand}` response: {source} (raw: {raw})")]
+    ResponseDecode {
+        subcommand: String,
+        raw: String,
+        source: serde_json::Error,
+    },
+
+    /// Failed to write CSV output
+    #[error("CSV error: {0}")]
+    CsvError(#[from] csv::Error),
+
+    /// Failed to write Parquet output
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    ParquetError(#[from] parquet::errors::ParquetError),
+
     /// IO error occurred during command execution
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -44,4 +73,200 @@ pub enum BjigError {
     /// File not found
     #[error("File not found: {0}")]
     FileNotFound(PathBuf),
+
+    /// USB vendor/product ID needed for port detection not configured
+    #[error("USB vendor/product ID not configured. Use .with_usb_ids() or set BJIG_CLI_USB_ID environment variable")]
+    UsbIdsNotConfigured,
+
+    /// No serial port with the requested USB IDs was found
+    #[error("No serial port found for USB device {vid:04x}:{pid:04x}. Check the router is connected or set BJIG_CLI_PORT")]
+    NoMatchingPort { vid: u16, pid: u16 },
+
+    /// Several serial ports have the requested USB IDs
+    #[error("Multiple serial ports found for USB device {vid:04x}:{pid:04x}: {}. Use .with_port() to choose one", candidates.join(", "))]
+    AmbiguousPort {
+        vid: u16,
+        pid: u16,
+        candidates: Vec<String>,
+    },
+
+    /// Command did not complete within the requested timeout
+    #[error("Command timed out after {secs} seconds")]
+    Timeout { secs: u64 },
+
+    /// Provisioning stopped at a failed step
+    ///
+    /// `report` holds every step executed so far, ending with the failed one.
+    #[error("Provisioning failed: {source}")]
+    ProvisionFailed {
+        report: ProvisionReport,
+        source: Box<BjigError>,
+    },
+
+    /// Response lacks a required field or has it with the wrong type
+    ///
+    /// `field` is empty if the response is not a JSON object. Reported
+    /// for whole responses with the `schema` feature, and by
+    /// `util::decode_base64_field` (`base64` feature) for missing binary
+    /// fields.
+    #[error("Unexpected response: {}", schema_detail(field, expected))]
+    SchemaMismatch { field: String, expected: String },
+
+    /// Uplink lacks data fields implied by its sensor's capabilities
+    ///
+    /// Reported by `RouterCommands::validate_uplink`.
+    #[error("Uplink from module {module_id} (sensor {sensor_id}) is missing fields: {}", missing.join(", "))]
+    IncompleteUplink {
+        sensor_id: String,
+        module_id: String,
+        missing: Vec<String>,
+    },
+
+    /// Binary field of a response is not valid base64
+    ///
+    /// `field` is the JSON pointer of the field. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    #[error("Invalid base64 in `{field}`: {source}")]
+    Base64Decode {
+        field: String,
+        source: base64::DecodeError,
+    },
+
+    /// Router state read back after a change does not show the change
+    ///
+    /// The command was acknowledged but not applied; the message describes
+    /// the mismatch.
+    #[error("Verification failed: {0}")]
+    VerificationFailed(String),
+
+    /// Parameter readback did not match after a transactional write
+    ///
+    /// `failed` holds JSON pointers of the parameters that did not take the
+    /// desired value. `restored` holds the pointers written back from the
+    /// snapshot taken before the write; it is empty if the restore failed.
+    #[error(
+        "Parameter verification failed for {}; restored: {}",
+        failed.join(", "),
+        if restored.is_empty() { "none".to_string() } else { restored.join(", ") }
+    )]
+    ParameterRollback {
+        failed: Vec<String>,
+        restored: Vec<String>,
+    },
+
+    /// Parameter snapshot could not be restored after a failed transactional write
+    ///
+    /// `source` is the write or readback error that triggered the restore,
+    /// and `restore` the error of the restore itself.
+    #[error("{source}; restoring parameters also failed: {restore}")]
+    ParameterRestoreFailed {
+        source: Box<BjigError>,
+        restore: Box<BjigError>,
+    },
+
+    /// No router registered under the given name in `BjigRouters`
+    #[error("Unknown router: {0}")]
+    UnknownRouter(String),
+
+    /// Controller was shut down and no longer accepts commands
+    #[error("Controller is shut down")]
+    Closed,
+}
+
+/// Serial port problem behind a failed command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortFault {
+    /// Port device does not exist (unplugged or re-enumerated under another path)
+    DeviceNotFound,
+    /// Port is held by another process
+    PortBusy,
+}
+
+/// stderr fragments (lowercase) reported when the port device is missing
+const DEVICE_NOT_FOUND_PATTERNS: &[&str] = &[
+    "no such file or directory",
+    "no such device",
+    "device not configured",
+    "device not found",
+];
+
+/// stderr fragments (lowercase) reported when the port is in use
+const PORT_BUSY_PATTERNS: &[&str] = &["resource busy", "port busy", "port is busy"];
+
+impl BjigError {
+    /// Timeout error for a wait of `duration`, rounded up to whole seconds
+    ///
+    /// Sub-second waits report `secs: 1` instead of a misleading 0.
+    pub(crate) fn timeout_after(duration: Duration) -> Self {
+        BjigError::Timeout {
+            secs: duration.as_secs() + u64::from(duration.subsec_nanos() > 0),
+        }
+    }
+
+    /// Classify a failed command as a serial port problem
+    ///
+    /// Only `CommandFailed` errors are classified, by matching bjig's stderr
+    /// against the messages of the underlying serial library. Returns
+    /// `None` for other errors and unrecognized messages.
+    pub fn port_fault(&self) -> Option<PortFault> {
+        let BjigError::CommandFailed(message) = self else {
+            return None;
+        };
+        let message = message.to_lowercase();
+
+        if DEVICE_NOT_FOUND_PATTERNS.iter().any(|p| message.contains(p)) {
+            Some(PortFault::DeviceNotFound)
+        } else if PORT_BUSY_PATTERNS.iter().any(|p| message.contains(p)) {
+            Some(PortFault::PortBusy)
+        } else {
+            None
+        }
+    }
+}
+
+/// Describe a schema mismatch for the error message
+fn schema_detail(field: &str, expected: &str) -> String {
+    if field.is_empty() {
+        format!("expected a JSON {}", expected)
+    } else {
+        let article = if expected.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" };
+        format!("field `{}` missing or not {} {}", field, article, expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_fault_classification() {
+        let failed = |stderr: &str| {
+            BjigError::CommandFailed(format!("Exit code: Some(1), stderr: {}", stderr))
+        };
+
+        assert_eq!(
+            failed("Error: could not open /dev/ttyACM0: No such file or directory").port_fault(),
+            Some(PortFault::DeviceNotFound)
+        );
+        assert_eq!(
+            failed("Error: Device or resource busy").port_fault(),
+            Some(PortFault::PortBusy)
+        );
+        assert_eq!(failed("Error: module not responding").port_fault(), None);
+        // Permission problems are not a busy port
+        assert_eq!(failed("Error: Access denied").port_fault(), None);
+        assert_eq!(BjigError::Timeout { secs: 5 }.port_fault(), None);
+    }
+
+    #[test]
+    fn test_timeout_after_rounds_up() {
+        let secs = |duration| match BjigError::timeout_after(duration) {
+            BjigError::Timeout { secs } => secs,
+            other => panic!("unexpected error: {:?}", other),
+        };
+        assert_eq!(secs(Duration::from_millis(200)), 1);
+        assert_eq!(secs(Duration::from_secs(2)), 2);
+        assert_eq!(secs(Duration::from_millis(2500)), 3);
+        assert_eq!(secs(Duration::ZERO), 0);
+    }
 }
@@ -0,0 +1,432 @@
+//! Monitor event types parsed from `bjig monitor` output
+
+use std::io::BufRead;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::Result;
+
+/// Uplink data received from a sensor module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UplinkEvent {
+    pub sensor_id: String,
+    pub module_id: String,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Parse an RSSI value encoded as an integer or a string (e.g. `-72` or `"-72"`)
+pub(crate) fn parse_rssi(value: &serde_json::Value) -> Option<i16> {
+    match value {
+        serde_json::Value::Number(n) => n.as_i64().and_then(|v| i16::try_from(v).ok()),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Field of a router notification line that holds its kind
+const NOTIFICATION_FIELD: &str = "notification";
+
+/// Kind of a router notification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum NotificationKind {
+    /// Router started scanning for modules (`scan_started`)
+    ScanStarted,
+    /// Router stopped scanning (`scan_stopped`)
+    ScanStopped,
+    /// A module joined the router (`module_joined`)
+    ModuleJoined,
+    /// A module left or timed out (`module_left`)
+    ModuleLeft,
+    /// Router reported an error (`error`)
+    Error,
+    /// Any other notification, with its raw kind
+    Other(String),
+}
+
+impl NotificationKind {
+    /// Get the kind as it appears in monitor output
+    pub fn as_str(&self) -> &str {
+        match self {
+            NotificationKind::ScanStarted => "scan_started",
+            NotificationKind::ScanStopped => "scan_stopped",
+            NotificationKind::ModuleJoined => "module_joined",
+            NotificationKind::ModuleLeft => "module_left",
+            NotificationKind::Error => "error",
+            NotificationKind::Other(kind) => kind,
+        }
+    }
+}
+
+impl From<String> for NotificationKind {
+    fn from(kind: String) -> Self {
+        match kind.as_str() {
+            "scan_started" => NotificationKind::ScanStarted,
+            "scan_stopped" => NotificationKind::ScanStopped,
+            "module_joined" => NotificationKind::ModuleJoined,
+            "module_left" => NotificationKind::ModuleLeft,
+            "error" => NotificationKind::Error,
+            _ => NotificationKind::Other(kind),
+        }
+    }
+}
+
+impl From<NotificationKind> for String {
+    fn from(kind: NotificationKind) -> Self {
+        match kind {
+            NotificationKind::Other(kind) => kind,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+/// Module joining or leaving the router, parsed from a notification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleMembership {
+    /// `module_joined` notification
+    Joined {
+        module_id: String,
+        sensor_id: Option<String>,
+    },
+    /// `module_left` notification
+    Left {
+        module_id: String,
+        sensor_id: Option<String>,
+    },
+}
+
+impl ModuleMembership {
+    /// Get the module ID
+    pub fn module_id(&self) -> &str {
+        match self {
+            ModuleMembership::Joined { module_id, .. } | ModuleMembership::Left { module_id, .. } => module_id,
+        }
+    }
+
+    /// Get the sensor ID, if the notification carried one
+    pub fn sensor_id(&self) -> Option<&str> {
+        match self {
+            ModuleMembership::Joined { sensor_id, .. } | ModuleMembership::Left { sensor_id, .. } => {
+                sensor_id.as_deref()
+            }
+        }
+    }
+}
+
+/// Event parsed from a single monitor output line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorEvent {
+    /// Sensor uplink carrying sensor and module identifiers
+    Uplink(UplinkEvent),
+    /// Router notification such as scan start or module join
+    ///
+    /// Parsed from lines carrying a `notification` field; `detail` holds the
+    /// remaining fields.
+    RouterNotification {
+        kind: NotificationKind,
+        detail: serde_json::Value,
+    },
+    /// Monitor process was restarted after an unexpected exit
+    ///
+    /// Emitted by reconnecting monitors to signal a gap in the data.
+    Reconnected { attempt: u32 },
+    /// Line that is not JSON or does not match a known event shape
+    Unknown(String),
+}
+
+impl MonitorEvent {
+    /// Parse a monitor output line
+    ///
+    /// Lines with a `notification` field are router notifications, other
+    /// objects with sensor and module IDs are uplinks. Lines that cannot be
+    /// classified are returned as `MonitorEvent::Unknown` so that no data is
+    /// lost.
+    pub fn parse(line: &str) -> Self {
+        let mut object = match serde_json::from_str(line) {
+            Ok(serde_json::Value::Object(object)) => object,
+            _ => return MonitorEvent::Unknown(line.to_string()),
+        };
+
+        // Notifications may also carry sensor/module IDs, so check them first
+        if let Some(serde_json::Value::String(kind)) = object.get(NOTIFICATION_FIELD) {
+            let kind = kind.clone().into();
+            object.remove(NOTIFICATION_FIELD);
+            return MonitorEvent::RouterNotification {
+                kind,
+                detail: serde_json::Value::Object(object),
+            };
+        }
+
+        match serde_json::from_value::<UplinkEvent>(serde_json::Value::Object(object)) {
+            Ok(uplink) => MonitorEvent::Uplink(uplink),
+            Err(_) => MonitorEvent::Unknown(line.to_string()),
+        }
+    }
+
+    /// Read and parse every line of a JSONL monitor capture
+    ///
+    /// The file is read through a buffer, one line at a time. Empty lines
+    /// are skipped; lines that are not valid UTF-8 or JSON become
+    /// `MonitorEvent::Unknown` like in `parse`. Use
+    /// `MonitorCommand::replay` to deliver events through a callback instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bjig_controller::MonitorEvent;
+    ///
+    /// let events = MonitorEvent::from_jsonl_file("capture.jsonl")?;
+    /// let uplinks = events.iter().filter(|e| e.as_uplink().is_some()).count();
+    /// println!("{} events, {} uplinks", events.len(), uplinks);
+    /// # Ok::<(), bjig_controller::BjigError>(())
+    /// ```
+    pub fn from_jsonl_file<P: AsRef<Path>>(path: P) -> Result<Vec<MonitorEvent>> {
+        let file = std::fs::File::open(path)?;
+        let mut events = Vec::new();
+
+        for line in std::io::BufReader::new(file).split(b'\n') {
+            let line = String::from_utf8_lossy(&line?).into_owned();
+            let line = line.trim();
+            if !line.is_empty() {
+                events.push(MonitorEvent::parse(line));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Get the notification kind if this is a router notification
+    pub fn notification_kind(&self) -> Option<&NotificationKind> {
+        match self {
+            MonitorEvent::RouterNotification { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+
+    /// Get the module join/leave carried by this event, if any
+    ///
+    /// Returns `None` for other notifications and for `module_joined` /
+    /// `module_left` notifications without a `module_id` field.
+    pub fn module_membership(&self) -> Option<ModuleMembership> {
+        let (kind, detail) = match self {
+            MonitorEvent::RouterNotification { kind, detail } => (kind, detail),
+            _ => return None,
+        };
+        let module_id = detail.get("module_id")?.as_str()?.to_string();
+        let sensor_id = detail.get("sensor_id").and_then(|v| v.as_str()).map(str::to_string);
+        match kind {
+            NotificationKind::ModuleJoined => Some(ModuleMembership::Joined { module_id, sensor_id }),
+            NotificationKind::ModuleLeft => Some(ModuleMembership::Left { module_id, sensor_id }),
+            _ => None,
+        }
+    }
+
+    /// Get the uplink payload if this is an uplink event
+    pub fn as_uplink(&self) -> Option<&UplinkEvent> {
+        match self {
+            MonitorEvent::Uplink(uplink) => Some(uplink),
+            _ => None,
+        }
+    }
+
+    /// Get the sensor ID of the event, if any
+    pub fn sensor_id(&self) -> Option<&str> {
+        self.as_uplink().map(|u| u.sensor_id.as_str())
+    }
+
+    /// Get the module ID of the event, if any
+    pub fn module_id(&self) -> Option<&str> {
+        self.as_uplink().map(|u| u.module_id.as_str())
+    }
+
+    /// Get the RSSI of the event, if any
+    ///
+    /// Accepts both integer and string-encoded values (e.g. `-72` or `"-72"`).
+    pub fn rssi(&self) -> Option<i16> {
+        parse_rssi(self.as_uplink()?.extra.get("rssi")?)
+    }
+
+    /// Get the timestamp of the event, if any
+    ///
+    /// The `timestamp` field of an uplink is accepted in two formats:
+    /// - ISO-8601 / RFC 3339 string, e.g. `"2024-05-01T12:34:56+09:00"`
+    /// - Unix epoch seconds as an integer, e.g. `1714534496`
+    ///
+    /// Returns `None` if the field is missing or malformed.
+    #[cfg(feature = "chrono")]
+    pub fn timestamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        use chrono::{DateTime, Utc};
+
+        match self.as_uplink()?.extra.get("timestamp")? {
+            serde_json::Value::String(s) => DateTime::parse_from_rfc3339(s.trim())
+                .ok()
+                .map(|t| t.with_timezone(&Utc)),
+            serde_json::Value::Number(n) => DateTime::from_timestamp(n.as_i64()?, 0),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_jsonl_file_mixed_validity() {
+        let mut capture = concat!(
+            r#"{"sensor_id":"0121","module_id":"A","lux":120.5}"#, "\n",
+            "\n",
+            "router ready\r\n",
+            r#"{"notification":"scan_started"}"#, "\n",
+            r#"{"sensor_id":"0121","lux":1}"#, "\n",
+        )
+        .as_bytes()
+        .to_vec();
+        capture.extend_from_slice(b"\xff\xfe garbage\n");
+        capture.extend_from_slice(br#"{"sensor_id":"0121","module_id":"B","lux":3}"#);
+        let path = crate::test_support::temp_dir().join("capture.jsonl");
+        std::fs::write(&path, capture).unwrap();
+
+        let events = MonitorEvent::from_jsonl_file(&path).unwrap();
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0].module_id(), Some("A"));
+        assert!(matches!(&events[1], MonitorEvent::Unknown(line) if line == "router ready"));
+        assert_eq!(events[2].notification_kind(), Some(&NotificationKind::ScanStarted));
+        assert!(matches!(events[3], MonitorEvent::Unknown(_)));
+        assert!(matches!(&events[4], MonitorEvent::Unknown(line) if line.ends_with("garbage")));
+        assert_eq!(events[5].module_id(), Some("B"));
+
+        assert!(MonitorEvent::from_jsonl_file(path.with_extension("missing")).is_err());
+    }
+
+    #[test]
+    fn test_parse_uplink() {
+        let event = MonitorEvent::parse(
+            r#"{"sensor_id":"0121","module_id":"2468800203400004","lux":120.5}"#,
+        );
+
+        assert_eq!(event.sensor_id(), Some("0121"));
+        assert_eq!(event.module_id(), Some("2468800203400004"));
+        let uplink = event.as_uplink().unwrap();
+        assert_eq!(uplink.extra["lux"], serde_json::json!(120.5));
+    }
+
+    #[test]
+    fn test_parse_unknown() {
+        let event = MonitorEvent::parse("router started");
+        assert!(matches!(event, MonitorEvent::Unknown(ref s) if s == "router started"));
+        assert_eq!(event.sensor_id(), None);
+
+        let event = MonitorEvent::parse(r#"{"type":"notice"}"#);
+        assert!(matches!(event, MonitorEvent::Unknown(_)));
+    }
+
+    #[test]
+    fn test_parse_router_notifications() {
+        let cases = [
+            (r#"{"notification":"scan_started","mode":1}"#, NotificationKind::ScanStarted),
+            (r#"{"notification":"scan_stopped"}"#, NotificationKind::ScanStopped),
+            (
+                r#"{"notification":"module_joined","module_id":"2468800203400004","sensor_id":"0121"}"#,
+                NotificationKind::ModuleJoined,
+            ),
+            (r#"{"notification":"module_left","module_id":"2468800203400004"}"#, NotificationKind::ModuleLeft),
+            (r#"{"notification":"error","code":12,"message":"buffer overflow"}"#, NotificationKind::Error),
+            (r#"{"notification":"firmware_ready"}"#, NotificationKind::Other("firmware_ready".to_string())),
+        ];
+
+        for (line, expected) in &cases {
+            let event = MonitorEvent::parse(line);
+            assert_eq!(event.notification_kind(), Some(expected), "{}", line);
+            assert!(event.as_uplink().is_none());
+        }
+
+        match MonitorEvent::parse(cases[2].0) {
+            MonitorEvent::RouterNotification { detail, .. } => {
+                assert_eq!(
+                    detail,
+                    serde_json::json!({"module_id": "2468800203400004", "sensor_id": "0121"})
+                );
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+
+        assert_eq!(
+            MonitorEvent::parse(cases[2].0).module_membership(),
+            Some(ModuleMembership::Joined {
+                module_id: "2468800203400004".to_string(),
+                sensor_id: Some("0121".to_string()),
+            })
+        );
+        let left = MonitorEvent::parse(cases[3].0).module_membership().unwrap();
+        assert!(matches!(left, ModuleMembership::Left { .. }));
+        assert_eq!((left.module_id(), left.sensor_id()), ("2468800203400004", None));
+        assert_eq!(MonitorEvent::parse(cases[0].0).module_membership(), None);
+
+        // A non-string kind is not a notification
+        assert!(matches!(MonitorEvent::parse(r#"{"notification":5}"#), MonitorEvent::Unknown(_)));
+    }
+
+    #[test]
+    fn test_parse_rssi() {
+        let event = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","rssi":-72}"#);
+        assert_eq!(event.rssi(), Some(-72));
+
+        let event = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","rssi":"-65"}"#);
+        assert_eq!(event.rssi(), Some(-65));
+
+        let event = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","rssi":"n/a"}"#);
+        assert_eq!(event.rssi(), None);
+        assert_eq!(MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A"}"#).rssi(), None);
+        assert_eq!(MonitorEvent::parse("not json").rssi(), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_parse_timestamp() {
+        use chrono::{TimeZone, Utc};
+
+        let expected = Utc.with_ymd_and_hms(2024, 5, 1, 3, 34, 56).unwrap();
+        let uplink = |ts: &str| {
+            MonitorEvent::parse(&format!(r#"{{"sensor_id":"0121","module_id":"A","timestamp":{}}}"#, ts))
+        };
+
+        assert_eq!(uplink(r#""2024-05-01T12:34:56+09:00""#).timestamp(), Some(expected));
+        assert_eq!(uplink(r#""2024-05-01T03:34:56Z""#).timestamp(), Some(expected));
+        assert_eq!(uplink("1714534496").timestamp(), Some(expected));
+
+        assert_eq!(uplink(r#""yesterday""#).timestamp(), None);
+        assert_eq!(uplink("1714534496.5").timestamp(), None);
+        assert_eq!(uplink("null").timestamp(), None);
+        assert_eq!(MonitorEvent::parse("not json").timestamp(), None);
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let events = [
+            MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","lux":1.5,"meta":{"seq":7}}"#),
+            MonitorEvent::Reconnected { attempt: 2 },
+            MonitorEvent::Unknown("router started".to_string()),
+            MonitorEvent::parse(r#"{"notification":"module_joined","module_id":"A"}"#),
+            MonitorEvent::parse(r#"{"notification":"firmware_ready"}"#),
+        ];
+
+        for event in events {
+            let json = serde_json::to_value(&event).unwrap();
+            let parsed: MonitorEvent = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+        }
+
+        let json = serde_json::to_value(MonitorEvent::Reconnected { attempt: 2 }).unwrap();
+        assert_eq!(json, serde_json::json!({"reconnected": {"attempt": 2}}));
+
+        // Extra uplink fields survive the round trip
+        let event = MonitorEvent::parse(r#"{"sensor_id":"0121","module_id":"A","meta":{"seq":7}}"#);
+        let json = serde_json::to_string(&event).unwrap();
+        let parsed: MonitorEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.as_uplink().unwrap().extra["meta"]["seq"], 7);
+    }
+}
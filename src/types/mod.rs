@@ -3,7 +3,16 @@
 pub mod error;
 pub mod results;
 pub mod common;
+pub mod events;
+pub mod parameters;
+pub mod schema;
+pub mod sensors;
 
-pub use error::{BjigError, Result};
+pub use error::{BjigError, PortFault, Result};
 pub use results::*;
 pub use common::*;
+pub use events::*;
+pub use parameters::*;
+pub use sensors::*;
+pub use schema::{JsonType, ResponseSchema};
+pub(crate) use schema::{decode, deserialize};
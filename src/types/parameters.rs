@@ -0,0 +1,207 @@
+//! Module parameter types
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Parameters of an illuminance sensor module (sensor ID 0121)
+///
+/// Only `interval`, the parameter used throughout bjig's examples, is
+/// typed; the full per-sensor parameter schema is not published, so every
+/// other parameter is kept in `extra` under the name bjig reports. All
+/// fields are optional so that partial responses still deserialize.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IlluminanceParameters {
+    /// Uplink interval in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u32>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// Parameters of a dry contact sensor module (sensor ID 0126)
+///
+/// Typed like `IlluminanceParameters`: only `interval` has a field, and
+/// every other parameter is kept in `extra`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DryContactParameters {
+    /// Uplink interval in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u32>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// A parameter whose current value differs from the desired value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterChange {
+    /// JSON pointer to the parameter (e.g. "/interval" or "/threshold/high")
+    pub key: String,
+    pub current: Value,
+    pub desired: Value,
+}
+
+/// Difference between desired and current module parameters
+///
+/// Keys are JSON pointers so nested parameters can be addressed
+/// individually. Parameters present on the module but absent from the
+/// desired set are not reported, since they are not part of the write.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterDiff {
+    /// Parameters present on the module with a different value
+    pub changed: Vec<ParameterChange>,
+    /// Desired parameters not present on the module
+    pub added: Vec<(String, Value)>,
+    /// Desired parameters that already have the desired value
+    pub unchanged: Vec<String>,
+}
+
+impl ParameterDiff {
+    /// Compare current parameters against desired ones
+    pub fn compute(current: &Value, desired: &Value) -> Self {
+        let mut diff = Self::default();
+        diff.compare("", current, desired);
+        diff
+    }
+
+    /// Returns `true` if applying `desired` would not change anything
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty()
+    }
+
+    fn compare(&mut self, path: &str, current: &Value, desired: &Value) {
+        match (current, desired) {
+            (Value::Object(cur), Value::Object(des)) => {
+                for (key, des_value) in des {
+                    let child = format!("{}/{}", path, escape_pointer(key));
+                    match cur.get(key) {
+                        Some(cur_value) => self.compare(&child, cur_value, des_value),
+                        None => self.added.push((child, des_value.clone())),
+                    }
+                }
+            }
+            _ if values_equal(current, desired) => self.unchanged.push(path.to_string()),
+            _ => self.changed.push(ParameterChange {
+                key: path.to_string(),
+                current: current.clone(),
+                desired: desired.clone(),
+            }),
+        }
+    }
+}
+
+/// Compare JSON values, treating numbers as equal if numerically equal
+///
+/// Firmware may report `60.0` for a parameter written as `60`, which must not
+/// count as a change.
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x == y || x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(a, b)| values_equal(a, b))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter().all(|(k, a)| y.get(k).is_some_and(|b| values_equal(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Escape a key for use as a JSON pointer segment (RFC 6901)
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_illuminance_parameters() {
+        let params: IlluminanceParameters = serde_json::from_value(json!({
+            "interval": 60,
+            "firmware": "1.0.3"
+        }))
+        .unwrap();
+
+        assert_eq!(params.interval, Some(60));
+        assert_eq!(params.extra["firmware"], "1.0.3");
+    }
+
+    #[test]
+    fn test_dry_contact_parameters() {
+        let params: DryContactParameters = serde_json::from_value(json!({"mode": 2})).unwrap();
+        assert_eq!(params.interval, None);
+        assert_eq!(params.extra["mode"], 2);
+
+        let empty: DryContactParameters = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(empty, DryContactParameters::default());
+        crate::test_support::assert_round_trip::<DryContactParameters>(json!({"interval": 30, "mode": 2}));
+    }
+
+    #[test]
+    fn test_diff_nested() {
+        let current = json!({
+            "interval": 60,
+            "mode": "auto",
+            "threshold": {"high": 100, "low": 10},
+            "extra": true
+        });
+        let desired = json!({
+            "interval": 120,
+            "mode": "auto",
+            "threshold": {"high": 100, "low": 5, "hysteresis": 2}
+        });
+
+        let diff = ParameterDiff::compute(&current, &desired);
+
+        assert_eq!(
+            diff.changed,
+            vec![
+                ParameterChange {
+                    key: "/interval".to_string(),
+                    current: json!(60),
+                    desired: json!(120),
+                },
+                ParameterChange {
+                    key: "/threshold/low".to_string(),
+                    current: json!(10),
+                    desired: json!(5),
+                },
+            ]
+        );
+        assert_eq!(diff.added, vec![("/threshold/hysteresis".to_string(), json!(2))]);
+        assert_eq!(diff.unchanged, vec!["/mode", "/threshold/high"]);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_diff_numeric_tolerance() {
+        let current = json!({"interval": 60.0, "levels": [1.0, 2]});
+        let desired = json!({"interval": 60, "levels": [1, 2.0]});
+        assert!(ParameterDiff::compute(&current, &desired).is_empty());
+
+        let desired = json!({"interval": 60.5});
+        assert!(!ParameterDiff::compute(&current, &desired).is_empty());
+    }
+
+    #[test]
+    fn test_diff_round_trip() {
+        crate::test_support::assert_round_trip::<ParameterDiff>(json!({
+            "changed": [{"key": "/interval", "current": 60, "desired": 120}],
+            "added": [["/threshold", 5]],
+            "unchanged": ["/mode"]
+        }));
+    }
+
+    #[test]
+    fn test_diff_empty() {
+        let params = json!({"interval": 60, "threshold": {"high": 100}});
+        let diff = ParameterDiff::compute(&params, &params);
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged.len(), 2);
+    }
+}
@@ -2,6 +2,41 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::common::{ModuleConfig, ScanModeType};
+use super::error::{BjigError, Result};
+
+/// Deserialize a `{result, message}` status response
+///
+/// `Null` (a successful command with empty stdout, see
+/// `CommandExecutor::execute_status`) is treated as
+/// `{"result": "success", "message": <empty_message>}`.
+pub(crate) fn status_from_json<T: super::schema::ResponseSchema + serde::de::DeserializeOwned>(
+    subcommand: &str,
+    json: serde_json::Value,
+    empty_message: &str,
+) -> Result<T> {
+    let json = match json {
+        serde_json::Value::Null => serde_json::json!({"result": "success", "message": empty_message}),
+        other => other,
+    };
+    super::schema::decode(subcommand, json)
+}
+
+/// Unparsed output of a bjig invocation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandOutput {
+    /// Exit code (`None` if bjig was terminated by a signal)
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    pub fn is_success(&self) -> bool {
+        self.status == Some(0)
+    }
+}
+
 /// Router start result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StartResult {
@@ -15,6 +50,33 @@ impl StartResult {
     }
 }
 
+/// Instant uplink response
+///
+/// Common envelope fields are parsed; all sensor-specific fields are kept
+/// in `data`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UplinkResult {
+    pub sensor_id: String,
+    pub module_id: String,
+    /// Signal strength in dBm (integer or string-encoded in the response)
+    #[serde(default, deserialize_with = "deserialize_rssi", skip_serializing_if = "Option::is_none")]
+    pub rssi: Option<i16>,
+    /// Timestamp as reported by the router
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<serde_json::Value>,
+    /// Sensor-specific fields
+    #[serde(flatten)]
+    pub data: serde_json::Map<String, serde_json::Value>,
+}
+
+fn deserialize_rssi<'de, D>(deserializer: D) -> std::result::Result<Option<i16>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(super::events::parse_rssi))
+}
+
 /// Router stop result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StopResult {
@@ -35,6 +97,82 @@ pub struct Version {
     pub minor: u8,
     pub build: u8,
     pub version: String,
+    /// Git commit the firmware was built from, if reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_hash: Option<String>,
+    /// Firmware build date as reported by the router, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_date: Option<String>,
+}
+
+impl std::str::FromStr for Version {
+    type Err = BjigError;
+
+    /// Parse `major.minor.build`, ignoring a `+metadata` suffix
+    ///
+    /// `version` holds the string without the suffix; `git_hash` and
+    /// `build_date` are left empty since the suffix format is not fixed.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || BjigError::InvalidParameter(format!("Invalid version: {}", s));
+
+        let core = s.trim().split('+').next().unwrap_or_default();
+        let mut parts = core.split('.').map(|part| part.parse::<u8>());
+        let (major, minor, build) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor)), Some(Ok(build)), None) => (major, minor, build),
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self {
+            major,
+            minor,
+            build,
+            version: core.to_string(),
+            git_hash: None,
+            build_date: None,
+        })
+    }
+}
+
+/// Versions compare by `major.minor.build` only; the version string and build
+/// metadata are ignored.
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.major, self.minor, self.build).cmp(&(other.major, other.minor, other.build))
+    }
+}
+
+/// Router firmware compared against a required minimum version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FirmwareStatus {
+    /// Version reported by the router
+    pub current: Version,
+    /// Whether `current` is at least the required minimum
+    pub meets_minimum: bool,
+    /// Whether the router should be updated (the inverse of `meets_minimum`)
+    pub needs_update: bool,
+}
+
+/// Router running status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouterStatus {
+    pub running: bool,
+    /// Router uptime in seconds, if known
+    #[serde(default)]
+    pub uptime_secs: Option<u64>,
 }
 
 /// Scan mode information
@@ -44,11 +182,21 @@ pub struct ScanMode {
     pub mode_name: String,
 }
 
+impl ScanMode {
+    /// Get the mode as `ScanModeType`, or `None` for an unrecognized value
+    pub fn as_type(&self) -> Option<ScanModeType> {
+        ScanModeType::from_u8(self.mode)
+    }
+}
+
 /// Set scan mode result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetScanModeResult {
     pub result: String,
     pub message: String,
+    /// Applied scan mode, if reported by bjig or confirmed by readback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u8>,
 }
 
 impl SetScanModeResult {
@@ -64,6 +212,46 @@ pub struct ModuleIdList {
     pub modules: Vec<String>,
 }
 
+/// Router state collected by `RouterCommands::snapshot`
+///
+/// Each field holds the outcome of its own command, so one failing query
+/// does not hide the others.
+#[derive(Debug)]
+pub struct RouterSnapshot {
+    pub version: Result<Version>,
+    pub scan_mode: Result<ScanMode>,
+    pub modules: Result<ModuleIdList>,
+}
+
+impl RouterSnapshot {
+    /// Returns `true` if all three queries succeeded
+    pub fn is_complete(&self) -> bool {
+        self.version.is_ok() && self.scan_mode.is_ok() && self.modules.is_ok()
+    }
+}
+
+/// Module registration changes computed by `RouterCommands::config_plan`
+///
+/// Module IDs are compared case-insensitively; IDs are listed as they
+/// appear in the config (`to_add`, `unchanged`) or on the router
+/// (`to_remove`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigPlan {
+    /// Config entries not registered on the router
+    pub to_add: Vec<ModuleConfig>,
+    /// Registered module IDs missing from the config
+    pub to_remove: Vec<String>,
+    /// Module IDs both in the config and registered on the router
+    pub unchanged: Vec<String>,
+}
+
+impl ConfigPlan {
+    /// Returns `true` if the router already matches the config
+    pub fn is_empty(&self) -> bool {
+        self.to_add.is_empty() && self.to_remove.is_empty()
+    }
+}
+
 /// Remove module ID result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoveResult {
@@ -82,19 +270,32 @@ impl RemoveResult {
 pub struct KeepAliveResult {
     pub result: String,
     pub message: String,
+    /// Time set on the router by the keep-alive, in Unix epoch seconds
+    ///
+    /// `None` for firmware that does not report it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub synced_epoch: Option<u64>,
 }
 
 impl KeepAliveResult {
     pub fn is_success(&self) -> bool {
         self.result == "success"
     }
+
+    /// Get the time set on the router, if reported
+    pub fn synced_time(&self) -> Option<std::time::SystemTime> {
+        self.synced_epoch
+            .map(|secs| std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+    }
 }
 
 /// DFU (firmware update) result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DfuResult {
     pub result: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
@@ -113,6 +314,42 @@ pub struct DfuProgress {
     pub percentage: u8,
 }
 
+/// Item of a DFU progress stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DfuUpdate {
+    /// Intermediate progress report
+    Progress(DfuProgress),
+    /// Final result; always the last item of the stream
+    Finished(DfuResult),
+}
+
+/// Final state of a DFU started with `ModuleCommands::dfu_with_abort`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DfuOutcome {
+    /// DFU ran to completion (successfully or not) before any abort
+    Completed(DfuResult),
+    /// DFU was aborted before it finished
+    ///
+    /// The module's state after an interrupted transfer is unknown. To
+    /// recover, run `dfu` again; if the module stays in its bootloader and
+    /// does not answer, power-cycle it first.
+    Aborted {
+        /// The transfer process was killed rather than cancelled cleanly
+        ///
+        /// Always `true` while bjig has no command to cancel a running DFU.
+        forced: bool,
+    },
+}
+
+impl DfuOutcome {
+    /// Check if the DFU was aborted
+    pub fn is_aborted(&self) -> bool {
+        matches!(self, Self::Aborted { .. })
+    }
+}
+
 /// Set parameter result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetParameterResult {
@@ -144,10 +381,244 @@ impl RestartResult {
 pub struct ControlResult {
     pub result: String,
     pub message: String,
+    /// Rejection reason reported by the module, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<u32>,
 }
 
 impl ControlResult {
     pub fn is_success(&self) -> bool {
         self.result == "success"
     }
+
+    /// Classify the result for branching on rejection reasons
+    pub fn outcome(&self) -> ControlOutcome {
+        match self.error_code {
+            _ if self.is_success() => ControlOutcome::Success,
+            Some(code) => ControlOutcome::Rejected(code),
+            None => ControlOutcome::Unknown,
+        }
+    }
+}
+
+/// Outcome of a module control command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlOutcome {
+    /// Command was accepted
+    Success,
+    /// Module rejected the command with an error code
+    Rejected(u32),
+    /// Command failed without an error code
+    Unknown,
+}
+
+/// Step of `RouterCommands::provision` or `ModuleCommands::provision`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisionStepKind {
+    SetScanMode,
+    ClearModules,
+    Settle,
+    VerifyScanMode,
+    VerifyModulesCleared,
+    SetParameter,
+    RestartModule,
+    VerifyParameters,
+}
+
+/// Outcome of one provisioning step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisionStep {
+    pub kind: ProvisionStepKind,
+    pub success: bool,
+    pub message: String,
+}
+
+/// Steps executed by `RouterCommands::provision` or `ModuleCommands::provision`, in order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProvisionReport {
+    pub steps: Vec<ProvisionStep>,
+    /// Parameter readback compared against the requested values (module
+    /// provisioning only): `unchanged` lists the applied fields, `changed`
+    /// and `added` the mismatched ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<super::parameters::ParameterDiff>,
+}
+
+impl ProvisionReport {
+    /// Check whether every executed step succeeded
+    pub fn is_success(&self) -> bool {
+        self.steps.iter().all(|step| step.success)
+    }
+}
+
+/// Diagnostic summary produced by `BjigController::health_check`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// bjig binary responded to `--version`
+    pub binary_ok: bool,
+    /// Serial port is configured and could be opened
+    pub port_ok: bool,
+    /// Router firmware version, if the router responded
+    pub router_version: Option<Version>,
+    /// Description of every failed check
+    pub problems: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::assert_round_trip;
+    use serde_json::json;
+
+    #[test]
+    fn test_scan_mode_as_type() {
+        let mode = |mode| ScanMode { mode, mode_name: String::new() };
+        assert_eq!(mode(0).as_type(), Some(ScanModeType::LongRange));
+        assert_eq!(mode(1).as_type(), Some(ScanModeType::Legacy));
+        assert_eq!(mode(7).as_type(), None);
+    }
+
+    #[test]
+    fn test_uplink_result_envelopes() {
+        let illuminance: UplinkResult = serde_json::from_value(json!({
+            "sensor_id": "0121",
+            "module_id": "2468800203400004",
+            "rssi": -68,
+            "timestamp": "2024-05-01T12:34:56+09:00",
+            "lux": 120.5
+        }))
+        .unwrap();
+        assert_eq!(illuminance.sensor_id, "0121");
+        assert_eq!(illuminance.rssi, Some(-68));
+        assert_eq!(illuminance.timestamp, Some(json!("2024-05-01T12:34:56+09:00")));
+        assert_eq!(illuminance.data.len(), 1);
+        assert_eq!(illuminance.data["lux"], 120.5);
+
+        let dry_contact: UplinkResult = serde_json::from_value(json!({
+            "sensor_id": "0126",
+            "module_id": "AAAA000000000001",
+            "rssi": "-80",
+            "timestamp": 1714534496,
+            "contact": {"ch1": "open", "ch2": "closed"}
+        }))
+        .unwrap();
+        assert_eq!(dry_contact.rssi, Some(-80));
+        assert_eq!(dry_contact.timestamp, Some(json!(1714534496)));
+        assert_eq!(dry_contact.data["contact"]["ch2"], "closed");
+
+        let minimal: UplinkResult =
+            serde_json::from_value(json!({"sensor_id": "0121", "module_id": "A"})).unwrap();
+        assert_eq!((minimal.rssi, minimal.timestamp), (None, None));
+        assert_round_trip::<UplinkResult>(json!({"sensor_id": "0121", "module_id": "A", "rssi": -50, "lux": 3}));
+    }
+
+    #[test]
+    fn test_control_outcome() {
+        let parse = |value| serde_json::from_value::<ControlResult>(value).unwrap();
+
+        let success = parse(json!({"result": "success", "message": "ok"}));
+        assert_eq!(success.error_code, None);
+        assert_eq!(success.outcome(), ControlOutcome::Success);
+
+        let rejected = parse(json!({"result": "error", "message": "busy", "error_code": 3}));
+        assert_eq!(rejected.outcome(), ControlOutcome::Rejected(3));
+
+        let unknown = parse(json!({"result": "error", "message": "failed"}));
+        assert_eq!(unknown.outcome(), ControlOutcome::Unknown);
+
+        assert_round_trip::<ControlResult>(json!({"result": "error", "message": "busy", "error_code": 3}));
+    }
+
+    #[test]
+    fn test_version_build_metadata() {
+        let plain: Version =
+            serde_json::from_value(json!({"major": 1, "minor": 2, "build": 3, "version": "1.2.3"})).unwrap();
+        assert_eq!((plain.git_hash, plain.build_date), (None, None));
+
+        let full: Version = serde_json::from_value(json!({
+            "major": 1, "minor": 2, "build": 3, "version": "1.2.3+a1b2c3d",
+            "git_hash": "a1b2c3d", "build_date": "2024-05-01"
+        }))
+        .unwrap();
+        assert_eq!(full.git_hash.as_deref(), Some("a1b2c3d"));
+        assert_eq!(full.build_date.as_deref(), Some("2024-05-01"));
+
+        let parsed: Version = "1.2.3".parse().unwrap();
+        assert_eq!((parsed.major, parsed.minor, parsed.build), (1, 2, 3));
+        assert_eq!(parsed.version, "1.2.3");
+
+        let parsed: Version = "1.2.3+a1b2c3d.20240501".parse().unwrap();
+        assert_eq!((parsed.major, parsed.minor, parsed.build), (1, 2, 3));
+        assert_eq!(parsed.version, "1.2.3");
+
+        for invalid in ["", "1.2", "1.2.3.4", "1.x.3", "+meta", "1.2.300"] {
+            assert!(invalid.parse::<Version>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        let v = |s: &str| s.parse::<Version>().unwrap();
+
+        assert!(v("1.2.3") < v("1.2.4"));
+        assert!(v("1.2.10") > v("1.2.9"));
+        assert!(v("1.10.0") > v("1.9.9"));
+        assert!(v("2.0.0") > v("1.255.255"));
+        assert_eq!(v("1.2.3"), v("1.2.3+a1b2c3d"));
+        assert_eq!(v("1.2.3").max(v("1.3.0")), v("1.3.0"));
+    }
+
+    #[test]
+    fn test_keep_alive_synced_time() {
+        let result: KeepAliveResult = serde_json::from_value(json!({
+            "result": "success",
+            "message": "Keep-alive sent",
+            "synced_epoch": 1714534496
+        }))
+        .unwrap();
+        assert_eq!(result.synced_epoch, Some(1714534496));
+        assert_eq!(
+            result.synced_time(),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1714534496))
+        );
+
+        let result: KeepAliveResult =
+            serde_json::from_value(json!({"result": "success", "message": "Keep-alive sent"})).unwrap();
+        assert!(result.is_success());
+        assert_eq!(result.synced_epoch, None);
+        assert_eq!(result.synced_time(), None);
+    }
+
+    #[test]
+    fn test_result_round_trips() {
+        let status = json!({"result": "success", "message": "ok"});
+        assert_round_trip::<StartResult>(status.clone());
+        assert_round_trip::<StopResult>(status.clone());
+        assert_round_trip::<SetScanModeResult>(status.clone());
+        assert_round_trip::<SetScanModeResult>(json!({"result": "success", "message": "ok", "mode": 1}));
+        assert_round_trip::<RemoveResult>(status.clone());
+        assert_round_trip::<KeepAliveResult>(status.clone());
+        assert_round_trip::<KeepAliveResult>(json!({"result": "success", "message": "ok", "synced_epoch": 1714534496}));
+        assert_round_trip::<SetParameterResult>(status.clone());
+        assert_round_trip::<RestartResult>(status.clone());
+        assert_round_trip::<ControlResult>(status);
+
+        assert_round_trip::<Version>(json!({"major": 1, "minor": 2, "build": 3, "version": "1.2.3"}));
+        assert_round_trip::<Version>(json!({"major": 1, "minor": 2, "build": 3, "version": "1.2.3", "git_hash": "a1b2c3d", "build_date": "2024-05-01"}));
+        assert_round_trip::<RouterStatus>(json!({"running": true, "uptime_secs": 3600}));
+        assert_round_trip::<ScanMode>(json!({"mode": 0, "mode_name": "LongRange"}));
+        assert_round_trip::<ModuleIdList>(json!({"module_count": 1, "modules": ["2468800203400004"]}));
+        assert_round_trip::<DfuResult>(json!({"result": "success", "message": "done"}));
+        assert_round_trip::<DfuResult>(json!({"result": "error", "error": "checksum mismatch"}));
+        assert_round_trip::<DfuProgress>(json!({"phase": "transfer", "chunk_number": 3, "total_chunks": 10, "percentage": 30}));
+        assert_round_trip::<DfuUpdate>(json!({"finished": {"result": "success", "message": "done"}}));
+        assert_round_trip::<HealthReport>(json!({"binary_ok": true, "port_ok": false, "router_version": null, "problems": ["port"]}));
+    }
 }
@@ -0,0 +1,216 @@
+//! Expected shapes of typed command responses
+//!
+//! With the `schema` feature enabled, responses are checked against the
+//! required top-level fields of their typed result before deserializing,
+//! so a firmware that changed its JSON output fails with
+//! `BjigError::SchemaMismatch` naming the offending field instead of a
+//! terse serde message.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::error::{BjigError, Result};
+use super::results::*;
+
+/// JSON type of a response field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonType {
+    String,
+    Number,
+    Bool,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    /// Check whether `value` has this type
+    pub fn matches(self, value: &Value) -> bool {
+        match self {
+            JsonType::String => value.is_string(),
+            JsonType::Number => value.is_number(),
+            JsonType::Bool => value.is_boolean(),
+            JsonType::Array => value.is_array(),
+            JsonType::Object => value.is_object(),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JsonType::String => "string",
+            JsonType::Number => "number",
+            JsonType::Bool => "bool",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        }
+    }
+}
+
+/// Required top-level fields of a typed response
+pub trait ResponseSchema {
+    /// Field names and their expected JSON types
+    const REQUIRED: &'static [(&'static str, JsonType)];
+
+    /// Check that `json` is an object holding every required field
+    ///
+    /// # Errors
+    /// Returns `BjigError::SchemaMismatch` for the first missing or
+    /// mistyped field (`field` is empty if `json` is not an object).
+    fn validate(json: &Value) -> Result<()> {
+        let object = json.as_object().ok_or_else(|| BjigError::SchemaMismatch {
+            field: String::new(),
+            expected: JsonType::Object.as_str().to_string(),
+        })?;
+
+        for (field, expected) in Self::REQUIRED {
+            if !object.get(*field).is_some_and(|value| expected.matches(value)) {
+                return Err(BjigError::SchemaMismatch {
+                    field: field.to_string(),
+                    expected: expected.as_str().to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Maximum number of characters of the raw response kept in `BjigError::ResponseDecode`
+const RAW_SNIPPET_LEN: usize = 200;
+
+/// Deserialize a typed response, validating it first with the `schema` feature
+pub(crate) fn decode<T: ResponseSchema + DeserializeOwned>(subcommand: &str, json: Value) -> Result<T> {
+    #[cfg(feature = "schema")]
+    T::validate(&json).inspect_err(|e| log::error!("{} in response: {}", e, json))?;
+
+    deserialize(subcommand, json)
+}
+
+/// Deserialize the response of `subcommand`, keeping the raw JSON on failure
+///
+/// # Errors
+/// Returns `BjigError::ResponseDecode` naming the subcommand (e.g.
+/// `"router get-version"`) with the response truncated to
+/// `RAW_SNIPPET_LEN` characters.
+pub(crate) fn deserialize<T: DeserializeOwned>(subcommand: &str, json: Value) -> Result<T> {
+    T::deserialize(&json).map_err(|source| {
+        let mut raw = json.to_string();
+        if let Some((end, _)) = raw.char_indices().nth(RAW_SNIPPET_LEN) {
+            raw.truncate(end);
+            raw.push_str("...");
+        }
+        log::error!("Failed to decode {} response: {}", subcommand, source);
+        BjigError::ResponseDecode {
+            subcommand: subcommand.to_string(),
+            raw,
+            source,
+        }
+    })
+}
+
+/// Fields of every `{result, message}` status response
+const STATUS: &[(&str, JsonType)] = &[("result", JsonType::String), ("message", JsonType::String)];
+
+impl ResponseSchema for StartResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = STATUS;
+}
+
+impl ResponseSchema for StopResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = STATUS;
+}
+
+impl ResponseSchema for SetScanModeResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = STATUS;
+}
+
+impl ResponseSchema for RemoveResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = STATUS;
+}
+
+impl ResponseSchema for KeepAliveResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = STATUS;
+}
+
+impl ResponseSchema for SetParameterResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = STATUS;
+}
+
+impl ResponseSchema for RestartResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = STATUS;
+}
+
+impl ResponseSchema for ControlResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = STATUS;
+}
+
+impl ResponseSchema for DfuResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] = &[("result", JsonType::String)];
+}
+
+impl ResponseSchema for Version {
+    const REQUIRED: &'static [(&'static str, JsonType)] = &[
+        ("major", JsonType::Number),
+        ("minor", JsonType::Number),
+        ("build", JsonType::Number),
+        ("version", JsonType::String),
+    ];
+}
+
+impl ResponseSchema for ScanMode {
+    const REQUIRED: &'static [(&'static str, JsonType)] =
+        &[("mode", JsonType::Number), ("mode_name", JsonType::String)];
+}
+
+impl ResponseSchema for ModuleIdList {
+    const REQUIRED: &'static [(&'static str, JsonType)] =
+        &[("module_count", JsonType::Number), ("modules", JsonType::Array)];
+}
+
+impl ResponseSchema for UplinkResult {
+    const REQUIRED: &'static [(&'static str, JsonType)] =
+        &[("sensor_id", JsonType::String), ("module_id", JsonType::String)];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_required_fields() {
+        assert!(Version::validate(&json!({"major": 1, "minor": 2, "build": 3, "version": "1.2.3"})).is_ok());
+
+        match Version::validate(&json!({"major": 1, "minor": 2, "version": "1.2.3"})) {
+            Err(BjigError::SchemaMismatch { field, expected }) => {
+                assert_eq!((field.as_str(), expected.as_str()), ("build", "number"));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+        match StopResult::validate(&json!({"result": "success", "message": 3})) {
+            Err(BjigError::SchemaMismatch { field, expected }) => {
+                assert_eq!((field.as_str(), expected.as_str()), ("message", "string"));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert!(matches!(
+            ModuleIdList::validate(&json!([])),
+            Err(BjigError::SchemaMismatch { ref field, .. }) if field.is_empty()
+        ));
+    }
+
+    #[cfg(feature = "schema")]
+    #[tokio::test]
+    async fn test_missing_field_reports_schema_mismatch() {
+        use crate::runner::MockRunner;
+        use crate::BjigController;
+
+        let mock = MockRunner::new().on(&["router", "get-scan-mode"], r#"{"mode_name":"LongRange"}"#);
+        let bjig = BjigController::builder()
+            .port("/dev/null")
+            .runner(mock)
+            .build()
+            .unwrap();
+
+        let err = bjig.router().get_scan_mode().await.unwrap_err();
+        assert!(matches!(err, BjigError::SchemaMismatch { ref field, .. } if field == "mode"));
+        assert_eq!(err.to_string(), "Unexpected response: field `mode` missing or not a number");
+    }
+}
@@ -0,0 +1,93 @@
+//! Illuminance sensor (sensor ID 0121) uplinks
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::error::{BjigError, Result};
+use crate::types::results::UplinkResult;
+
+/// Uplink payload of an illuminance sensor module (sensor ID 0121)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IlluminanceUplink {
+    /// Measured illuminance in lux
+    pub lux: f32,
+    /// Battery voltage in millivolts, if reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery_mv: Option<u16>,
+    /// Unix time (seconds) the value was sampled at, if reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampled_at: Option<u64>,
+}
+
+impl IlluminanceUplink {
+    /// Sensor ID of illuminance modules
+    pub const SENSOR_ID: &'static str = "0121";
+
+    /// Parse the sensor-specific fields of an uplink
+    ///
+    /// # Errors
+    /// Returns `BjigError::InvalidParameter` if the uplink is not from an
+    /// illuminance sensor, or `BjigError::ResponseDecode` if its fields do
+    /// not match the 0121 payload.
+    pub fn from_uplink(uplink: &UplinkResult) -> Result<Self> {
+        if uplink.sensor_id != Self::SENSOR_ID {
+            return Err(BjigError::InvalidParameter(format!(
+                "Expected an uplink from sensor {}, got sensor {}",
+                Self::SENSOR_ID,
+                uplink.sensor_id
+            )));
+        }
+
+        crate::types::deserialize(
+            "module instant-uplink",
+            serde_json::Value::Object(uplink.data.clone()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::assert_round_trip;
+    use serde_json::json;
+
+    /// Instant uplink response captured from a 0121 module
+    const SAMPLE: &str = r#"{"sensor_id":"0121","module_id":"2468800203400004","rssi":-62,"timestamp":"2024-05-01T09:00:00","lux":523.4,"battery_mv":3012,"sampled_at":1714554000}"#;
+
+    #[test]
+    fn test_illuminance_uplink() {
+        let uplink: UplinkResult = serde_json::from_str(SAMPLE).unwrap();
+        let illuminance = IlluminanceUplink::from_uplink(&uplink).unwrap();
+        assert_eq!(
+            illuminance,
+            IlluminanceUplink {
+                lux: 523.4,
+                battery_mv: Some(3012),
+                sampled_at: Some(1714554000),
+            }
+        );
+
+        let uplink: UplinkResult =
+            serde_json::from_value(json!({"sensor_id": "0121", "module_id": "A", "lux": 0})).unwrap();
+        let illuminance = IlluminanceUplink::from_uplink(&uplink).unwrap();
+        assert_eq!((illuminance.lux, illuminance.battery_mv, illuminance.sampled_at), (0.0, None, None));
+
+        assert_round_trip::<IlluminanceUplink>(json!({"lux": 120.5, "battery_mv": 2900}));
+    }
+
+    #[test]
+    fn test_illuminance_uplink_rejects_other_payloads() {
+        let uplink: UplinkResult =
+            serde_json::from_value(json!({"sensor_id": "0122", "module_id": "A", "lux": 10})).unwrap();
+        assert!(matches!(
+            IlluminanceUplink::from_uplink(&uplink),
+            Err(BjigError::InvalidParameter(_))
+        ));
+
+        let uplink: UplinkResult =
+            serde_json::from_value(json!({"sensor_id": "0121", "module_id": "A", "temperature": 21.5})).unwrap();
+        assert!(matches!(
+            IlluminanceUplink::from_uplink(&uplink),
+            Err(BjigError::ResponseDecode { .. })
+        ));
+    }
+}
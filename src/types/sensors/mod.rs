@@ -0,0 +1,5 @@
+//! Typed uplink payloads of specific sensors
+
+pub mod illuminance;
+
+pub use illuminance::IlluminanceUplink;
@@ -0,0 +1,72 @@
+//! Helpers for decoding bjig responses
+
+use base64::Engine;
+use serde_json::Value;
+
+use crate::types::{BjigError, Result};
+
+/// Decode the base64 string at `pointer` into raw bytes
+///
+/// For commands returning binary payloads (e.g. DFU data) as a base64
+/// field of the JSON response. `pointer` is a JSON pointer (RFC 6901) such
+/// as `"/data"`. Standard base64 with padding is expected.
+///
+/// # Errors
+/// Returns `BjigError::SchemaMismatch` if the field is missing or not a
+/// string, and `BjigError::Base64Decode` if it is not valid base64.
+///
+/// # Examples
+///
+/// ```
+/// use bjig_controller::util::decode_base64_field;
+/// use serde_json::json;
+///
+/// let response = json!({"result": "success", "data": "AQID"});
+/// assert_eq!(decode_base64_field(&response, "/data")?, vec![1, 2, 3]);
+/// # Ok::<(), bjig_controller::BjigError>(())
+/// ```
+pub fn decode_base64_field(value: &Value, pointer: &str) -> Result<Vec<u8>> {
+    let encoded = value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .ok_or_else(|| BjigError::SchemaMismatch {
+            field: pointer.to_string(),
+            expected: "base64 string".to_string(),
+        })?;
+
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|source| BjigError::Base64Decode {
+            field: pointer.to_string(),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_base64_field() {
+        let response = json!({"result": "success", "flash": {"data": "3q2+7w=="}, "size": 4});
+        assert_eq!(
+            decode_base64_field(&response, "/flash/data").unwrap(),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(decode_base64_field(&json!({"data": ""}), "/data").unwrap(), Vec::<u8>::new());
+
+        assert!(matches!(
+            decode_base64_field(&json!({"data": "not base64!"}), "/data"),
+            Err(BjigError::Base64Decode { field, .. }) if field == "/data"
+        ));
+        assert!(matches!(
+            decode_base64_field(&response, "/size"),
+            Err(BjigError::SchemaMismatch { field, .. }) if field == "/size"
+        ));
+        assert!(matches!(
+            decode_base64_field(&response, "/missing"),
+            Err(BjigError::SchemaMismatch { .. })
+        ));
+    }
+}